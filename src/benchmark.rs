@@ -0,0 +1,534 @@
+//! Benchmarking of planners over random start and goal pairs, in the spirit of the
+//! Nav2 planner benchmark in `test_maps/planner_benchmarking`.
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::maps::gridmap::Gridmap;
+use crate::maps::movingai::{self, Scenario};
+use crate::metrics;
+use crate::planners::planner_base::Planner;
+use crate::planners::planner_common::{MotionPlan, Neighborhood, PlanningBudget, PlanningError};
+use crate::{a_star, bfs, dfs, dijkstra, fmm, hpa_star, space_time_a_star};
+
+/// Number of cells drawn when looking for a valid start or goal before giving up
+const MAX_SAMPLES: usize = 10_000;
+
+/// Number of start and goal pairs drawn for each one kept before giving up
+const MAX_DRAWS_PER_PAIR: usize = 10;
+
+/// Settings of a benchmark run
+#[derive(Clone, Debug)]
+pub struct BenchmarkConfig {
+    /// Number of start and goal pairs that at least one planner finds a path between
+    pub num_pairs: usize,
+    /// Starts and goals are drawn from cells cheaper than this
+    pub max_cost: u8,
+    /// Number of cells along each edge of the map that starts and goals are not drawn from
+    pub side_buffer: u32,
+    /// Minimum straight line distance between a start and its goal
+    pub min_distance_m: f32,
+    pub seed: u64,
+    /// Limits on each plan, for planners that would otherwise take too long
+    pub budget: PlanningBudget,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> BenchmarkConfig {
+        BenchmarkConfig {
+            num_pairs: 100,
+            max_cost: 210,
+            side_buffer: 100,
+            min_distance_m: 3.0,
+            seed: 33,
+            budget: PlanningBudget::default(),
+        }
+    }
+}
+
+type PlanFn = Box<dyn FnMut((u32, u32), (u32, u32)) -> Result<MotionPlan, PlanningError>>;
+
+/// A planner set up once for a map, which then plans between each pair in turn
+pub struct BenchmarkPlanner {
+    pub name: String,
+    plan: PlanFn,
+}
+
+impl BenchmarkPlanner {
+    /// Set up a planner with its default parameters
+    pub fn new<P: Planner + 'static>(name: &str, gridmap: &Gridmap, budget: &PlanningBudget) -> BenchmarkPlanner {
+        let mut planner = P::new(gridmap);
+        planner.update_budget(budget);
+
+        BenchmarkPlanner::from_planner(name, planner)
+    }
+
+    /// Benchmark a planner that has already been configured
+    pub fn from_planner<P: Planner + 'static>(name: &str, mut planner: P) -> BenchmarkPlanner {
+        BenchmarkPlanner {
+            name: name.to_string(),
+            plan: Box::new(move |start, goal| {
+                planner.update_start_and_goal(start, goal);
+                planner.try_generate_plan()
+            }),
+        }
+    }
+
+    /// Plan between a start and goal, returning None if no path is found
+    pub fn plan(&mut self, start: (u32, u32), goal: (u32, u32)) -> Option<MotionPlan> {
+        (self.plan)(start, goal).ok().filter(|motion_plan| !motion_plan.path.is_empty())
+    }
+}
+
+/// Get every single agent grid planner. The slower ones can take long on large
+/// maps without a time limit in the budget.
+pub fn get_default_planners(gridmap: &Gridmap, budget: &PlanningBudget) -> Vec<BenchmarkPlanner> {
+    vec![
+        BenchmarkPlanner::new::<dijkstra::DijkstraPlanner>("Dijkstra", gridmap, budget),
+        BenchmarkPlanner::new::<a_star::AStarPlanner>("A*", gridmap, budget),
+        BenchmarkPlanner::new::<fmm::FMMPlanner>("FMM", gridmap, budget),
+        BenchmarkPlanner::new::<hpa_star::HPAStarPlanner>("HPA*", gridmap, budget),
+        BenchmarkPlanner::new::<bfs::BFSPlanner>("BFS", gridmap, budget),
+        BenchmarkPlanner::new::<dfs::DFSPlanner>("DFS", gridmap, budget),
+        BenchmarkPlanner::new::<space_time_a_star::SpaceTimeAStarPlanner>("Space-Time A*", gridmap, budget),
+    ]
+}
+
+/// Get the planners whose path lengths are comparable with the optimal lengths of MovingAI
+/// scenarios, which do not let diagonal moves cut the corners of blocked cells. Dijkstra and
+/// A* are set up without corner cutting, along with the 4 way planners. The other planners
+/// cut corners, so their paths could be shorter than optimal, and are left out.
+pub fn get_scenario_planners(gridmap: &Gridmap, budget: &PlanningBudget) -> Vec<BenchmarkPlanner> {
+    let mut dijkstra_planner = dijkstra::DijkstraPlanner::new(gridmap);
+    dijkstra_planner.update_budget(budget);
+    dijkstra_planner.update_neighborhood(Neighborhood::EightWayNoCornerCutting);
+
+    let mut a_star_planner = a_star::AStarPlanner::new(gridmap);
+    a_star_planner.update_budget(budget);
+    a_star_planner.update_neighborhood(Neighborhood::EightWayNoCornerCutting);
+
+    vec![
+        BenchmarkPlanner::from_planner("Dijkstra", dijkstra_planner),
+        BenchmarkPlanner::from_planner("A*", a_star_planner),
+        BenchmarkPlanner::new::<fmm::FMMPlanner>("FMM", gridmap, budget),
+        BenchmarkPlanner::new::<space_time_a_star::SpaceTimeAStarPlanner>("Space-Time A*", gridmap, budget),
+    ]
+}
+
+/// Result of one planner on one start and goal pair
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkResult {
+    pub planner: String,
+    /// Index of the start and goal pair
+    pub pair: usize,
+    pub start: (u32, u32),
+    pub goal: (u32, u32),
+    pub success: bool,
+    pub time: Duration,
+    pub path_length_m: f32,
+    /// Sum of the lengths in meters of the second differences along the path, zero for a straight line
+    pub smoothness: f32,
+    pub nodes_expanded: usize,
+    pub average_cost: f32,
+    pub max_cost: u8,
+}
+
+/// Averages of the results of a planner over the pairs it found a path for
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkSummary {
+    pub planner: String,
+    /// Fraction of pairs a path was found for
+    pub success_rate: f32,
+    pub time: Duration,
+    pub path_length_m: f32,
+    pub smoothness: f32,
+    pub nodes_expanded: f32,
+    pub average_cost: f32,
+    pub max_cost: f32,
+}
+
+/// Draw a random start, away from the sides of the map and cheaper than the maximum cost.
+/// Returns None if no such cell is drawn after many attempts.
+pub fn get_random_start<R: Rng>(gridmap: &Gridmap, config: &BenchmarkConfig, rng: &mut R) -> Option<(u32, u32)> {
+    get_random_cell(gridmap, config, rng, |_| true)
+}
+
+/// Draw a random goal like a start, and at least the minimum distance from the start
+pub fn get_random_goal<R: Rng>(
+    gridmap: &Gridmap,
+    start: (u32, u32),
+    config: &BenchmarkConfig,
+    rng: &mut R,
+) -> Option<(u32, u32)> {
+    let min_distance = config.min_distance_m / gridmap.get_resolution();
+
+    get_random_cell(gridmap, config, rng, |cell| {
+        let (dx, dy) = (cell.0 as f32 - start.0 as f32, cell.1 as f32 - start.1 as f32);
+        dx.hypot(dy) > min_distance
+    })
+}
+
+fn get_random_cell<R: Rng>(
+    gridmap: &Gridmap,
+    config: &BenchmarkConfig,
+    rng: &mut R,
+    is_valid: impl Fn((u32, u32)) -> bool,
+) -> Option<(u32, u32)> {
+    let (width, height) = (gridmap.get_width(), gridmap.get_height());
+    if 2 * config.side_buffer >= width || 2 * config.side_buffer >= height {
+        return None;
+    }
+
+    (0..MAX_SAMPLES)
+        .map(|_| {
+            (
+                rng.gen_range(config.side_buffer..width - config.side_buffer),
+                rng.gen_range(config.side_buffer..height - config.side_buffer),
+            )
+        })
+        .find(|cell| gridmap.get_val_xy(*cell) < config.max_cost && gridmap.xy_is_traversable(*cell) && is_valid(*cell))
+}
+
+/// Run every planner between random start and goal pairs. Pairs that no planner finds
+/// a path for are drawn again, as they say nothing about the planners, while the
+/// failures of some planners are kept. Stops early if no valid start or goal is found,
+/// or too many pairs have no path.
+pub fn run_benchmark(
+    gridmap: &Gridmap,
+    planners: &mut [BenchmarkPlanner],
+    config: &BenchmarkConfig,
+) -> Vec<BenchmarkResult> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut results: Vec<BenchmarkResult> = Vec::new();
+    let mut pair = 0;
+
+    for _ in 0..config.num_pairs * MAX_DRAWS_PER_PAIR {
+        if pair == config.num_pairs {
+            break;
+        }
+
+        let Some(start) = get_random_start(gridmap, config, &mut rng) else {
+            break;
+        };
+        let Some(goal) = get_random_goal(gridmap, start, config, &mut rng) else {
+            break;
+        };
+
+        let pair_results: Vec<BenchmarkResult> = planners
+            .iter_mut()
+            .map(|planner| {
+                let motion_plan = planner.plan(start, goal);
+                get_result(gridmap, &planner.name, pair, (start, goal), motion_plan.as_ref())
+            })
+            .collect();
+
+        if pair_results.iter().any(|result| result.success) {
+            results.extend(pair_results);
+            pair += 1;
+        }
+    }
+
+    results
+}
+
+fn get_result(
+    gridmap: &Gridmap,
+    planner: &str,
+    pair: usize,
+    (start, goal): ((u32, u32), (u32, u32)),
+    motion_plan: Option<&MotionPlan>,
+) -> BenchmarkResult {
+    let mut result = BenchmarkResult {
+        planner: planner.to_string(),
+        pair,
+        start,
+        goal,
+        success: motion_plan.is_some(),
+        time: Duration::ZERO,
+        path_length_m: 0.0,
+        smoothness: 0.0,
+        nodes_expanded: 0,
+        average_cost: 0.0,
+        max_cost: 0,
+    };
+
+    if let Some(motion_plan) = motion_plan {
+        let costs: Vec<u8> = motion_plan.path.iter().map(|cell| gridmap.get_val_xy(*cell)).collect();
+
+        result.time = motion_plan.stats.elapsed_time;
+        result.path_length_m = motion_plan.stats.path_length_m;
+        result.smoothness = metrics::get_smoothness(&metrics::get_points(&motion_plan.path), gridmap.get_resolution());
+        result.nodes_expanded = motion_plan.stats.nodes_expanded;
+        result.average_cost = costs.iter().map(|cost| *cost as f32).sum::<f32>() / costs.len() as f32;
+        result.max_cost = costs.iter().copied().max().unwrap_or(0);
+    }
+
+    result
+}
+
+/// Average the results of each planner, in the order the planners first appear
+pub fn summarize(results: &[BenchmarkResult]) -> Vec<BenchmarkSummary> {
+    get_planner_names(results.iter().map(|result| result.planner.as_str()))
+        .into_iter()
+        .map(|planner| {
+            let runs: Vec<&BenchmarkResult> = results.iter().filter(|result| result.planner == planner).collect();
+            let successes: Vec<&BenchmarkResult> = runs.iter().copied().filter(|result| result.success).collect();
+            let count = successes.len().max(1) as f32;
+            let average = |get_value: fn(&BenchmarkResult) -> f32| {
+                successes.iter().map(|result| get_value(result)).sum::<f32>() / count
+            };
+
+            BenchmarkSummary {
+                planner: planner.to_string(),
+                success_rate: successes.len() as f32 / runs.len() as f32,
+                time: successes.iter().map(|result| result.time).sum::<Duration>().div_f32(count),
+                path_length_m: average(|result| result.path_length_m),
+                smoothness: average(|result| result.smoothness),
+                nodes_expanded: average(|result| result.nodes_expanded as f32),
+                average_cost: average(|result| result.average_cost),
+                max_cost: average(|result| result.max_cost as f32),
+            }
+        })
+        .collect()
+}
+
+/// Get the distinct planner names in the order they first appear
+fn get_planner_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut planners: Vec<&str> = Vec::new();
+    for name in names {
+        if !planners.contains(&name) {
+            planners.push(name);
+        }
+    }
+    planners
+}
+
+/// Get the summaries as a table with aligned columns
+pub fn get_summary_table(summaries: &[BenchmarkSummary]) -> String {
+    let header = [
+        "Planner",
+        "Success rate",
+        "Average time (s)",
+        "Average path length (m)",
+        "Average smoothness",
+        "Average expansions",
+        "Average cost",
+        "Max cost",
+    ];
+    let rows: Vec<Vec<String>> = summaries
+        .iter()
+        .map(|summary| {
+            vec![
+                summary.planner.clone(),
+                format!("{:.2}", summary.success_rate),
+                format!("{:.6}", summary.time.as_secs_f64()),
+                format!("{:.3}", summary.path_length_m),
+                format!("{:.3}", summary.smoothness),
+                format!("{:.1}", summary.nodes_expanded),
+                format!("{:.2}", summary.average_cost),
+                format!("{:.2}", summary.max_cost),
+            ]
+        })
+        .collect();
+
+    format_table(&header, &rows)
+}
+
+/// Format rows under a header and separator, with columns aligned to their widest cell
+fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|col| rows.iter().map(|row| row[col].len()).fold(header[col].len(), usize::max))
+        .collect();
+    let format_row = |cells: &[&str]| -> String {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        cells.join("  ").trim_end().to_string() + "\n"
+    };
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+
+    let mut table = format_row(header);
+    table.push_str(&format_row(&separator.iter().map(String::as_str).collect::<Vec<&str>>()));
+    for row in rows {
+        table.push_str(&format_row(&row.iter().map(String::as_str).collect::<Vec<&str>>()));
+    }
+
+    table
+}
+
+/// Get the results as CSV, one row per planner and pair
+pub fn get_results_csv(results: &[BenchmarkResult]) -> String {
+    let mut csv = String::from(
+        "planner,pair,start_x,start_y,goal_x,goal_y,success,time_s,path_length_m,smoothness,nodes_expanded,average_cost,max_cost\n",
+    );
+
+    for result in results {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            result.planner,
+            result.pair,
+            result.start.0,
+            result.start.1,
+            result.goal.0,
+            result.goal.1,
+            result.success,
+            result.time.as_secs_f64(),
+            result.path_length_m,
+            result.smoothness,
+            result.nodes_expanded,
+            result.average_cost,
+            result.max_cost
+        ));
+    }
+
+    csv
+}
+
+/// Get the results as a JSON array of objects
+pub fn get_results_json(results: &[BenchmarkResult]) -> String {
+    let objects: Vec<String> = results
+        .iter()
+        .map(|result| {
+            format!(
+                "  {{\"planner\": \"{}\", \"pair\": {}, \"start\": [{}, {}], \"goal\": [{}, {}], \"success\": {}, \
+                 \"time_s\": {}, \"path_length_m\": {}, \"smoothness\": {}, \"nodes_expanded\": {}, \
+                 \"average_cost\": {}, \"max_cost\": {}}}",
+                escape_json(&result.planner),
+                result.pair,
+                result.start.0,
+                result.start.1,
+                result.goal.0,
+                result.goal.1,
+                result.success,
+                result.time.as_secs_f64(),
+                result.path_length_m,
+                result.smoothness,
+                result.nodes_expanded,
+                result.average_cost,
+                result.max_cost
+            )
+        })
+        .collect();
+
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
+
+/// Escape quotes, backslashes and control characters for use within a JSON string
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            character if character.is_control() => escaped.push_str(&format!("\\u{:04x}", character as u32)),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Result of one planner on one query of a MovingAI scenario
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioResult {
+    pub planner: String,
+    /// Index of the query in the scenario
+    pub scenario: usize,
+    pub optimal_length: f64,
+    /// Octile length of the path in cells, None if no path was found
+    pub path_length: Option<f64>,
+    pub time: Duration,
+}
+
+impl ScenarioResult {
+    /// Ratio of the path length to the optimal length, None if no path was found. The ratio is
+    /// only meaningful for planners that do not cut corners, like those of `get_scenario_planners`.
+    /// When the start is the goal, an empty path is optimal and any other has no ratio.
+    pub fn get_suboptimality(&self) -> Option<f64> {
+        let path_length = self.path_length?;
+
+        if self.optimal_length > 0.0 {
+            Some(path_length / self.optimal_length)
+        } else {
+            (path_length == 0.0).then_some(1.0)
+        }
+    }
+}
+
+/// Run every planner on every query of a scenario, on the map the scenario refers to
+pub fn run_scenarios(planners: &mut [BenchmarkPlanner], scenarios: &[Scenario]) -> Vec<ScenarioResult> {
+    let mut results: Vec<ScenarioResult> = Vec::new();
+
+    for (idx, scenario) in scenarios.iter().enumerate() {
+        for planner in planners.iter_mut() {
+            let motion_plan = planner.plan(scenario.start, scenario.goal);
+
+            results.push(ScenarioResult {
+                planner: planner.name.clone(),
+                scenario: idx,
+                optimal_length: scenario.optimal_length,
+                path_length: motion_plan.as_ref().map(|motion_plan| movingai::get_octile_length(&motion_plan.path)),
+                time: motion_plan.map_or(Duration::ZERO, |motion_plan| motion_plan.stats.elapsed_time),
+            });
+        }
+    }
+
+    results
+}
+
+/// Get the share of queries each planner solved, and how far its paths were from optimal,
+/// as a table with aligned columns
+pub fn get_scenario_table(results: &[ScenarioResult]) -> String {
+    let header = [
+        "Planner",
+        "Solved",
+        "Average suboptimality",
+        "Max suboptimality",
+        "Average time (s)",
+    ];
+    let rows: Vec<Vec<String>> = get_planner_names(results.iter().map(|result| result.planner.as_str()))
+        .into_iter()
+        .map(|planner| {
+            let runs: Vec<&ScenarioResult> = results.iter().filter(|result| result.planner == planner).collect();
+            let suboptimalities: Vec<f64> = runs.iter().filter_map(|result| result.get_suboptimality()).collect();
+            let num_solved = runs.iter().filter(|result| result.path_length.is_some()).count();
+            let count = suboptimalities.len().max(1);
+
+            vec![
+                planner.to_string(),
+                format!("{}/{}", num_solved, runs.len()),
+                format!("{:.4}", suboptimalities.iter().sum::<f64>() / count as f64),
+                format!("{:.4}", suboptimalities.iter().copied().fold(0.0, f64::max)),
+                format!(
+                    "{:.6}",
+                    runs.iter().map(|result| result.time).sum::<Duration>().as_secs_f64() / num_solved.max(1) as f64
+                ),
+            ]
+        })
+        .collect();
+
+    format_table(&header, &rows)
+}
+
+/// Get the scenario results as CSV, one row per planner and query
+pub fn get_scenario_csv(results: &[ScenarioResult]) -> String {
+    let mut csv = String::from("planner,scenario,optimal_length,path_length,suboptimality,time_s\n");
+
+    for result in results {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            result.planner,
+            result.scenario,
+            result.optimal_length,
+            result.path_length.map_or(String::new(), |length| length.to_string()),
+            result.get_suboptimality().map_or(String::new(), |ratio| ratio.to_string()),
+            result.time.as_secs_f64()
+        ));
+    }
+
+    csv
+}
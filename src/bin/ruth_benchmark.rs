@@ -0,0 +1,144 @@
+//! Benchmark runner comparing the planners over ROS map server maps and MovingAI scenarios
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use ruth_planner::benchmark::{self, BenchmarkConfig};
+use ruth_planner::maps::{builder, movingai};
+
+const DEFAULT_MAPS: [&str; 3] = [
+    "test_maps/planner_benchmarking/100by100_10.yaml",
+    "test_maps/planner_benchmarking/100by100_15.yaml",
+    "test_maps/planner_benchmarking/100by100_20.yaml",
+];
+
+const USAGE: &str = "\
+Usage: ruth_benchmark [OPTIONS] [MAP.yaml | SCENARIO.scen]...
+
+Runs every planner between seeded random start and goal pairs on each map, the
+100by100 benchmark maps by default, and writes the results and a summary table.
+MovingAI scenarios are run on their map, found beside the scenario file, and
+their path lengths are compared with the optimal lengths of the scenario, by
+the planners that do not cut corners.
+
+Options:
+  --pairs <N>              Number of start and goal pairs per map (default 100)
+  --seed <N>               Seed of the random pairs (default 33)
+  --max-cost <N>           Draw starts and goals from cells cheaper than this (default 210)
+  --side-buffer <N>        Cells along each edge of the map to keep starts and goals out of (default 100)
+  --min-distance <M>       Minimum distance in meters between a start and its goal (default 3.0)
+  --time-limit-ms <MS>     Stop each plan after this many milliseconds
+  --output-dir <DIR>       Directory for the results of each map (default benchmark_results)
+  -h, --help               Print this help";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match parse_args(&args).and_then(|(config, maps, output_dir)| run(&config, &maps, &output_dir)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(config: &BenchmarkConfig, maps: &[String], output_dir: &str) -> Result<(), String> {
+    fs::create_dir_all(output_dir).map_err(|error| format!("could not create '{}': {}", output_dir, error))?;
+
+    for map in maps {
+        if map.ends_with(".scen") {
+            run_scenarios(config, map, output_dir)?;
+            continue;
+        }
+
+        let gridmap = builder::build_gridmap_from_yaml(map).ok_or(format!("could not load map '{}'", map))?;
+        let mut planners = benchmark::get_default_planners(&gridmap, &config.budget);
+
+        let results = benchmark::run_benchmark(&gridmap, &mut planners, config);
+        let table = benchmark::get_summary_table(&benchmark::summarize(&results));
+
+        let name = Path::new(map).file_stem().unwrap_or_default().to_string_lossy();
+        let output_path = Path::new(output_dir).join(name.as_ref());
+        let outputs = [
+            ("csv", benchmark::get_results_csv(&results)),
+            ("json", benchmark::get_results_json(&results)),
+            ("txt", table.clone()),
+        ];
+        for (extension, contents) in outputs {
+            let file_path = output_path.with_extension(extension);
+            fs::write(&file_path, contents)
+                .map_err(|error| format!("could not write '{}': {}", file_path.display(), error))?;
+        }
+
+        println!("{} ({} pairs)\n{}", map, results.len() / planners.len().max(1), table);
+    }
+
+    Ok(())
+}
+
+/// Run the queries of a MovingAI scenario file and write how close each planner came to optimal
+fn run_scenarios(config: &BenchmarkConfig, file_path: &str, output_dir: &str) -> Result<(), String> {
+    let scenarios = movingai::read_scenarios(file_path).ok_or(format!("could not read scenarios '{}'", file_path))?;
+    let Some(map_name) = scenarios.first().and_then(|scenario| Path::new(&scenario.map).file_name()) else {
+        return Err(format!("no scenarios in '{}'", file_path));
+    };
+
+    let map_path = Path::new(file_path).with_file_name(map_name);
+    let gridmap = movingai::build_gridmap_from_map(&map_path.to_string_lossy())
+        .ok_or(format!("could not load map '{}'", map_path.display()))?;
+    let mut planners = benchmark::get_scenario_planners(&gridmap, &config.budget);
+
+    let results = benchmark::run_scenarios(&mut planners, &scenarios);
+    let table = benchmark::get_scenario_table(&results);
+
+    let name = Path::new(file_path).file_name().unwrap_or_default().to_string_lossy();
+    let output_path = Path::new(output_dir).join(format!("{}.csv", name));
+    fs::write(&output_path, benchmark::get_scenario_csv(&results))
+        .map_err(|error| format!("could not write '{}': {}", output_path.display(), error))?;
+
+    println!("{} ({} queries)\n{}", file_path, scenarios.len(), table);
+
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<(BenchmarkConfig, Vec<String>, String), String> {
+    let mut config = BenchmarkConfig::default();
+    let mut maps: Vec<String> = Vec::new();
+    let mut output_dir = "benchmark_results".to_string();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut get_value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--pairs" => config.num_pairs = parse_value(arg, get_value()?)?,
+            "--seed" => config.seed = parse_value(arg, get_value()?)?,
+            "--max-cost" => config.max_cost = parse_value(arg, get_value()?)?,
+            "--side-buffer" => config.side_buffer = parse_value(arg, get_value()?)?,
+            "--min-distance" => config.min_distance_m = parse_value(arg, get_value()?)?,
+            "--time-limit-ms" => {
+                config.budget.time_limit = Some(Duration::from_millis(parse_value(arg, get_value()?)?))
+            }
+            "--output-dir" => output_dir = get_value()?.clone(),
+            _ if arg.starts_with("--") => return Err(format!("unknown argument '{}'", arg)),
+            _ => maps.push(arg.clone()),
+        }
+    }
+
+    if maps.is_empty() {
+        maps = DEFAULT_MAPS.iter().map(|map| map.to_string()).collect();
+    }
+
+    Ok((config, maps, output_dir))
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value '{}' for {}", value, arg))
+}
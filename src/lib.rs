@@ -10,7 +10,18 @@ pub use crate::planners::{
   dijkstra,
   a_star,
   rrt,
+  fmm,
+  hpa_star,
+  tour,
+  coverage,
+  space_time_a_star,
+  cbs,
+  cooperative_a_star,
+  validation,
 };
 
+pub mod benchmark;
 pub mod maps;
-pub mod planners;
\ No newline at end of file
+pub mod metrics;
+pub mod planners;
+pub mod post_processing;
\ No newline at end of file
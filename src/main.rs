@@ -1,24 +1,323 @@
-// use ruth_planner::maps::builder;
-// use ruth_planner::planners;
-
-// const O: u8 = 0;// freespace
-// const X: u8 = 255;// occupied
-
-// pub fn create_maze_0_arr_map() -> Vec<Vec<u8>> {
-//   return vec![
-//     vec![O, O, O, X, O, X, O, O, X, O],
-//     vec![O, X, O, O, O, O, O, O, X, O],
-//     vec![O, X, X, X, X, X, X, O, X, O],
-//     vec![O, O, O, O, X, O, X, O, X, O],
-//     vec![O, O, O, O, O, O, X, O, O, O],
-//     vec![X, X, X, O, O, O, X, X, X, X],
-//     vec![O, O, O, O, X, O, X, O, O, O],
-//     vec![O, O, X, O, X, O, X, O, O, O],
-//     vec![X, X, X, O, X, O, X, O, O, O],
-//     vec![O, O, O, O, X, O, O, O, O, O],
-//   ];
-// }
-
-fn main() {
+//! Command-line tool that plans a path over a map with any of the planners
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
 
+use ruth_planner::maps::builder::{self, MapMetadata};
+use ruth_planner::maps::render;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::{MotionPlan, PlanningBudget, SearchStats};
+use ruth_planner::{a_star, bfs, dfs, dijkstra, fmm, hpa_star, space_time_a_star, Gridmap};
+
+const USAGE: &str = "\
+Usage: ruth_planner --map <FILE> --start <X,Y> --goal <X,Y> [OPTIONS]
+
+Plans a path over a PGM map, or a ROS map server YAML file and its image.
+
+Options:
+  --map <FILE>             Map to plan over (.pgm or .yaml)
+  --start <X,Y>            Start position, in cells unless --meters is given
+  --goal <X,Y>             Goal position, in cells unless --meters is given
+  --meters                 Read positions and the goal tolerance in meters, relative to the map origin
+  --planner <NAME>         a_star (default), dijkstra, bfs, dfs, fmm, hpa_star or space_time_a_star
+  --goal-tolerance <R>     Accept any cell within this radius of the goal
+  --max-expansions <N>     Stop planning after expanding this many cells
+  --time-limit-ms <MS>     Stop planning after this many milliseconds
+  --cluster-size <N>       Cells along each side of an HPA* cluster
+  --cost-factor <F>        How strongly cell costs slow down the FMM front
+  --output <FILE>          Write the path from start to goal as CSV, or JSON if the file ends in .json
+  --plot <FILE>            Plot the plan as an image, or SVG if the file ends in .svg
+  --render                 Print the plan as text
+  -h, --help               Print this help
+
+Exits with 0 when a path is found, 2 when none is found and 1 on any other error.";
+
+/// Settings given on the command line
+#[derive(Debug, Default)]
+struct Args {
+    map: String,
+    start: (f32, f32),
+    goal: (f32, f32),
+    meters: bool,
+    planner: String,
+    goal_tolerance: Option<f32>,
+    budget: PlanningBudget,
+    cluster_size: Option<u32>,
+    cost_factor: Option<f32>,
+    output: Option<String>,
+    plot: Option<String>,
+    render: bool,
+}
+
+/// A map and where it lies in the world
+struct LoadedMap {
+    gridmap: Gridmap,
+    metadata: MapMetadata,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match parse_args(&args).and_then(|args| run(&args)) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(2),
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Plan and write out the results, returning whether a path was found
+fn run(args: &Args) -> Result<bool, String> {
+    let map = load_map(&args.map)?;
+    let start_cell = get_cell(&map, args.start, args.meters)?;
+    let goal_cell = get_cell(&map, args.goal, args.meters)?;
+
+    // Plotting cannot report a failure to write, so check the directory beforehand
+    if let Some(plot) = &args.plot {
+        let directory = Path::new(plot).parent().filter(|directory| !directory.as_os_str().is_empty());
+        if directory.is_some_and(|directory| !directory.is_dir()) {
+            return Err(format!("could not write '{}': no such directory", plot));
+        }
+    }
+
+    let motion_plan = match args.planner.as_str() {
+        "a_star" => plan::<a_star::AStarPlanner>(&map.gridmap, start_cell, goal_cell, args, |_| true)?,
+        "dijkstra" => plan::<dijkstra::DijkstraPlanner>(&map.gridmap, start_cell, goal_cell, args, |_| true)?,
+        "bfs" => plan::<bfs::BFSPlanner>(&map.gridmap, start_cell, goal_cell, args, |_| true)?,
+        "dfs" => plan::<dfs::DFSPlanner>(&map.gridmap, start_cell, goal_cell, args, |_| true)?,
+        "space_time_a_star" => {
+            plan::<space_time_a_star::SpaceTimeAStarPlanner>(&map.gridmap, start_cell, goal_cell, args, |_| true)?
+        }
+        "fmm" => plan(&map.gridmap, start_cell, goal_cell, args, |planner: &mut fmm::FMMPlanner| {
+            args.cost_factor.is_none_or(|cost_factor| planner.update_cost_factor(cost_factor))
+        })?,
+        "hpa_star" => plan(&map.gridmap, start_cell, goal_cell, args, |planner: &mut hpa_star::HPAStarPlanner| {
+            args.cluster_size.is_none_or(|cluster_size| planner.update_cluster_size(cluster_size))
+        })?,
+        planner => return Err(format!("unknown planner '{}'", planner)),
+    };
+
+    // Planners return the path from goal to start
+    let path: Vec<(u32, u32)> = motion_plan.path.iter().rev().copied().collect();
+
+    print_stats(&motion_plan.stats);
+
+    if let Some(output) = &args.output {
+        let contents = if output.ends_with(".json") {
+            get_path_json(&map, &path, &motion_plan.stats)
+        } else {
+            get_path_csv(&map, &path)
+        };
+        fs::write(output, contents).map_err(|error| format!("could not write '{}': {}", output, error))?;
+    }
+
+    if let Some(plot) = &args.plot {
+        builder::plot_gridmap(&map.gridmap, &start_cell, &goal_cell, &motion_plan, plot, &2);
+    }
+
+    if args.render {
+        render::print_plan(&map.gridmap, &start_cell, &goal_cell, &motion_plan, &render::RenderOptions::default());
+    }
+
+    if path.is_empty() {
+        eprintln!("no path found from {:?} to {:?}", start_cell, goal_cell);
+    }
+
+    Ok(!path.is_empty())
+}
+
+/// Set up a planner with the common settings and any of its own, then plan.
+/// Interrupted plans are reported, and return their partial plan with an empty path.
+fn plan<P: Planner>(
+    gridmap: &Gridmap,
+    start_cell: (u32, u32),
+    goal_cell: (u32, u32),
+    args: &Args,
+    configure: impl FnOnce(&mut P) -> bool,
+) -> Result<MotionPlan, String> {
+    let mut planner = P::new(gridmap);
+
+    if !configure(&mut planner) {
+        return Err(format!("invalid parameters for planner '{}'", args.planner));
+    }
+
+    planner.update_start_and_goal(start_cell, goal_cell);
+    planner.update_budget(&args.budget);
+
+    if let Some(tolerance) = args.goal_tolerance {
+        let updated = if args.meters {
+            planner.update_goal_tolerance_m(tolerance)
+        } else {
+            planner.update_goal_tolerance(tolerance)
+        };
+        if !updated {
+            return Err(format!("invalid goal tolerance {}", tolerance));
+        }
+    }
+
+    Ok(planner.try_generate_plan().unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        MotionPlan {
+            path: Vec::new(),
+            ..*error.partial_plan
+        }
+    }))
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args {
+        planner: "a_star".to_string(),
+        ..Default::default()
+    };
+    let (mut has_start, mut has_goal) = (false, false);
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut get_value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--map" => parsed.map = get_value()?.clone(),
+            "--start" => {
+                parsed.start = parse_position(get_value()?)?;
+                has_start = true;
+            }
+            "--goal" => {
+                parsed.goal = parse_position(get_value()?)?;
+                has_goal = true;
+            }
+            "--meters" => parsed.meters = true,
+            "--planner" => parsed.planner = get_value()?.clone(),
+            "--goal-tolerance" => parsed.goal_tolerance = Some(parse_value(arg, get_value()?)?),
+            "--max-expansions" => parsed.budget.max_expansions = Some(parse_value(arg, get_value()?)?),
+            "--time-limit-ms" => {
+                parsed.budget.time_limit = Some(Duration::from_millis(parse_value(arg, get_value()?)?))
+            }
+            "--cluster-size" => parsed.cluster_size = Some(parse_value(arg, get_value()?)?),
+            "--cost-factor" => parsed.cost_factor = Some(parse_value(arg, get_value()?)?),
+            "--output" => parsed.output = Some(get_value()?.clone()),
+            "--plot" => parsed.plot = Some(get_value()?.clone()),
+            "--render" => parsed.render = true,
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+
+    if parsed.map.is_empty() || !has_start || !has_goal {
+        return Err("--map, --start and --goal are required, see --help".to_string());
+    }
+
+    Ok(parsed)
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value '{}' for {}", value, arg))
+}
+
+/// Parse a position given as "x,y"
+fn parse_position(value: &str) -> Result<(f32, f32), String> {
+    let invalid = || format!("invalid position '{}', expected X,Y", value);
+    let (x, y) = value.split_once(',').ok_or_else(invalid)?;
+
+    Ok((
+        x.trim().parse().map_err(|_| invalid())?,
+        y.trim().parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// Load a map from a YAML file with its metadata, or from an image placed at the origin
+fn load_map(file_path: &str) -> Result<LoadedMap, String> {
+    if !Path::new(file_path).is_file() {
+        return Err(format!("map '{}' does not exist", file_path));
+    }
+
+    let is_yaml = file_path.ends_with(".yaml") || file_path.ends_with(".yml");
+    let (gridmap, metadata) = if is_yaml {
+        (builder::build_gridmap_from_yaml(file_path), builder::read_map_yaml(file_path))
+    } else {
+        (builder::build_gridmap_from_pgm(file_path), Some(MapMetadata::default()))
+    };
+
+    match (gridmap, metadata) {
+        (Some(gridmap), Some(metadata)) => Ok(LoadedMap { gridmap, metadata }),
+        _ => Err(format!("could not load map '{}'", file_path)),
+    }
+}
+
+/// Convert a position to a cell, from meters in the world if asked to
+fn get_cell(map: &LoadedMap, position: (f32, f32), meters: bool) -> Result<(u32, u32), String> {
+    let (x, y) = if meters {
+        let resolution = map.metadata.resolution;
+        let x = ((position.0 - map.metadata.origin.0) / resolution).floor();
+        let y_up = ((position.1 - map.metadata.origin.1) / resolution).floor();
+
+        // Cells are numbered from the top of the map
+        (x, map.gridmap.get_height() as f32 - 1.0 - y_up)
+    } else {
+        position
+    };
+
+    if x < 0.0 || y < 0.0 || x.fract() != 0.0 || y.fract() != 0.0 || !map.gridmap.xy_in_map((x as u32, y as u32)) {
+        return Err(format!("position {:?} is not a cell of the map", position));
+    }
+
+    Ok((x as u32, y as u32))
+}
+
+/// Position of a cell's centroid in meters in the world
+fn get_position(map: &LoadedMap, cell: (u32, u32)) -> (f32, f32) {
+    let (x, y) = map.gridmap.get_cell_centroid((cell.0, map.gridmap.flip_y_u32(cell.1)));
+
+    (x + map.metadata.origin.0, y + map.metadata.origin.1)
+}
+
+fn print_stats(stats: &SearchStats) {
+    println!("nodes_expanded: {}", stats.nodes_expanded);
+    println!("nodes_generated: {}", stats.nodes_generated);
+    println!("max_open_list_size: {}", stats.max_open_list_size);
+    println!("reopenings: {}", stats.reopenings);
+    println!("path_cost: {}", stats.path_cost);
+    println!("path_length_m: {}", stats.path_length_m);
+    println!("elapsed_time_ms: {}", stats.elapsed_time.as_secs_f64() * 1000.0);
+}
+
+fn get_path_csv(map: &LoadedMap, path: &[(u32, u32)]) -> String {
+    let mut csv = String::from("x,y,x_m,y_m\n");
+
+    for cell in path {
+        let (x_m, y_m) = get_position(map, *cell);
+        csv.push_str(&format!("{},{},{},{}\n", cell.0, cell.1, x_m, y_m));
+    }
+
+    csv
+}
+
+fn get_path_json(map: &LoadedMap, path: &[(u32, u32)], stats: &SearchStats) -> String {
+    let points: Vec<String> = path
+        .iter()
+        .map(|cell| {
+            let (x_m, y_m) = get_position(map, *cell);
+            format!("{{\"x\": {}, \"y\": {}, \"x_m\": {}, \"y_m\": {}}}", cell.0, cell.1, x_m, y_m)
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"path\": [{}],\n  \"stats\": {{\"nodes_expanded\": {}, \"nodes_generated\": {}, \
+         \"max_open_list_size\": {}, \"reopenings\": {}, \"path_cost\": {}, \"path_length_m\": {}, \
+         \"elapsed_time_ms\": {}}}\n}}\n",
+        points.join(", "),
+        stats.nodes_expanded,
+        stats.nodes_generated,
+        stats.max_open_list_size,
+        stats.reopenings,
+        stats.path_cost,
+        stats.path_length_m,
+        stats.elapsed_time.as_secs_f64() * 1000.0
+    )
 }
@@ -1,2 +1,5 @@
 pub mod builder;
+pub mod generators;
 pub mod gridmap;
+pub mod movingai;
+pub mod render;
@@ -1,34 +1,228 @@
 use std::collections::HashSet;
 use std::path::Path;
 
-use image::{GenericImageView, GrayImage};
+use image::GrayImage;
 use plotters::coord::types::RangedCoordf32;
-use plotters::{coord::Shift, prelude::*};
+use plotters::chart::SeriesAnno;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::colors::colormaps::ViridisRGB;
 
 use super::gridmap::*;
-use crate::planners::planner_common::MotionPlan;
+use crate::planners::planner_common::{MotionPlan, SearchTrace};
+
+/// Chart with axes in meters covering a gridmap, drawn on any plotters backend
+pub type GridmapChart<'a, DB> = ChartContext<'a, DB, Cartesian2d<RangedCoordf32, RangedCoordf32>>;
+
+/// Image size and colours of a plot
+#[derive(Clone, Debug)]
+pub struct PlotStyle {
+    /// Width of the image in pixels, the height following the aspect ratio of the map
+    pub img_width: u32,
+    pub start_color: RGBAColor,
+    pub goal_color: RGBAColor,
+    pub closed_list_color: RGBAColor,
+    pub path_color: RGBAColor,
+    /// Open list after the latest expansions of an animation
+    pub frontier_color: RGBAColor,
+    /// Latest expansions of an animation
+    pub expansion_color: RGBAColor,
+    pub show_legend: bool,
+}
+
+impl Default for PlotStyle {
+    fn default() -> PlotStyle {
+        PlotStyle {
+            img_width: 1280,
+            start_color: CYAN.to_rgba(),
+            goal_color: GREEN.to_rgba(),
+            closed_list_color: RGBAColor(255, 255, 0, 0.2),
+            path_color: BLUE.to_rgba(),
+            frontier_color: RGBAColor(255, 0, 0, 0.4),
+            expansion_color: RGBAColor(255, 128, 0, 0.8),
+            show_legend: false,
+        }
+    }
+}
+
+/// Settings of an animated plan
+#[derive(Clone, Debug)]
+pub struct AnimationOptions {
+    pub frames_per_second: u32,
+    /// Number of expansions added by each frame
+    pub cells_per_frame: usize,
+    /// Radius of an expanded cell in pixels
+    pub cell_size: u32,
+    /// Number of frames the final path is shown for before the animation loops
+    pub final_frames: u32,
+    /// Image width defaults to 480 pixels, as every frame is encoded separately
+    pub style: PlotStyle,
+}
 
-/// Builds a Gridmap struct from a PGM Image
+impl Default for AnimationOptions {
+    fn default() -> AnimationOptions {
+        AnimationOptions {
+            frames_per_second: 10,
+            cells_per_frame: 1,
+            cell_size: 3,
+            final_frames: 20,
+            style: PlotStyle {
+                img_width: 480,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Builds a Gridmap struct from a PGM Image, converting other images to grayscale.
+/// Returns None if the file cannot be read as an image.
 pub fn build_gridmap_from_pgm(file_path: &str) -> Option<Gridmap> {
-    let img = image::open(&Path::new(file_path)).unwrap();
+    let img = image::open(Path::new(file_path)).ok()?.to_luma8();
 
     let (width, height) = img.dimensions();
 
     let mut gridmap = Gridmap::new(width, height, 0.05);
 
-    let img_grayscale = img.as_luma8().unwrap();
-
     // 0 is black, 255 is white (free space)
-    for (x, y, pixel) in img_grayscale.enumerate_pixels() {
+    for (x, y, pixel) in img.enumerate_pixels() {
         gridmap.set_val_xy(255 - pixel.0[0], (x, y));
     }
 
     Some(gridmap)
 }
 
+/// How pixels of a map image are interpreted, as in the ROS map server
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MapMode {
+    /// Cells are free, lethal or unknown
+    #[default]
+    Trinary,
+    /// Cells between the thresholds get a cost that scales with their occupancy
+    Scale,
+    /// Cell values are the inverted pixel values, as with `build_gridmap_from_pgm`
+    Raw,
+}
+
+/// Metadata of a map, as read from a ROS map server YAML file
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapMetadata {
+    /// Path to the map image, relative to the YAML file
+    pub image: String,
+    pub mode: MapMode,
+    /// Resolution of the map in meter/cell
+    pub resolution: f32,
+    /// Position in meters of the bottom left corner of the map, and its yaw
+    pub origin: (f32, f32, f32),
+    /// Whether white rather than black pixels are occupied
+    pub negate: bool,
+    pub occupied_thresh: f32,
+    pub free_thresh: f32,
+}
+
+impl Default for MapMetadata {
+    fn default() -> MapMetadata {
+        MapMetadata {
+            image: String::new(),
+            mode: MapMode::Trinary,
+            resolution: 0.05,
+            origin: (0.0, 0.0, 0.0),
+            negate: false,
+            occupied_thresh: 0.65,
+            free_thresh: 0.196,
+        }
+    }
+}
+
+/// Reads the metadata of a map from a ROS map server YAML file. Returns None if the
+/// file cannot be read, or it has no image or resolution.
+pub fn read_map_yaml(file_path: &str) -> Option<MapMetadata> {
+    let contents = std::fs::read_to_string(file_path).ok()?;
+    let mut metadata = MapMetadata::default();
+    let (mut has_image, mut has_resolution) = (false, false);
+
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match key.trim() {
+            "image" => {
+                metadata.image = value.trim_matches(|c| c == '"' || c == '\'').to_string();
+                has_image = true;
+            }
+            "mode" => {
+                metadata.mode = match value {
+                    "trinary" => MapMode::Trinary,
+                    "scale" => MapMode::Scale,
+                    "raw" => MapMode::Raw,
+                    _ => return None,
+                }
+            }
+            "resolution" => {
+                metadata.resolution = value.parse().ok()?;
+                has_resolution = true;
+            }
+            "origin" => {
+                let values: Vec<f32> = value
+                    .trim_matches(|c| c == '[' || c == ']')
+                    .split(',')
+                    .map(|val| val.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .ok()?;
+                metadata.origin = (*values.first()?, *values.get(1)?, *values.get(2).unwrap_or(&0.0));
+            }
+            "negate" => metadata.negate = value != "0" && value != "false",
+            "occupied_thresh" => metadata.occupied_thresh = value.parse().ok()?,
+            "free_thresh" => metadata.free_thresh = value.parse().ok()?,
+            _ => {}
+        }
+    }
+
+    (has_image && has_resolution).then_some(metadata)
+}
+
+/// Builds a Gridmap struct from a ROS map server YAML file and the image it refers to
+pub fn build_gridmap_from_yaml(file_path: &str) -> Option<Gridmap> {
+    let metadata = read_map_yaml(file_path)?;
+    let image_path = Path::new(file_path).parent()?.join(&metadata.image);
+    let img = image::open(image_path).ok()?.to_luma8();
+
+    let (width, height) = img.dimensions();
+
+    let mut gridmap = Gridmap::new(width, height, metadata.resolution);
+
+    for (x, y, pixel) in img.enumerate_pixels() {
+        gridmap.set_val_xy(get_map_cell_value(&metadata, pixel.0[0]), (x, y));
+    }
+
+    Some(gridmap)
+}
+
+/// Cell value of a pixel of a map image
+fn get_map_cell_value(metadata: &MapMetadata, pixel: u8) -> u8 {
+    let pixel = if metadata.negate { 255 - pixel } else { pixel };
+    let occupancy = (255 - pixel) as f32 / 255.0;
+
+    if metadata.mode == MapMode::Raw {
+        return 255 - pixel;
+    }
+
+    if occupancy > metadata.occupied_thresh {
+        LETHAL_OBSTACLE
+    } else if occupancy < metadata.free_thresh {
+        FREE_SPACE
+    } else if metadata.mode == MapMode::Scale {
+        let ratio = (occupancy - metadata.free_thresh) / (metadata.occupied_thresh - metadata.free_thresh);
+        1 + (ratio * (INSCRIBED_INFLATED_OBSTACLE - 2) as f32) as u8
+    } else {
+        NO_INFORMATION
+    }
+}
+
 /// Builds a gridmap struct from a 2d vector of u8 values
-pub fn build_gridmap_from_2d_arr(arr_map: &Vec<Vec<u8>>) -> Option<Gridmap> {
-    if arr_map.len() == 0 || arr_map[0].len() == 0 {
+pub fn build_gridmap_from_2d_arr(arr_map: &[Vec<u8>]) -> Option<Gridmap> {
+    if arr_map.is_empty() || arr_map[0].is_empty() {
         return None;
     }
 
@@ -46,7 +240,7 @@ pub fn build_gridmap_from_2d_arr(arr_map: &Vec<Vec<u8>>) -> Option<Gridmap> {
 }
 
 /// Saves gridmap as PGM file
-pub fn save_gridmap(gridmap: &Gridmap, file_path: &str) -> () {
+pub fn save_gridmap(gridmap: &Gridmap, file_path: &str) {
     let (width, height) = (gridmap.get_width(), gridmap.get_height());
 
     let mut imgbuf = GrayImage::new(width, height);
@@ -58,6 +252,27 @@ pub fn save_gridmap(gridmap: &Gridmap, file_path: &str) -> () {
     imgbuf.save(&Path::new(file_path)).unwrap();
 }
 
+/// Draw onto an SVG backend if the file path ends in `.svg`, and a bitmap backend in the
+/// format of its extension otherwise, then write the plot to the file. A macro rather than
+/// a function, as the drawing is generic over the backend.
+macro_rules! with_backend {
+    ($file_path:expr, $img_size:expr, |$root:ident| $draw:expr) => {
+        if is_svg_path($file_path) {
+            let $root = SVGBackend::new($file_path, $img_size).into_drawing_area();
+            $draw;
+            $root.present()
+                .expect("Unable to write result to file, please make sure directory exists");
+        } else {
+            let $root = BitMapBackend::new($file_path, $img_size).into_drawing_area();
+            $draw;
+            $root.present()
+                .expect("Unable to write result to file, please make sure directory exists");
+        }
+    };
+}
+
+/// Plot a motion plan over the gridmap with the default style. Writes an SVG
+/// if the file path ends in `.svg`, and a bitmap in the format of its extension otherwise.
 pub fn plot_gridmap<'a>(
     gridmap: &'a Gridmap,
     start_cell: &'a (u32, u32),
@@ -65,22 +280,331 @@ pub fn plot_gridmap<'a>(
     motion_plan: &'a MotionPlan,
     file_path: &'a str,
     cell_size: &'a u32,
-) -> () {
+) {
+    plot_gridmap_with_style(
+        gridmap,
+        start_cell,
+        goal_cell,
+        motion_plan,
+        file_path,
+        cell_size,
+        &PlotStyle::default(),
+    );
+}
+
+/// Plot a motion plan over the gridmap like `plot_gridmap`, in a given style
+pub fn plot_gridmap_with_style(
+    gridmap: &Gridmap,
+    start_cell: &(u32, u32),
+    goal_cell: &(u32, u32),
+    motion_plan: &MotionPlan,
+    file_path: &str,
+    cell_size: &u32,
+    style: &PlotStyle,
+) {
+    let img_size = get_image_size(gridmap, style.img_width);
+
+    with_backend!(file_path, img_size, |root| {
+        draw_gridmap(&root, gridmap, start_cell, goal_cell, motion_plan, *cell_size, style)
+    });
+}
+
+/// Draw a motion plan over the gridmap onto a drawing area of any backend
+pub fn draw_gridmap<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    gridmap: &Gridmap,
+    start_cell: &(u32, u32),
+    goal_cell: &(u32, u32),
+    motion_plan: &MotionPlan,
+    cell_size: u32,
+    style: &PlotStyle,
+) {
+    let mut chart = build_chart(root, gridmap);
+
+    let start_goal_cell_size = cell_size * 2;
+    let path_size = cell_size / 2;
+
+    plot_obstacles(&mut chart, gridmap);
+    plot_start_and_goal(
+        &mut chart,
+        gridmap,
+        start_cell,
+        goal_cell,
+        start_goal_cell_size,
+        style,
+    );
+    plot_closed_list(&mut chart, gridmap, &motion_plan.closed_list, cell_size, style);
+    plot_path(&mut chart, gridmap, &motion_plan.path, path_size, style);
+
+    if style.show_legend {
+        draw_legend(&mut chart);
+    }
+}
+
+/// Plot several named motion plans over a single gridmap to compare them, each path in its
+/// own colour with a legend of path lengths and expansions. Writes an SVG if the file path
+/// ends in `.svg`, and a bitmap in the format of its extension otherwise.
+pub fn plot_comparison_overlay(
+    gridmap: &Gridmap,
+    start_cell: &(u32, u32),
+    goal_cell: &(u32, u32),
+    plans: &[(&str, &MotionPlan)],
+    file_path: &str,
+    cell_size: &u32,
+    style: &PlotStyle,
+) {
+    let img_size = get_image_size(gridmap, style.img_width);
+
+    with_backend!(file_path, img_size, |root| {
+        draw_comparison_overlay(&root, gridmap, start_cell, goal_cell, plans, *cell_size, style)
+    });
+}
+
+/// Plot several named motion plans side by side in a grid of gridmaps, each captioned
+/// with its path length and expansions. The style's image width is that of the whole grid.
+pub fn plot_comparison_grid(
+    gridmap: &Gridmap,
+    start_cell: &(u32, u32),
+    goal_cell: &(u32, u32),
+    plans: &[(&str, &MotionPlan)],
+    file_path: &str,
+    cell_size: &u32,
+    style: &PlotStyle,
+) {
+    let (rows, cols) = get_grid_shape(plans.len());
+    let (_, subplot_height) = get_image_size(gridmap, style.img_width / cols as u32);
+    let img_size = (style.img_width, (subplot_height + COMPARISON_CAPTION_SIZE) * rows as u32);
+
+    with_backend!(file_path, img_size, |root| {
+        draw_comparison_grid(&root, gridmap, start_cell, goal_cell, plans, *cell_size, style)
+    });
+}
+
+/// Draw several named motion plans over a single gridmap onto a drawing area of any backend
+pub fn draw_comparison_overlay<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    gridmap: &Gridmap,
+    start_cell: &(u32, u32),
+    goal_cell: &(u32, u32),
+    plans: &[(&str, &MotionPlan)],
+    cell_size: u32,
+    style: &PlotStyle,
+) {
+    let mut chart = build_chart(root, gridmap);
+
+    plot_obstacles(&mut chart, gridmap);
+    for (idx, (name, motion_plan)) in plans.iter().enumerate() {
+        let color = Palette99::pick(idx).to_rgba();
+
+        plot_line(&mut chart, gridmap, &motion_plan.path, cell_size / 2, color)
+            .label(get_comparison_label(name, motion_plan))
+            .legend(move |(x, y)| PathElement::new([(x - 10, y), (x + 10, y)], color.stroke_width(2)));
+    }
+    plot_start_and_goal(&mut chart, gridmap, start_cell, goal_cell, cell_size * 2, style);
+
+    draw_legend(&mut chart);
+}
+
+/// Draw several named motion plans in a grid of gridmaps onto a drawing area of any backend
+pub fn draw_comparison_grid<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    gridmap: &Gridmap,
+    start_cell: &(u32, u32),
+    goal_cell: &(u32, u32),
+    plans: &[(&str, &MotionPlan)],
+    cell_size: u32,
+    style: &PlotStyle,
+) {
+    root.fill(&WHITE).unwrap();
+
+    let subplots = root.split_evenly(get_grid_shape(plans.len()));
+    for ((name, motion_plan), subplot) in plans.iter().zip(&subplots) {
+        let subplot = subplot
+            .titled(&get_comparison_label(name, motion_plan), ("sans-serif", COMPARISON_CAPTION_SIZE))
+            .unwrap();
+        draw_gridmap(&subplot, gridmap, start_cell, goal_cell, motion_plan, cell_size, style);
+    }
+}
+
+/// Height in pixels of the caption above each plan of a comparison grid
+const COMPARISON_CAPTION_SIZE: u32 = 20;
+
+/// Get the rows and columns of the smallest near-square grid holding a number of subplots
+fn get_grid_shape(num_subplots: usize) -> (usize, usize) {
+    let cols = (1..).find(|cols| cols * cols >= num_subplots).unwrap();
+    (num_subplots.div_ceil(cols).max(1), cols)
+}
+
+/// Describe a plan by its name, path length and search effort
+fn get_comparison_label(name: &str, motion_plan: &MotionPlan) -> String {
+    format!(
+        "{}: {:.2} m, {} expansions",
+        name, motion_plan.stats.path_length_m, motion_plan.stats.nodes_expanded
+    )
+}
+
+/// Plot a field holding a value per cell, such as the cost-to-go of a search or the
+/// distance to the nearest obstacle, as a heatmap over the gridmap with a colour bar.
+/// Cells with non-finite values are left uncoloured. Writes an SVG if the file path
+/// ends in `.svg`, and a bitmap in the format of its extension otherwise.
+pub fn plot_heatmap(gridmap: &Gridmap, field: &[f32], file_path: &str, style: &PlotStyle) {
+    let (map_width, img_height) = get_image_size(gridmap, style.img_width);
+    let img_size = (map_width + COLOR_BAR_WIDTH, img_height);
+
+    with_backend!(file_path, img_size, |root| draw_heatmap(&root, gridmap, field));
+}
+
+/// Draw a field as a heatmap over the gridmap onto a drawing area of any backend,
+/// with a colour bar along the right edge
+pub fn draw_heatmap<DB: DrawingBackend>(root: &DrawingArea<DB, Shift>, gridmap: &Gridmap, field: &[f32]) {
+    let finite_values = field.iter().copied().filter(|value| value.is_finite());
+    let min_value = finite_values.clone().fold(f32::INFINITY, f32::min);
+    let max_value = finite_values.fold(f32::NEG_INFINITY, f32::max);
+
+    // Keep the colour range valid for empty and constant fields
+    let (min_value, max_value) = if min_value.is_finite() {
+        (min_value, max_value.max(min_value + 1.0))
+    } else {
+        (0.0, 1.0)
+    };
+
+    let (img_width, _) = root.dim_in_pixel();
+    let (map_area, bar_area) = root.split_horizontally(img_width.saturating_sub(COLOR_BAR_WIDTH));
+
+    let mut chart = build_chart(&map_area, gridmap);
+
+    plot_obstacles(&mut chart, gridmap);
+    chart
+        .draw_series(
+            field
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| value.is_finite())
+                .map(|(idx, value)| {
+                    let color = ViridisRGB::get_color_normalized(*value, min_value, max_value);
+                    get_cell_rect(gridmap, &gridmap.idx_to_xy(idx as u32), color.filled())
+                }),
+        )
+        .unwrap();
+
+    bar_area.fill(&WHITE).unwrap();
+
+    let mut color_bar = ChartBuilder::on(&bar_area)
+        .margin(5)
+        .set_label_area_size(LabelAreaPosition::Right, 70)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40)
+        .build_cartesian_2d(0.0f32..1.0f32, min_value..max_value)
+        .unwrap();
+
+    color_bar
+        .configure_mesh()
+        .disable_mesh()
+        .disable_x_axis()
+        .y_label_style(("sans-serif", 15))
+        .y_label_formatter(&|value| format!("{:.0}", value))
+        .draw()
+        .unwrap();
+
+    let step = (max_value - min_value) / COLOR_BAR_STEPS as f32;
+    color_bar
+        .draw_series((0..COLOR_BAR_STEPS).map(|idx| {
+            let value = min_value + idx as f32 * step;
+            let color = ViridisRGB::get_color_normalized(value + step / 2.0, min_value, max_value);
+            Rectangle::new([(0.0, value), (1.0, value + step)], color.filled())
+        }))
+        .unwrap();
+}
+
+/// Width in pixels of the colour bar beside a heatmap, including its labels
+const COLOR_BAR_WIDTH: u32 = 120;
+
+/// Number of colours shown along a colour bar
+const COLOR_BAR_STEPS: u32 = 100;
+
+/// Animate the search behind a plan as a GIF, adding the expansions of the trace a few
+/// at a time with the open list of the latest one as the frontier, and then showing the path
+pub fn animate_plan(
+    gridmap: &Gridmap,
+    start_cell: &(u32, u32),
+    goal_cell: &(u32, u32),
+    trace: &SearchTrace,
+    path: &[(u32, u32)],
+    file_path: &str,
+    options: &AnimationOptions,
+) {
+    let frame_delay_ms = 1000 / options.frames_per_second.max(1);
+    let cells_per_frame = options.cells_per_frame.max(1);
+    let style = &options.style;
+
+    let root = BitMapBackend::gif(file_path, get_image_size(gridmap, style.img_width), frame_delay_ms)
+        .expect("Unable to create animation file, please make sure directory exists")
+        .into_drawing_area();
+
+    let num_search_frames = trace.expansions.len().div_ceil(cells_per_frame);
+    let num_frames = num_search_frames + options.final_frames.max(1) as usize;
+
+    for frame in 0..num_frames {
+        // The final frames all show the same path, so only the first is drawn
+        if frame <= num_search_frames {
+            let num_expanded = ((frame + 1) * cells_per_frame).min(trace.expansions.len());
+            let mut chart = build_chart(&root, gridmap);
+
+            plot_obstacles(&mut chart, gridmap);
+            plot_cells(
+                &mut chart,
+                gridmap,
+                trace.expansions[..num_expanded].iter().map(|record| &record.cell),
+                options.cell_size,
+                style.closed_list_color,
+            );
+
+            if frame < num_search_frames {
+                if let Some(frontier) = trace.open_list_snapshots.get(num_expanded - 1) {
+                    plot_cells(&mut chart, gridmap, frontier.iter(), options.cell_size, style.frontier_color);
+                }
+                plot_cells(
+                    &mut chart,
+                    gridmap,
+                    trace.expansions[frame * cells_per_frame..num_expanded].iter().map(|record| &record.cell),
+                    options.cell_size,
+                    style.expansion_color,
+                );
+            } else {
+                plot_path(&mut chart, gridmap, path, options.cell_size / 2, style);
+            }
+            plot_start_and_goal(&mut chart, gridmap, start_cell, goal_cell, options.cell_size * 2, style);
+        }
+
+        root.present()
+            .expect("Unable to write animation frame to file");
+    }
+}
+
+/// Whether a plot should be written as SVG rather than a bitmap
+fn is_svg_path(file_path: &str) -> bool {
+    Path::new(file_path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"))
+}
+
+/// Get the image size of a plot, keeping the aspect ratio of the map
+fn get_image_size(gridmap: &Gridmap, img_width: u32) -> (u32, u32) {
+    let aspect_ratio = (gridmap.get_width() as f32) / (gridmap.get_height() as f32);
+
+    (img_width, (img_width as f32 / aspect_ratio) as u32)
+}
+
+/// Clear the drawing area and set up axes in meters covering the map
+fn build_chart<'a, DB: DrawingBackend>(root: &'a DrawingArea<DB, Shift>, gridmap: &Gridmap) -> GridmapChart<'a, DB> {
     let (width, height, resolution) = (
         gridmap.get_width(),
         gridmap.get_height(),
         gridmap.get_resolution(),
     );
 
-    let aspect_ratio = (width as f32) / (height as f32);
-    let img_width: u32 = 1280;
-    let img_height: u32 = (img_width as f32 / aspect_ratio) as u32;
-
-    let root = BitMapBackend::new(&file_path, (img_width, img_height)).into_drawing_area();
-
     root.fill(&WHITE).unwrap();
 
-    let mut chart = ChartBuilder::on(&root)
+    let mut chart = ChartBuilder::on(root)
         .margin(5)
         .set_label_area_size(LabelAreaPosition::Left, 40)
         .set_label_area_size(LabelAreaPosition::Bottom, 40)
@@ -97,106 +621,113 @@ pub fn plot_gridmap<'a>(
         .draw()
         .unwrap();
 
-    let start_goal_cell_size = cell_size * 2;
-    let path_size = cell_size / 2;
-
-    plot_obstacles(&mut chart, gridmap);
-    plot_start_and_goal(
-        &mut chart,
-        gridmap,
-        start_cell,
-        goal_cell,
-        start_goal_cell_size,
-    );
-    plot_closed_list(&mut chart, gridmap, &motion_plan.closed_list, *cell_size);
-    plot_path(&mut chart, gridmap, &motion_plan.path, path_size);
-
-    // Legend
-    // chart.configure_series_labels()
-    //   .border_style(&BLACK)
-    //   .background_style(&WHITE.mix(0.8))
-    //   .draw()
-    //   .unwrap();
+    chart
+}
 
-    root.present()
-        .expect("Unable to write result to file, please make sure directory exists");
+/// Draw the labels of the plotted series in the top right corner
+pub fn draw_legend<'a, DB: DrawingBackend + 'a>(chart: &mut GridmapChart<'a, DB>) {
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperRight)
+        .border_style(BLACK)
+        .background_style(WHITE.mix(0.8))
+        .draw()
+        .unwrap();
 }
 
-/// Plot Obstacles (grayscale)
-pub fn plot_obstacles(
-    chart: &mut ChartContext<BitMapBackend, Cartesian2d<RangedCoordf32, RangedCoordf32>>,
-    gridmap: &Gridmap,
-) {
+/// Plot Obstacles (grayscale), skipping free cells which match the white background
+pub fn plot_obstacles<'a, DB: DrawingBackend + 'a>(chart: &mut GridmapChart<'a, DB>, gridmap: &Gridmap) {
     chart
         .draw_series(
             gridmap
                 .get_cells()
                 .iter()
                 .enumerate()
+                .filter(|(_, cell_val)| **cell_val > 0)
                 .map(|(idx, cell_val)| (gridmap.idx_to_xy(idx as u32), cell_val))
-                .map(|(xy, cell_val)| get_obs_rect(&gridmap, &xy, cell_val)),
+                .map(|(xy, cell_val)| get_obs_rect(gridmap, &xy, cell_val)),
         )
         .unwrap();
 }
 
 /// Plot Start and goal cell
-pub fn plot_start_and_goal(
-    chart: &mut ChartContext<BitMapBackend, Cartesian2d<RangedCoordf32, RangedCoordf32>>,
+pub fn plot_start_and_goal<'a, DB: DrawingBackend + 'a>(
+    chart: &mut GridmapChart<'a, DB>,
     gridmap: &Gridmap,
     start_cell: &(u32, u32),
     goal_cell: &(u32, u32),
     cell_size: u32,
+    style: &PlotStyle,
 ) {
-    chart
-        .draw_series([Circle::new(
-            get_cell_centroid(gridmap, start_cell),
-            cell_size,
-            CYAN.filled(),
-        )])
-        .unwrap();
-    chart
-        .draw_series([Circle::new(
-            get_cell_centroid(gridmap, goal_cell),
-            cell_size,
-            GREEN.filled(),
-        )])
-        .unwrap();
+    for (label, cell, color) in [("Start", start_cell, style.start_color), ("Goal", goal_cell, style.goal_color)] {
+        chart
+            .draw_series([Circle::new(get_cell_centroid(gridmap, cell), cell_size, color.filled())])
+            .unwrap()
+            .label(label)
+            .legend(move |(x, y)| Circle::new((x, y), 5, color.filled()));
+    }
 }
 
 /// Plot Closed List (visited cells)
-pub fn plot_closed_list(
-    chart: &mut ChartContext<BitMapBackend, Cartesian2d<RangedCoordf32, RangedCoordf32>>,
+pub fn plot_closed_list<'a, DB: DrawingBackend + 'a>(
+    chart: &mut GridmapChart<'a, DB>,
     gridmap: &Gridmap,
     closed_list: &HashSet<(u32, u32)>,
     cell_size: u32,
+    style: &PlotStyle,
 ) {
+    let color = style.closed_list_color;
+
+    plot_cells(chart, gridmap, closed_list.iter(), cell_size, color)
+        .label("Visited cells")
+        .legend(move |(x, y)| Circle::new((x, y), 5, color.filled()));
+}
+
+/// Plot cells as circles of a color
+fn plot_cells<'a, 'b, 'c, DB: DrawingBackend + 'a>(
+    chart: &'b mut GridmapChart<'a, DB>,
+    gridmap: &Gridmap,
+    cells: impl Iterator<Item = &'c (u32, u32)>,
+    cell_size: u32,
+    color: RGBAColor,
+) -> &'b mut SeriesAnno<'a, DB> {
     chart
-        .draw_series(closed_list.iter().map(|xy| {
-            Circle::new(
-                get_cell_centroid(gridmap, xy),
-                cell_size,
-                RGBAColor(255, 255, 0, 0.2).filled(),
-            )
-        }))
-        .unwrap();
+        .draw_series(cells.map(|xy| Circle::new(get_cell_centroid(gridmap, xy), cell_size, color.filled())))
+        .unwrap()
 }
 
 /// Plot points from the final path    
-pub fn plot_path(
-    chart: &mut ChartContext<BitMapBackend, Cartesian2d<RangedCoordf32, RangedCoordf32>>,
+pub fn plot_path<'a, DB: DrawingBackend + 'a>(
+    chart: &mut GridmapChart<'a, DB>,
     gridmap: &Gridmap,
-    path: &Vec<(u32, u32)>,
+    path: &[(u32, u32)],
     path_size: u32,
+    style: &PlotStyle,
 ) {
+    let color = style.path_color;
+
+    plot_line(chart, gridmap, path, path_size, color)
+        .label("Path")
+        .legend(move |(x, y)| PathElement::new([(x - 10, y), (x + 10, y)], color.stroke_width(2)));
+}
+
+/// Plot a path as a line through its cells of a color
+fn plot_line<'a, 'b, DB: DrawingBackend + 'a>(
+    chart: &'b mut GridmapChart<'a, DB>,
+    gridmap: &Gridmap,
+    path: &[(u32, u32)],
+    path_size: u32,
+    color: RGBAColor,
+) -> &'b mut SeriesAnno<'a, DB> {
     chart
         .draw_series(
             LineSeries::new(
                 path.iter().map(|xy| get_cell_centroid(gridmap, xy)),
-                BLUE.filled(),
+                color.filled(),
             )
             .point_size(path_size),
         )
-        .unwrap();
+        .unwrap()
 }
 
 /// Get cell centroid in plotter coordinate system
@@ -210,6 +741,15 @@ pub fn get_obs_rect(
     xy: &(u32, u32),
     cell_val: &u8,
 ) -> plotters::element::Rectangle<(f32, f32)> {
+    get_cell_rect(
+        gridmap,
+        xy,
+        RGBAColor(255 - cell_val, 255 - cell_val, 255 - cell_val, 0.75).filled(),
+    )
+}
+
+/// Construct rectangle covering a cell
+fn get_cell_rect(gridmap: &Gridmap, xy: &(u32, u32), style: ShapeStyle) -> Rectangle<(f32, f32)> {
     let (x_f, y_f) = get_cell_centroid(gridmap, xy);
     let cell_offset = gridmap.get_resolution() / 2.0;
 
@@ -218,6 +758,6 @@ pub fn get_obs_rect(
             (x_f - cell_offset, y_f - cell_offset),
             (x_f + cell_offset, y_f + cell_offset),
         ],
-        RGBAColor(255 - cell_val, 255 - cell_val, 255 - cell_val, 0.75).filled(),
+        style,
     )
 }
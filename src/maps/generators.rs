@@ -0,0 +1,423 @@
+//! Seeded random maps for testing and benchmarking planners. The same seed always
+//! generates the same map.
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::gridmap::*;
+
+/// Resolution of generated maps in meter/cell, matching the map builders
+const RESOLUTION: f32 = 0.05;
+
+/// Algorithm used to carve a perfect maze
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MazeAlgorithm {
+    /// Depth-first search, giving long winding corridors
+    RecursiveBacktracker,
+    /// Randomised Prim's algorithm, giving many short dead ends
+    Prim,
+    /// Randomised Kruskal's algorithm, joining random walls of separate regions
+    Kruskal,
+}
+
+/// Shape of the obstacles of a forest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObstacleShape {
+    Rectangle,
+    Circle,
+}
+
+/// Generate a perfect maze, with exactly one path between any two free cells. Maze
+/// cells lie at odd coordinates with walls between them, so a map of even width or
+/// height has an extra wall along its right or bottom side.
+pub fn generate_maze(width: u32, height: u32, algorithm: MazeAlgorithm, seed: u64) -> Gridmap {
+    let mut gridmap = new_filled_gridmap(width, height, LETHAL_OBSTACLE);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (cols, rows) = (width.saturating_sub(1) / 2, height.saturating_sub(1) / 2);
+
+    if cols == 0 || rows == 0 {
+        return gridmap;
+    }
+
+    let passages = match algorithm {
+        MazeAlgorithm::RecursiveBacktracker => get_backtracker_passages(cols, rows, &mut rng),
+        MazeAlgorithm::Prim => get_prim_passages(cols, rows, &mut rng),
+        MazeAlgorithm::Kruskal => get_kruskal_passages(cols, rows, &mut rng),
+    };
+
+    for y in 0..rows {
+        for x in 0..cols {
+            gridmap.set_val_xy(FREE_SPACE, get_maze_cell((x, y)));
+        }
+    }
+
+    // Open the wall between the two maze cells of each passage
+    for (cell_1, cell_2) in passages {
+        let (xy_1, xy_2) = (get_maze_cell(cell_1), get_maze_cell(cell_2));
+        gridmap.set_val_xy(FREE_SPACE, ((xy_1.0 + xy_2.0) / 2, (xy_1.1 + xy_2.1) / 2));
+    }
+
+    gridmap
+}
+
+/// Position in the map of a maze cell
+fn get_maze_cell(cell: (u32, u32)) -> (u32, u32) {
+    (2 * cell.0 + 1, 2 * cell.1 + 1)
+}
+
+/// Get the maze cells next to a maze cell
+fn get_maze_neighbors(cell: (u32, u32), cols: u32, rows: u32) -> Vec<(u32, u32)> {
+    let (x, y) = (cell.0 as i64, cell.1 as i64);
+
+    [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+        .into_iter()
+        .filter(|(nb_x, nb_y)| (0..cols as i64).contains(nb_x) && (0..rows as i64).contains(nb_y))
+        .map(|(nb_x, nb_y)| (nb_x as u32, nb_y as u32))
+        .collect()
+}
+
+fn get_backtracker_passages(cols: u32, rows: u32, rng: &mut StdRng) -> Vec<((u32, u32), (u32, u32))> {
+    let mut visited = vec![false; (cols * rows) as usize];
+    let mut passages = Vec::new();
+    let mut stack = vec![(0, 0)];
+    visited[0] = true;
+
+    while let Some(&cell) = stack.last() {
+        let unvisited: Vec<(u32, u32)> = get_maze_neighbors(cell, cols, rows)
+            .into_iter()
+            .filter(|nb| !visited[(nb.1 * cols + nb.0) as usize])
+            .collect();
+
+        match unvisited.choose(rng) {
+            Some(&nb) => {
+                visited[(nb.1 * cols + nb.0) as usize] = true;
+                passages.push((cell, nb));
+                stack.push(nb);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    passages
+}
+
+fn get_prim_passages(cols: u32, rows: u32, rng: &mut StdRng) -> Vec<((u32, u32), (u32, u32))> {
+    let mut in_maze = vec![false; (cols * rows) as usize];
+    let mut passages = Vec::new();
+    let mut frontier: Vec<((u32, u32), (u32, u32))> = Vec::new();
+
+    let start = (rng.gen_range(0..cols), rng.gen_range(0..rows));
+    in_maze[(start.1 * cols + start.0) as usize] = true;
+    frontier.extend(get_maze_neighbors(start, cols, rows).into_iter().map(|nb| (start, nb)));
+
+    while !frontier.is_empty() {
+        let (cell, nb) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+        let nb_idx = (nb.1 * cols + nb.0) as usize;
+
+        if in_maze[nb_idx] {
+            continue;
+        }
+
+        in_maze[nb_idx] = true;
+        passages.push((cell, nb));
+        frontier.extend(
+            get_maze_neighbors(nb, cols, rows)
+                .into_iter()
+                .filter(|next| !in_maze[(next.1 * cols + next.0) as usize])
+                .map(|next| (nb, next)),
+        );
+    }
+
+    passages
+}
+
+fn get_kruskal_passages(cols: u32, rows: u32, rng: &mut StdRng) -> Vec<((u32, u32), (u32, u32))> {
+    let mut walls: Vec<((u32, u32), (u32, u32))> = Vec::new();
+    for y in 0..rows {
+        for x in 0..cols {
+            if x + 1 < cols {
+                walls.push(((x, y), (x + 1, y)));
+            }
+            if y + 1 < rows {
+                walls.push(((x, y), (x, y + 1)));
+            }
+        }
+    }
+    walls.shuffle(rng);
+
+    let mut regions = DisjointSet::new((cols * rows) as usize);
+
+    walls
+        .into_iter()
+        .filter(|(cell_1, cell_2)| {
+            regions.union(
+                (cell_1.1 * cols + cell_1.0) as usize,
+                (cell_2.1 * cols + cell_2.0) as usize,
+            )
+        })
+        .collect()
+}
+
+/// Union-find over indices, tracking which maze cells are already connected
+struct DisjointSet {
+    parents: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> DisjointSet {
+        DisjointSet {
+            parents: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, idx: usize) -> usize {
+        let mut root = idx;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        // Point the whole chain at the root, so later lookups are short
+        let mut cur = idx;
+        while self.parents[cur] != root {
+            let next = self.parents[cur];
+            self.parents[cur] = root;
+            cur = next;
+        }
+
+        root
+    }
+
+    /// Join the sets of two indices, returning false if they were already joined
+    fn union(&mut self, idx_1: usize, idx_2: usize) -> bool {
+        let (root_1, root_2) = (self.find(idx_1), self.find(idx_2));
+        self.parents[root_1] = root_2;
+        root_1 != root_2
+    }
+}
+
+/// Generate an open map scattered with obstacles of random size up to a maximum, until
+/// at least the target fraction of cells is occupied
+pub fn generate_forest(
+    width: u32,
+    height: u32,
+    shape: ObstacleShape,
+    density: f32,
+    max_size: u32,
+    seed: u64,
+) -> Gridmap {
+    let mut gridmap = Gridmap::new(width, height, RESOLUTION);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let target = (density.clamp(0.0, 1.0) * (width * height) as f32).ceil() as usize;
+    let mut occupied = 0;
+
+    while occupied < target {
+        let center = (rng.gen_range(0..width) as i64, rng.gen_range(0..height) as i64);
+        let (size_x, size_y) = (
+            rng.gen_range(1..=max_size.max(1)) as i64,
+            rng.gen_range(1..=max_size.max(1)) as i64,
+        );
+
+        for y in center.1 - size_y..=center.1 + size_y {
+            for x in center.0 - size_x..=center.0 + size_x {
+                let in_shape = match shape {
+                    ObstacleShape::Rectangle => true,
+                    ObstacleShape::Circle => (x - center.0).pow(2) + (y - center.1).pow(2) <= size_x.pow(2),
+                };
+                let in_map = (0..width as i64).contains(&x) && (0..height as i64).contains(&y);
+
+                if in_shape && in_map && gridmap.get_val_xy((x as u32, y as u32)) == FREE_SPACE {
+                    gridmap.set_val_xy(LETHAL_OBSTACLE, (x as u32, y as u32));
+                    occupied += 1;
+                }
+            }
+        }
+    }
+
+    gridmap
+}
+
+/// Generate a cave by filling cells at random, then repeatedly smoothing them with a
+/// cellular automaton: a cell becomes a wall when at least five of its eight neighbors
+/// are walls, and free when at most three are. Cells beyond the map count as walls.
+pub fn generate_cave(width: u32, height: u32, fill_probability: f32, iterations: u32, seed: u64) -> Gridmap {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut walls: Vec<bool> = (0..width * height)
+        .map(|_| rng.gen::<f32>() < fill_probability)
+        .collect();
+
+    for _ in 0..iterations {
+        walls = (0..width * height)
+            .map(|idx| {
+                let (x, y) = ((idx % width) as i64, (idx / width) as i64);
+                let wall_count = (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+                    .filter(|nb| *nb != (x, y))
+                    .filter(|(nb_x, nb_y)| {
+                        !(0..width as i64).contains(nb_x)
+                            || !(0..height as i64).contains(nb_y)
+                            || walls[(nb_y * width as i64 + nb_x) as usize]
+                    })
+                    .count();
+
+                match wall_count {
+                    0..=3 => false,
+                    4 => walls[idx as usize],
+                    _ => true,
+                }
+            })
+            .collect();
+    }
+
+    let mut gridmap = Gridmap::new(width, height, RESOLUTION);
+    for (idx, wall) in walls.iter().enumerate() {
+        if *wall {
+            gridmap.set_val_idx(LETHAL_OBSTACLE, idx);
+        }
+    }
+
+    gridmap
+}
+
+/// Generate rectangular rooms of random size between a minimum and maximum that do not
+/// overlap, joined in the order they were placed by L-shaped corridors. Fewer rooms are
+/// placed if there is no space left for them.
+pub fn generate_rooms(width: u32, height: u32, num_rooms: usize, room_size: (u32, u32), seed: u64) -> Gridmap {
+    let mut gridmap = new_filled_gridmap(width, height, LETHAL_OBSTACLE);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (min_size, max_size) = (room_size.0.max(1), room_size.1.max(room_size.0).max(1));
+    let mut rooms: Vec<(u32, u32, u32, u32)> = Vec::new();
+
+    if width < min_size + 2 || height < min_size + 2 {
+        return gridmap;
+    }
+
+    // Keep a wall around every room, and give up after many failed placements
+    for _ in 0..num_rooms * 100 {
+        if rooms.len() == num_rooms {
+            break;
+        }
+
+        let room_width = rng.gen_range(min_size..=max_size.min(width - 2));
+        let room_height = rng.gen_range(min_size..=max_size.min(height - 2));
+        let room = (
+            rng.gen_range(1..=width - 1 - room_width),
+            rng.gen_range(1..=height - 1 - room_height),
+            room_width,
+            room_height,
+        );
+
+        let overlaps = rooms.iter().any(|other| {
+            room.0 <= other.0 + other.2
+                && other.0 <= room.0 + room.2
+                && room.1 <= other.1 + other.3
+                && other.1 <= room.1 + room.3
+        });
+        if !overlaps {
+            rooms.push(room);
+        }
+    }
+
+    for (x, y, room_width, room_height) in &rooms {
+        for room_y in *y..y + room_height {
+            for room_x in *x..x + room_width {
+                gridmap.set_val_xy(FREE_SPACE, (room_x, room_y));
+            }
+        }
+    }
+
+    for pair in rooms.windows(2) {
+        let (center_1, center_2) = (get_room_center(pair[0]), get_room_center(pair[1]));
+
+        for x in center_1.0.min(center_2.0)..=center_1.0.max(center_2.0) {
+            gridmap.set_val_xy(FREE_SPACE, (x, center_1.1));
+        }
+        for y in center_1.1.min(center_2.1)..=center_1.1.max(center_2.1) {
+            gridmap.set_val_xy(FREE_SPACE, (center_2.0, y));
+        }
+    }
+
+    gridmap
+}
+
+/// Get the center cell of a room given as (x, y, width, height)
+pub fn get_room_center(room: (u32, u32, u32, u32)) -> (u32, u32) {
+    (room.0 + room.2 / 2, room.1 + room.3 / 2)
+}
+
+/// Generate a traversable cost field from Perlin noise, with features roughly `scale`
+/// cells across. Costs range from free space up to just below inscribed obstacles.
+pub fn generate_noise_costs(width: u32, height: u32, scale: f32, seed: u64) -> Gridmap {
+    let mut gridmap = Gridmap::new(width, height, RESOLUTION);
+    let noise = PerlinNoise::new(seed);
+    let scale = scale.max(f32::EPSILON);
+
+    for y in 0..height {
+        for x in 0..width {
+            // Noise lies roughly within [-1, 1]
+            let value = noise.get(x as f32 / scale, y as f32 / scale);
+            let cost = ((value + 1.0) / 2.0).clamp(0.0, 1.0) * (INSCRIBED_INFLATED_OBSTACLE - 1) as f32;
+            gridmap.set_val_xy(cost as u8, (x, y));
+        }
+    }
+
+    gridmap
+}
+
+/// Gradient noise of Ken Perlin's improved noise, in two dimensions
+struct PerlinNoise {
+    /// Shuffled 0..256, repeated so lookups of index + 1 need no wrapping
+    permutation: Vec<usize>,
+}
+
+impl PerlinNoise {
+    fn new(seed: u64) -> PerlinNoise {
+        let mut permutation: Vec<usize> = (0..256).collect();
+        permutation.shuffle(&mut StdRng::seed_from_u64(seed));
+        permutation.extend_from_within(..);
+
+        PerlinNoise { permutation }
+    }
+
+    fn get(&self, x: f32, y: f32) -> f32 {
+        let (cell_x, cell_y) = (x.floor() as usize & 255, y.floor() as usize & 255);
+        let (x, y) = (x - x.floor(), y - y.floor());
+        let (u, v) = (fade(x), fade(y));
+        let p = &self.permutation;
+
+        let corner = |dx: usize, dy: usize| p[p[cell_x + dx] + cell_y + dy];
+        let gradient = |hash: usize, x: f32, y: f32| match hash & 3 {
+            0 => x + y,
+            1 => -x + y,
+            2 => x - y,
+            _ => -x - y,
+        };
+
+        let bottom = lerp(u, gradient(corner(0, 0), x, y), gradient(corner(1, 0), x - 1.0, y));
+        let top = lerp(
+            u,
+            gradient(corner(0, 1), x, y - 1.0),
+            gradient(corner(1, 1), x - 1.0, y - 1.0),
+        );
+
+        lerp(v, bottom, top)
+    }
+}
+
+/// Smoothstep of the improved noise, with zero first and second derivatives at 0 and 1
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+fn new_filled_gridmap(width: u32, height: u32, val: u8) -> Gridmap {
+    let mut gridmap = Gridmap::new(width, height, RESOLUTION);
+    for idx in 0..gridmap.get_cells().len() {
+        gridmap.set_val_idx(val, idx);
+    }
+    gridmap
+}
@@ -32,31 +32,31 @@ impl Gridmap {
     // TODO: Change name of function from 'build' to 'new'
     pub fn new(width: u32, height: u32, resolution: f32) -> Gridmap {
         Gridmap {
-            width: width,
-            height: height,
-            resolution: resolution,
+            width,
+            height,
+            resolution,
             cells: vec![FREE_SPACE; (width * height) as usize],
         }
     }
 
     /// Get the width of the gridmap
     pub fn get_width(&self) -> u32 {
-        return self.width;
+        self.width
     }
 
     /// Get the height of the gridmap
     pub fn get_height(&self) -> u32 {
-        return self.height;
+        self.height
     }
 
     /// Get the resolution of the gridmap
     pub fn get_resolution(&self) -> f32 {
-        return self.resolution;
+        self.resolution
     }
 
     /// Get total number of cells in gridmap
     pub fn get_cells(&self) -> &Vec<u8> {
-        return &self.cells;
+        &self.cells
     }
 
     pub fn flip_y_u32(&self, y: u32) -> u32 {
@@ -121,7 +121,7 @@ impl Gridmap {
 
     /// Check if cell at 2D position is within the map
     pub fn xy_in_map(&self, pos: (u32, u32)) -> bool {
-        self.idx_in_map(self.xy_to_idx(pos))
+        pos.0 < self.width && pos.1 < self.height
     }
 
     /// Check if cell at 2D position is traversable
@@ -130,21 +130,6 @@ impl Gridmap {
         self.idx_is_traversable(self.xy_to_idx(pos))
     }
 
-    // /// Render the gripmap
-    // pub fn render(&self) -> () {
-    //   for y in (0..self.height).rev() {
-    //     for x in 0..self.width {
-    //       if self.get_val_xy((x,y)) < INSCRIBED_INFLATED_OBSTACLE{
-    //         print!("o");
-    //       }
-    //       else {
-    //         print!("x");
-    //       }
-    //     }
-    //     print!("\n");
-    //   }
-    // }
-
     /// Convert from 2D position to 1D index
     pub fn xy_to_idx(&self, pos: (u32, u32)) -> usize {
         (pos.1 * (self.width) + pos.0) as usize
@@ -183,6 +168,17 @@ mod tests {
         assert_eq!(gridmap.xy_to_idx((3, 5)), 23);
     }
 
+    #[test]
+    /// Test that positions beyond either axis are outside the map
+    fn test_xy_in_map() {
+        let gridmap = Gridmap::new(4, 6, 0.05);
+
+        assert!(gridmap.xy_in_map((0, 0)));
+        assert!(gridmap.xy_in_map((3, 5)));
+        assert!(!gridmap.xy_in_map((4, 0)));
+        assert!(!gridmap.xy_in_map((0, 6)));
+    }
+
     #[test]
     /// Test traversability of cells based on assigned values
     fn test_traversable_check() {
@@ -190,12 +186,12 @@ mod tests {
 
         for i in 0..gridmap.get_cells().len() {
             assert_eq!(gridmap.get_val_idx(i), FREE_SPACE);
-            assert_eq!(gridmap.idx_is_traversable(i), true);
+            assert!(gridmap.idx_is_traversable(i));
         }
 
         gridmap.set_val_idx(INSCRIBED_INFLATED_OBSTACLE, 4);
         assert_eq!(gridmap.get_val_idx(4), INSCRIBED_INFLATED_OBSTACLE);
-        assert_eq!(gridmap.xy_is_traversable((0, 1)), false);
+        assert!(!gridmap.xy_is_traversable((0, 1)));
     }
 
     #[test]
@@ -0,0 +1,111 @@
+//! Maps and scenarios of the MovingAI grid benchmarks by Nathan Sturtevant,
+//! from https://movingai.com/benchmarks/grids.html
+use std::path::Path;
+
+use super::gridmap::*;
+
+/// A query of a scenario file, with the length of its shortest path
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    /// Group of queries of similar length
+    pub bucket: u32,
+    /// Map file the query is on, as written in the scenario file
+    pub map: String,
+    pub map_width: u32,
+    pub map_height: u32,
+    pub start: (u32, u32),
+    pub goal: (u32, u32),
+    /// Length of the shortest path in cells, with diagonal moves of length √2 that may
+    /// not cut the corner of a blocked cell
+    pub optimal_length: f64,
+}
+
+/// Cell value of a terrain character. Swamp is treated as passable and water as
+/// blocked, as the planners do not restrict which terrain a cell is entered from.
+pub fn get_terrain_value(terrain: char) -> Option<u8> {
+    match terrain {
+        '.' | 'G' | 'S' => Some(FREE_SPACE),
+        '@' | 'O' => Some(NO_INFORMATION),
+        'T' | 'W' => Some(LETHAL_OBSTACLE),
+        _ => None,
+    }
+}
+
+/// Builds a Gridmap struct from a MovingAI `.map` file. Returns None if the file cannot
+/// be read, its header is malformed or it contains unknown terrain.
+pub fn build_gridmap_from_map(file_path: &str) -> Option<Gridmap> {
+    let contents = std::fs::read_to_string(Path::new(file_path)).ok()?;
+    let mut lines = contents.lines();
+    let (mut width, mut height) = (None, None);
+
+    for line in lines.by_ref() {
+        match line.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["type", _] => {}
+            ["height", value] => height = value.parse::<u32>().ok(),
+            ["width", value] => width = value.parse::<u32>().ok(),
+            ["map"] => break,
+            _ => return None,
+        }
+    }
+
+    let (width, height) = (width?, height?);
+    let mut gridmap = Gridmap::new(width, height, 1.0);
+    let rows: Vec<&str> = lines.take(height as usize).collect();
+
+    if rows.len() != height as usize {
+        return None;
+    }
+
+    for (y, row) in rows.iter().enumerate() {
+        let row: Vec<char> = row.trim_end().chars().collect();
+        if row.len() != width as usize {
+            return None;
+        }
+
+        for (x, terrain) in row.iter().enumerate() {
+            gridmap.set_val_xy(get_terrain_value(*terrain)?, (x as u32, y as u32));
+        }
+    }
+
+    Some(gridmap)
+}
+
+/// Reads the queries of a MovingAI `.scen` file. Returns None if the file cannot be read
+/// or any query is malformed.
+pub fn read_scenarios(file_path: &str) -> Option<Vec<Scenario>> {
+    let contents = std::fs::read_to_string(Path::new(file_path)).ok()?;
+
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with("version"))
+        .map(parse_scenario)
+        .collect()
+}
+
+fn parse_scenario(line: &str) -> Option<Scenario> {
+    let fields: Vec<&str> = line.split('\t').map(|field| field.trim()).collect();
+    let [bucket, map, map_width, map_height, start_x, start_y, goal_x, goal_y, optimal_length] = fields[..] else {
+        return None;
+    };
+
+    Some(Scenario {
+        bucket: bucket.parse().ok()?,
+        map: map.to_string(),
+        map_width: map_width.parse().ok()?,
+        map_height: map_height.parse().ok()?,
+        start: (start_x.parse().ok()?, start_y.parse().ok()?),
+        goal: (goal_x.parse().ok()?, goal_y.parse().ok()?),
+        optimal_length: optimal_length.parse().ok()?,
+    })
+}
+
+/// Get the length of a path in cells, with diagonal moves of length √2 as in the scenarios
+pub fn get_octile_length(path: &[(u32, u32)]) -> f64 {
+    path.windows(2)
+        .map(|pair| {
+            let dx = (pair[0].0 as f64 - pair[1].0 as f64).abs();
+            let dy = (pair[0].1 as f64 - pair[1].1 as f64).abs();
+            dx.hypot(dy)
+        })
+        .sum()
+}
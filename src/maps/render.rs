@@ -0,0 +1,194 @@
+use std::io::IsTerminal;
+
+use super::gridmap::*;
+use crate::planners::planner_common::MotionPlan;
+
+/// Characters drawn for each kind of cell
+#[derive(Clone, Debug)]
+pub struct RenderGlyphs {
+    pub free: char,
+    /// Traversable cell with a cost above free space
+    pub cost: char,
+    pub obstacle: char,
+    pub unknown: char,
+    pub visited: char,
+    pub path: char,
+    pub start: char,
+    pub goal: char,
+}
+
+impl Default for RenderGlyphs {
+    fn default() -> RenderGlyphs {
+        RenderGlyphs {
+            free: '.',
+            cost: ':',
+            obstacle: '#',
+            unknown: '?',
+            visited: '+',
+            path: '*',
+            start: 'S',
+            goal: 'G',
+        }
+    }
+}
+
+impl RenderGlyphs {
+    /// Block and dot characters, for terminals with Unicode support
+    pub fn unicode() -> RenderGlyphs {
+        RenderGlyphs {
+            free: '·',
+            cost: '░',
+            obstacle: '█',
+            unknown: '▒',
+            visited: '∘',
+            path: '●',
+            start: 'S',
+            goal: 'G',
+        }
+    }
+}
+
+/// When to colour cells with ANSI escape codes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colour only when printing to a terminal, unless NO_COLOR is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Glyphs and colouring of a rendered gridmap
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    pub glyphs: RenderGlyphs,
+    pub color: ColorMode,
+}
+
+/// Kind of cell drawn, in increasing order of precedence
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum CellKind {
+    Free,
+    Cost,
+    Obstacle,
+    Unknown,
+    Visited,
+    Path,
+    Start,
+    Goal,
+}
+
+impl CellKind {
+    fn get_glyph(&self, glyphs: &RenderGlyphs) -> char {
+        match self {
+            CellKind::Free => glyphs.free,
+            CellKind::Cost => glyphs.cost,
+            CellKind::Obstacle => glyphs.obstacle,
+            CellKind::Unknown => glyphs.unknown,
+            CellKind::Visited => glyphs.visited,
+            CellKind::Path => glyphs.path,
+            CellKind::Start => glyphs.start,
+            CellKind::Goal => glyphs.goal,
+        }
+    }
+
+    /// ANSI colour code, matching the colours of plotted plans
+    fn get_ansi_code(&self) -> Option<&'static str> {
+        match self {
+            CellKind::Free => None,
+            CellKind::Cost => Some("90"),
+            CellKind::Obstacle => Some("1"),
+            CellKind::Unknown => Some("2"),
+            CellKind::Visited => Some("33"),
+            CellKind::Path => Some("34"),
+            CellKind::Start => Some("1;36"),
+            CellKind::Goal => Some("1;32"),
+        }
+    }
+}
+
+/// Render a gridmap as text, one line per row with the top row first
+pub fn render_gridmap(gridmap: &Gridmap, options: &RenderOptions) -> String {
+    render_cells(gridmap, options, |xy| get_map_cell_kind(gridmap, xy))
+}
+
+/// Render a gridmap as text with the visited cells, path, start and goal of a plan drawn over it
+pub fn render_plan(
+    gridmap: &Gridmap,
+    start_cell: &(u32, u32),
+    goal_cell: &(u32, u32),
+    motion_plan: &MotionPlan,
+    options: &RenderOptions,
+) -> String {
+    let mut kinds: Vec<CellKind> = (0..gridmap.get_cells().len())
+        .map(|idx| get_map_cell_kind(gridmap, gridmap.idx_to_xy(idx as u32)))
+        .collect();
+
+    let plan_cells = motion_plan
+        .closed_list
+        .iter()
+        .map(|xy| (xy, CellKind::Visited))
+        .chain(motion_plan.path.iter().map(|xy| (xy, CellKind::Path)))
+        .chain([(start_cell, CellKind::Start), (goal_cell, CellKind::Goal)]);
+
+    for (xy, kind) in plan_cells.filter(|(xy, _)| gridmap.xy_in_map(**xy)) {
+        let idx = gridmap.xy_to_idx(*xy);
+        kinds[idx] = kinds[idx].max(kind);
+    }
+
+    render_cells(gridmap, options, |xy| kinds[gridmap.xy_to_idx(xy)])
+}
+
+/// Print a plan to stdout, colouring it when stdout is a terminal
+pub fn print_plan(
+    gridmap: &Gridmap,
+    start_cell: &(u32, u32),
+    goal_cell: &(u32, u32),
+    motion_plan: &MotionPlan,
+    options: &RenderOptions,
+) {
+    print!("{}", render_plan(gridmap, start_cell, goal_cell, motion_plan, options));
+}
+
+/// Kind of a cell from its value in the map alone
+fn get_map_cell_kind(gridmap: &Gridmap, xy: (u32, u32)) -> CellKind {
+    match gridmap.get_val_xy(xy) {
+        FREE_SPACE => CellKind::Free,
+        NO_INFORMATION => CellKind::Unknown,
+        val if val >= INSCRIBED_INFLATED_OBSTACLE => CellKind::Obstacle,
+        _ => CellKind::Cost,
+    }
+}
+
+/// Whether rendered text should contain ANSI colour codes
+fn use_color(mode: ColorMode) -> bool {
+    match mode {
+        ColorMode::Auto => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+    }
+}
+
+/// Render every cell of the map row by row, ending each row with a newline
+fn render_cells<F>(gridmap: &Gridmap, options: &RenderOptions, get_kind: F) -> String
+where
+    F: Fn((u32, u32)) -> CellKind,
+{
+    let color = use_color(options.color);
+    let mut text = String::new();
+
+    for y in 0..gridmap.get_height() {
+        for x in 0..gridmap.get_width() {
+            let kind = get_kind((x, y));
+            let glyph = kind.get_glyph(&options.glyphs);
+
+            match kind.get_ansi_code().filter(|_| color) {
+                Some(code) => text.push_str(&format!("\x1b[{}m{}\x1b[0m", code, glyph)),
+                None => text.push(glyph),
+            }
+        }
+        text.push('\n');
+    }
+
+    text
+}
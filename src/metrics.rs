@@ -0,0 +1,224 @@
+//! Quality metrics of a path over a gridmap, for comparing planners and post-processed
+//! paths. Paths are given as points in cell units, where integer coordinates lie on cell
+//! centroids, so that smoothed sub-cell paths can be measured like grid paths.
+use crate::maps::gridmap::Gridmap;
+
+/// Squared distance, in cells, standing in for infinity in the distance transform
+const FAR_AWAY: f64 = 1e12;
+
+/// Quality of a path, with lengths in meters and angles in radians
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PathMetrics {
+    pub length_m: f32,
+    /// Distance from the closest point of the path to an untraversable cell, infinite
+    /// if the map has none
+    pub min_clearance_m: f32,
+    pub mean_clearance_m: f32,
+    /// Sum of the absolute changes of heading along the path
+    pub turning_angle: f32,
+    /// Largest curvature at any point of the path, in 1/m
+    pub max_curvature: f32,
+    pub mean_curvature: f32,
+    /// Sum of the lengths of the second differences of the path, as in the Nav2 benchmark scripts
+    pub smoothness: f32,
+    /// Cell cost integrated over the length of the path, in cost·m
+    pub integrated_cost: f32,
+}
+
+/// Measure every metric of a path
+pub fn get_path_metrics(gridmap: &Gridmap, path: &[(f32, f32)]) -> PathMetrics {
+    let resolution = gridmap.get_resolution();
+    let clearances = get_clearances(gridmap, path, &get_obstacle_distance_field(gridmap));
+    let curvatures = get_curvatures(path, resolution);
+
+    PathMetrics {
+        length_m: get_length_m(path, resolution),
+        min_clearance_m: clearances.iter().copied().fold(f32::INFINITY, f32::min),
+        mean_clearance_m: get_mean(&clearances),
+        turning_angle: get_turning_angle(path),
+        max_curvature: curvatures.iter().copied().fold(0.0, f32::max),
+        mean_curvature: get_mean(&curvatures),
+        smoothness: get_smoothness(path, resolution),
+        integrated_cost: get_integrated_cost(gridmap, path),
+    }
+}
+
+/// Convert a path of cells into points
+pub fn get_points(path: &[(u32, u32)]) -> Vec<(f32, f32)> {
+    path.iter().map(|cell| (cell.0 as f32, cell.1 as f32)).collect()
+}
+
+pub fn get_length_m(path: &[(f32, f32)], resolution: f32) -> f32 {
+    path.windows(2).map(|pair| get_distance(pair[0], pair[1])).sum::<f32>() * resolution
+}
+
+/// Get the Euclidean distance in cells from every cell to the nearest untraversable
+/// cell, infinite if there is none. Uses the exact distance transform of Felzenszwalb
+/// and Huttenlocher, a pass along each row and then along each column.
+pub fn get_obstacle_distance_field(gridmap: &Gridmap) -> Vec<f32> {
+    let (width, height) = (gridmap.get_width() as usize, gridmap.get_height() as usize);
+    let mut squared_distances: Vec<f64> = (0..width * height)
+        .map(|idx| if gridmap.idx_is_traversable(idx) { FAR_AWAY } else { 0.0 })
+        .collect();
+
+    for y in 0..height {
+        let row = transform_1d(&squared_distances[y * width..(y + 1) * width]);
+        squared_distances[y * width..(y + 1) * width].copy_from_slice(&row);
+    }
+
+    for x in 0..width {
+        let column: Vec<f64> = (0..height).map(|y| squared_distances[y * width + x]).collect();
+        for (y, squared_distance) in transform_1d(&column).into_iter().enumerate() {
+            squared_distances[y * width + x] = squared_distance;
+        }
+    }
+
+    squared_distances
+        .into_iter()
+        .map(|squared_distance| {
+            if squared_distance >= FAR_AWAY / 2.0 {
+                f32::INFINITY
+            } else {
+                squared_distance.sqrt() as f32
+            }
+        })
+        .collect()
+}
+
+/// Lower envelope of the parabolas rooted at each sample, giving the squared distance
+/// to the nearest sample along a line
+fn transform_1d(samples: &[f64]) -> Vec<f64> {
+    let num_samples = samples.len();
+    if num_samples == 0 {
+        return Vec::new();
+    }
+
+    let mut roots: Vec<usize> = vec![0; num_samples];
+    let mut boundaries: Vec<f64> = vec![f64::NEG_INFINITY; num_samples + 1];
+    let mut k = 0;
+    boundaries[1] = f64::INFINITY;
+
+    let get_intersection = |q: usize, r: usize| {
+        ((samples[q] + (q * q) as f64) - (samples[r] + (r * r) as f64)) / (2.0 * q as f64 - 2.0 * r as f64)
+    };
+
+    for q in 1..num_samples {
+        let mut s = get_intersection(q, roots[k]);
+        while s <= boundaries[k] {
+            k -= 1;
+            s = get_intersection(q, roots[k]);
+        }
+
+        k += 1;
+        roots[k] = q;
+        boundaries[k] = s;
+        boundaries[k + 1] = f64::INFINITY;
+    }
+
+    k = 0;
+    (0..num_samples)
+        .map(|q| {
+            while boundaries[k + 1] < q as f64 {
+                k += 1;
+            }
+            let offset = q as f64 - roots[k] as f64;
+            offset * offset + samples[roots[k]]
+        })
+        .collect()
+}
+
+/// Get the distance in meters from each point to the nearest untraversable cell, looked
+/// up at the cell the point lies in
+pub fn get_clearances(gridmap: &Gridmap, path: &[(f32, f32)], distance_field: &[f32]) -> Vec<f32> {
+    path.iter()
+        .filter_map(|point| get_cell(gridmap, *point))
+        .map(|cell| distance_field[gridmap.xy_to_idx(cell)] * gridmap.get_resolution())
+        .collect()
+}
+
+/// Sum of the absolute changes of heading between consecutive moves, ignoring waits
+pub fn get_turning_angle(path: &[(f32, f32)]) -> f32 {
+    let moves = get_moves(path);
+
+    moves
+        .windows(2)
+        .map(|pair| {
+            let cross = pair[0].0 * pair[1].1 - pair[0].1 * pair[1].0;
+            let dot = pair[0].0 * pair[1].0 + pair[0].1 * pair[1].1;
+            cross.atan2(dot).abs()
+        })
+        .sum()
+}
+
+/// Get the curvature in 1/m at each interior point, that of the circle through the point
+/// and its neighbors. Points on a straight line have no curvature, and waits and
+/// reversals are skipped.
+pub fn get_curvatures(path: &[(f32, f32)], resolution: f32) -> Vec<f32> {
+    let mut points: Vec<(f32, f32)> = path.to_vec();
+    points.dedup();
+
+    points
+        .windows(3)
+        .filter_map(|points| {
+            let (a, b, c) = (points[0], points[1], points[2]);
+            let cross = (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0);
+            let sides = get_distance(a, b) * get_distance(b, c) * get_distance(a, c);
+
+            (sides > 0.0).then(|| 2.0 * cross.abs() / sides / resolution)
+        })
+        .collect()
+}
+
+/// Sum of the lengths of the second differences between consecutive points, in meters,
+/// as in the Nav2 benchmark scripts
+pub fn get_smoothness(path: &[(f32, f32)], resolution: f32) -> f32 {
+    path.windows(3)
+        .map(|points| {
+            let dx = points[2].0 - 2.0 * points[1].0 + points[0].0;
+            let dy = points[2].1 - 2.0 * points[1].1 + points[0].1;
+            dx.hypot(dy) * resolution
+        })
+        .sum()
+}
+
+/// Integrate the cell cost over the path, taking each move to cost the average of the
+/// cells at either end of it per meter
+pub fn get_integrated_cost(gridmap: &Gridmap, path: &[(f32, f32)]) -> f32 {
+    let get_cost = |point: (f32, f32)| get_cell(gridmap, point).map_or(0.0, |cell| gridmap.get_val_xy(cell) as f32);
+
+    path.windows(2)
+        .map(|pair| {
+            let length_m = get_distance(pair[0], pair[1]) * gridmap.get_resolution();
+            (get_cost(pair[0]) + get_cost(pair[1])) / 2.0 * length_m
+        })
+        .sum()
+}
+
+/// Get the cell a point lies in, if it is within the map
+fn get_cell(gridmap: &Gridmap, point: (f32, f32)) -> Option<(u32, u32)> {
+    let (x, y) = (point.0.round(), point.1.round());
+    if x < 0.0 || y < 0.0 {
+        return None;
+    }
+
+    Some((x as u32, y as u32)).filter(|cell| gridmap.xy_in_map(*cell))
+}
+
+/// Get the non-zero moves between consecutive points
+fn get_moves(path: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    path.windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1))
+        .filter(|step| *step != (0.0, 0.0))
+        .collect()
+}
+
+fn get_distance(point_1: (f32, f32), point_2: (f32, f32)) -> f32 {
+    (point_1.0 - point_2.0).hypot(point_1.1 - point_2.1)
+}
+
+fn get_mean(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f32>() / values.len() as f32
+}
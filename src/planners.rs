@@ -6,5 +6,13 @@ pub mod dfs;
 pub mod dijkstra;
 pub mod a_star;
 pub mod rrt;
+pub mod fmm;
+pub mod hpa_star;
+pub mod tour;
+pub mod coverage;
+pub mod space_time_a_star;
+pub mod cbs;
+pub mod cooperative_a_star;
+pub mod validation;
 
 // pub mod dfs;
\ No newline at end of file
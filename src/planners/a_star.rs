@@ -1,5 +1,6 @@
 use std::cmp::{Ordering, Reverse};
 use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::time::Instant;
 
 use super::planner_common::*;
 use super::planner_base::*;
@@ -8,8 +9,12 @@ use crate::maps::gridmap::Gridmap;
 // A Star
 pub struct AStarPlanner{
     start: (u32, u32),
-    goal: (u32, u32),
+    goal_region: GoalRegion,
     gridmap: Gridmap,
+    budget: PlanningBudget,
+    trace_level: TraceLevel,
+    /// Moves between cells, 8 way with corner cutting by default
+    neighborhood: Neighborhood,
 }
 
 impl Planner for AStarPlanner {
@@ -17,12 +22,16 @@ impl Planner for AStarPlanner {
     fn new(gridmap: &Gridmap) -> AStarPlanner {
         AStarPlanner {
             start: (0, 0),
-            goal: (0, 0),
+            goal_region: GoalRegion::new(&[(0, 0)], 0.0),
             gridmap: gridmap.clone(),
+            budget: PlanningBudget::default(),
+            trace_level: TraceLevel::Off,
+            neighborhood: Neighborhood::EightWay,
         }
     }
 
-    fn generate_plan(&self) -> MotionPlan {
+    fn try_generate_plan(&self) -> Result<MotionPlan, PlanningError> {
+        let start_time = Instant::now();
         let mut path: Vec<(u32, u32)> = Vec::new();
 
         let mut open_list: BinaryHeap<Reverse<Cell2D>> = BinaryHeap::new();
@@ -30,28 +39,55 @@ impl Planner for AStarPlanner {
         let mut parents: HashMap<(u32, u32), (u32, u32)> = HashMap::new();
 
         // Movement cost from start to cell
-        let mut g_cost: Vec<u32> = vec![std::u32::MAX; self.gridmap.get_cells().len()];
+        let mut g_cost: Vec<u32> = vec![u32::MAX; self.gridmap.get_cells().len()];
 
         g_cost[self.gridmap.xy_to_idx(self.start)] = 0;
 
         open_list.push(Reverse(Cell2D::new(
             self.start,
-            get_l2_cost(self.start, self.goal),
+            self.goal_region.get_l2_cost(self.start),
         )));
         parents.insert(self.start, self.start);
 
+        let mut budget_tracker = BudgetTracker::new(&self.budget);
+        let mut trace_recorder = TraceRecorder::new(self.trace_level);
+        let mut closest_cell = self.start;
+        let mut stats = SearchStats { nodes_generated: 1, max_open_list_size: 1, ..Default::default() };
+
         while !open_list.is_empty() {
             let cur_cell = open_list.pop().unwrap().0;
+            // Skip stale entries of cells already expanded through a cheaper path
+            if closed_list.contains(&cur_cell.pos) {
+                continue;
+            }
 
-            if cur_cell.pos == self.goal {
-                path = trace_path(&self.goal, &parents);
+            if self.goal_region.contains(cur_cell.pos) {
+                path = trace_path(&cur_cell.pos, &parents);
                 break;
             }
 
+            if let Err(interruption) = budget_tracker.expand() {
+                let partial_path = trace_path(&closest_cell, &parents);
+                stats.finish(&partial_path, &self.gridmap, start_time);
+                let partial_plan = MotionPlan {
+                    path: partial_path,
+                    closed_list,
+                    stats,
+                    trace: trace_recorder.finish(),
+                };
+                return Err(PlanningError { interruption, partial_plan: Box::new(partial_plan) });
+            }
+
             closed_list.insert(cur_cell.pos);
+            stats.nodes_expanded += 1;
+            let h_cost = self.goal_region.get_l2_cost(cur_cell.pos);
+            trace_recorder.record_expansion(cur_cell.pos, parents[&cur_cell.pos], cur_cell.f_cost - h_cost, h_cost);
+            if self.goal_region.get_l2_cost(cur_cell.pos) < self.goal_region.get_l2_cost(closest_cell) {
+                closest_cell = cur_cell.pos;
+            }
 
             // Explore neighbors
-            for nb_cell_pos in get_neighbors_8_con(cur_cell.pos, &self.gridmap) {
+            for nb_cell_pos in self.neighborhood.get_neighbors(cur_cell.pos, &self.gridmap) {
                 if closed_list.contains(&nb_cell_pos) {
                     continue;
                 }
@@ -65,30 +101,63 @@ impl Planner for AStarPlanner {
                     parents.insert(nb_cell_pos, cur_cell.pos);
                     open_list.push(Reverse(Cell2D::new(
                         nb_cell_pos,
-                        alt_g_cost + get_l2_cost(nb_cell_pos, self.goal),
+                        alt_g_cost + self.goal_region.get_l2_cost(nb_cell_pos),
                     )));
+                    stats.nodes_generated += 1;
+                    stats.max_open_list_size = stats.max_open_list_size.max(open_list.len());
                 }
             }
+            trace_recorder.record_open_list(|| get_open_list_cells(&open_list));
         }
 
-        return MotionPlan { path, closed_list };
+        stats.finish(&path, &self.gridmap, start_time);
+        Ok(MotionPlan {
+            path,
+            closed_list,
+            stats,
+            trace: trace_recorder.finish(),
+        })
+    }
+
+    fn get_gridmap(&self) -> &Gridmap {
+        &self.gridmap
     }
 
     fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool {
         self.gridmap = gridmap.clone();
-        return true;
+        true
     }
 
     fn update_start(&mut self, start: (u32, u32)) -> bool {
         self.start = start;
-        return true;
+        true
+    }
+
+    fn update_goals(&mut self, goals: &[(u32, u32)]) -> bool {
+        self.goal_region.set_goals(goals)
     }
 
-    fn update_goal(&mut self, goal: (u32, u32)) -> bool {
-        self.goal = goal;
-        return true;
+    fn update_goal_tolerance(&mut self, tolerance: f32) -> bool {
+        self.goal_region.set_tolerance(tolerance)
     }
 
+    fn update_budget(&mut self, budget: &PlanningBudget) -> bool {
+        self.budget = budget.clone();
+        true
+    }
+
+    fn update_trace_level(&mut self, trace_level: TraceLevel) -> bool {
+        self.trace_level = trace_level;
+        true
+    }
+}
+
+impl AStarPlanner {
+    /// Set the moves between cells, such as diagonal moves that do not cut corners
+    pub fn update_neighborhood(&mut self, neighborhood: Neighborhood) -> bool {
+        self.neighborhood = neighborhood;
+        true
+    }
 }
 
 
@@ -121,3 +190,10 @@ impl Cell2D {
         Cell2D { pos, f_cost }
     }
 }
+
+/// Get the cells on the open list, those popped first coming first
+fn get_open_list_cells(open_list: &BinaryHeap<Reverse<Cell2D>>) -> Vec<(u32, u32)> {
+    let mut entries: Vec<&Cell2D> = open_list.iter().map(|entry| &entry.0).collect();
+    entries.sort_by_key(|entry| entry.f_cost);
+    entries.into_iter().map(|entry| entry.pos).collect()
+}
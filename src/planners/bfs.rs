@@ -1,4 +1,6 @@
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Instant;
 
 use super::planner_common::*;
 use super::planner_base::*;
@@ -7,8 +9,10 @@ use crate::maps::gridmap::Gridmap;
 // Breadth-First Search
 pub struct BFSPlanner{
     start: (u32, u32),
-    goal: (u32, u32),
+    goal_region: GoalRegion,
     gridmap: Gridmap,
+    budget: PlanningBudget,
+    trace_level: TraceLevel,
 }
 
 impl Planner for BFSPlanner {
@@ -16,12 +20,15 @@ impl Planner for BFSPlanner {
     fn new(gridmap: &Gridmap) -> BFSPlanner {
         BFSPlanner {
             start: (0, 0),
-            goal: (0, 0),
+            goal_region: GoalRegion::new(&[(0, 0)], 0.0),
             gridmap: gridmap.clone(),
+            budget: PlanningBudget::default(),
+            trace_level: TraceLevel::Off,
         }
     }
 
-    fn generate_plan(&self) -> MotionPlan {
+    fn try_generate_plan(&self) -> Result<MotionPlan, PlanningError> {
+        let start_time = Instant::now();
         let mut path: Vec<(u32, u32)> = Vec::new();
 
         let mut open_list: VecDeque<(u32, u32)> = VecDeque::new();
@@ -32,44 +39,90 @@ impl Planner for BFSPlanner {
         open_list.push_back(self.start);
         parents.insert(self.start, self.start);
 
+        let mut budget_tracker = BudgetTracker::new(&self.budget);
+        let mut trace_recorder = TraceRecorder::new(self.trace_level);
+        let mut closest_cell = self.start;
+        let mut stats = SearchStats { nodes_generated: 1, max_open_list_size: 1, ..Default::default() };
+
         while !open_list.is_empty() {
             let cur_cell = open_list.pop_front().unwrap();
 
-            if cur_cell == self.goal {
-                path = trace_path(&self.goal, &parents);
+            if self.goal_region.contains(cur_cell) {
+                path = trace_path(&cur_cell, &parents);
                 break;
             }
 
+            if let Err(interruption) = budget_tracker.expand() {
+                let partial_path = trace_path(&closest_cell, &parents);
+                stats.finish(&partial_path, &self.gridmap, start_time);
+                let partial_plan = MotionPlan {
+                    path: partial_path,
+                    closed_list,
+                    stats,
+                    trace: trace_recorder.finish(),
+                };
+                return Err(PlanningError { interruption, partial_plan: Box::new(partial_plan) });
+            }
+
             closed_list.insert(cur_cell);
+            stats.nodes_expanded += 1;
+            trace_recorder.record_expansion_from_parent(cur_cell, parents[&cur_cell], 0);
+            if self.goal_region.get_l2_cost(cur_cell) < self.goal_region.get_l2_cost(closest_cell) {
+                closest_cell = cur_cell;
+            }
 
-            // Explore neighbors
+            // Explore neighbors, queueing each cell only the first time it is reached so
+            // that it keeps the parent of the shortest path to it
             for nb_cell in get_neighbors_8_con(cur_cell, &self.gridmap) {
-                if !closed_list.contains(&nb_cell) {
-                    parents.insert(nb_cell, cur_cell);
+                if let Entry::Vacant(entry) = parents.entry(nb_cell) {
+                    entry.insert(cur_cell);
                     open_list.push_back(nb_cell);
+                    stats.nodes_generated += 1;
+                    stats.max_open_list_size = stats.max_open_list_size.max(open_list.len());
                 }
             }
+            trace_recorder.record_open_list(|| open_list.iter().copied());
         }
 
-        return MotionPlan {
-            path: path,
-            closed_list: closed_list,
-        };
+        stats.finish(&path, &self.gridmap, start_time);
+        Ok(MotionPlan {
+            path,
+            closed_list,
+            stats,
+            trace: trace_recorder.finish(),
+        })
+    }
+
+    fn get_gridmap(&self) -> &Gridmap {
+        &self.gridmap
     }
 
     fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool {
         self.gridmap = gridmap.clone();
-        return true;
+        true
     }
 
     fn update_start(&mut self, start: (u32, u32)) -> bool {
         self.start = start;
-        return true;
+        true
+    }
+
+    fn update_goals(&mut self, goals: &[(u32, u32)]) -> bool {
+        self.goal_region.set_goals(goals)
+    }
+
+    fn update_goal_tolerance(&mut self, tolerance: f32) -> bool {
+        self.goal_region.set_tolerance(tolerance)
+    }
+
+    fn update_budget(&mut self, budget: &PlanningBudget) -> bool {
+        self.budget = budget.clone();
+        true
     }
 
-    fn update_goal(&mut self, goal: (u32, u32)) -> bool {
-        self.goal = goal;
-        return true;
+    fn update_trace_level(&mut self, trace_level: TraceLevel) -> bool {
+        self.trace_level = trace_level;
+        true
     }
 
 }
\ No newline at end of file
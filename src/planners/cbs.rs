@@ -0,0 +1,326 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::fmt;
+
+use super::planner_base::*;
+use super::planner_common::*;
+use super::space_time_a_star::*;
+use crate::maps::gridmap::Gridmap;
+
+// Conflict-Based Search
+
+/// `(cell, timestep)` pairs from goal to start, one entry per timestep
+type TimedPath = Vec<((u32, u32), u32)>;
+
+/// Largest number of constraint tree nodes expanded before giving up
+const DEFAULT_MAX_HIGH_LEVEL_NODES: usize = 10_000;
+
+/// Contains a collision-free timed path for every agent and the sum of their costs
+#[derive(Debug)]
+pub struct MAPFPlan {
+    /// Timed paths in the order of the agents, each running from goal to start as
+    /// `(cell, timestep)` pairs with one entry per timestep. Empty if no solution was found.
+    pub paths: Vec<Vec<((u32, u32), u32)>>,
+    /// Sum over the agents of the timestep at which they reach their goal for good
+    pub cost: u64,
+}
+
+impl MAPFPlan {
+    /// Collect the timed paths of the agents, costed by the sum of their arrival timesteps
+    pub fn from_paths(paths: Vec<Vec<((u32, u32), u32)>>) -> MAPFPlan {
+        MAPFPlan {
+            cost: get_sum_of_costs(&paths),
+            paths,
+        }
+    }
+}
+
+/// Returned when multi-agent planning is interrupted, holding the best partial result found
+#[derive(Debug)]
+pub struct MAPFPlanningError {
+    pub interruption: PlanningInterruption,
+    /// Paths of the agents planned so far, which may still conflict with each other
+    pub partial_plan: Box<MAPFPlan>,
+}
+
+impl fmt::Display for MAPFPlanningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.interruption)
+    }
+}
+
+impl std::error::Error for MAPFPlanningError {}
+
+/// Two agents occupying the same cell, or swapping cells, at a timestep
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    Vertex {
+        agents: (usize, usize),
+        pos: (u32, u32),
+        time: u32,
+    },
+    /// The first agent moves `from -> to` while the second moves `to -> from`, arriving at `time`
+    Edge {
+        agents: (usize, usize),
+        from: (u32, u32),
+        to: (u32, u32),
+        time: u32,
+    },
+}
+
+/// Plans collision-free paths for several agents sharing a gridmap. Agents move
+/// between 4 way neighbors or wait in place, taking one timestep per action, and stay
+/// at their goal once they arrive. The sum of arrival timesteps is minimised.
+pub struct CBSPlanner {
+    gridmap: Gridmap,
+    starts: Vec<(u32, u32)>,
+    goals: Vec<(u32, u32)>,
+    max_high_level_nodes: usize,
+    /// Shared by the constraint tree search and all the searches of single agents
+    budget: PlanningBudget,
+}
+
+impl CBSPlanner {
+    /// Constructor
+    pub fn new(gridmap: &Gridmap) -> CBSPlanner {
+        CBSPlanner {
+            gridmap: gridmap.clone(),
+            starts: Vec::new(),
+            goals: Vec::new(),
+            max_high_level_nodes: DEFAULT_MAX_HIGH_LEVEL_NODES,
+            budget: PlanningBudget::default(),
+        }
+    }
+
+    /// Retrieve collision-free paths for all agents. The paths are empty if any agent
+    /// cannot reach its goal, the node limit is exceeded or the planning budget runs out.
+    pub fn generate_plan(&self) -> MAPFPlan {
+        self.try_generate_plan().unwrap_or(MAPFPlan { paths: Vec::new(), cost: 0 })
+    }
+
+    /// Retrieve collision-free paths for all agents like `generate_plan`, or the paths of
+    /// the cheapest constraint tree node expanded if the planning budget runs out first
+    pub fn try_generate_plan(&self) -> Result<MAPFPlan, MAPFPlanningError> {
+        let no_plan = MAPFPlan { paths: Vec::new(), cost: 0 };
+        let mut budget_tracker = BudgetTracker::new(&self.budget);
+
+        // Space-time A* for each agent, replanned under its constraints
+        let mut planners: Vec<SpaceTimeAStarPlanner> = self
+            .starts
+            .iter()
+            .zip(&self.goals)
+            .map(|(start, goal)| {
+                let mut planner = SpaceTimeAStarPlanner::new(&self.gridmap);
+                planner.update_start_and_goal(*start, *goal);
+                planner
+            })
+            .collect();
+
+        let mut root = CTNode {
+            constraints: vec![ReservationTable::new(); self.starts.len()],
+            paths: Vec::new(),
+            cost: 0,
+        };
+        for (planner, constraints) in planners.iter_mut().zip(&root.constraints) {
+            let path = plan_agent(planner, constraints, &mut budget_tracker).map_err(|interruption| {
+                MAPFPlanningError { interruption, partial_plan: Box::new(MAPFPlan::from_paths(root.paths.clone())) }
+            })?;
+            match path {
+                Some(path) => root.paths.push(path),
+                None => return Ok(no_plan),
+            }
+        }
+        root.cost = get_sum_of_costs(&root.paths);
+
+        let mut nodes: Vec<CTNode> = vec![root];
+        let mut open_list: BinaryHeap<Reverse<HighLevelEntry>> = BinaryHeap::new();
+        open_list.push(Reverse(HighLevelEntry { cost: nodes[0].cost, idx: 0 }));
+
+        let mut num_expanded = 0;
+        while let Some(Reverse(entry)) = open_list.pop() {
+            // Nodes are expanded cheapest first, so the current one is the best partial result
+            let interrupted = |interruption: PlanningInterruption, node: &CTNode| MAPFPlanningError {
+                interruption,
+                partial_plan: Box::new(MAPFPlan::from_paths(node.paths.clone())),
+            };
+
+            budget_tracker.check().map_err(|interruption| interrupted(interruption, &nodes[entry.idx]))?;
+            num_expanded += 1;
+            if num_expanded > self.max_high_level_nodes {
+                break;
+            }
+
+            let Some(conflict) = find_first_conflict(&nodes[entry.idx].paths) else {
+                let node = nodes.swap_remove(entry.idx);
+                return Ok(MAPFPlan::from_paths(node.paths));
+            };
+
+            // Resolve the conflict by forbidding it for either agent in turn
+            let branches = match conflict {
+                Conflict::Vertex { agents, pos, time } => [
+                    (agents.0, Constraint::Vertex(pos, time)),
+                    (agents.1, Constraint::Vertex(pos, time)),
+                ],
+                Conflict::Edge { agents, from, to, time } => [
+                    (agents.0, Constraint::Edge(from, to, time)),
+                    (agents.1, Constraint::Edge(to, from, time)),
+                ],
+            };
+
+            for (agent, constraint) in branches {
+                let mut constraints = nodes[entry.idx].constraints.clone();
+                match constraint {
+                    Constraint::Vertex(pos, time) => constraints[agent].reserve_cell(pos, time),
+                    Constraint::Edge(from, to, time) => constraints[agent].reserve_edge(from, to, time),
+                }
+
+                let Some(path) = plan_agent(&mut planners[agent], &constraints[agent], &mut budget_tracker)
+                    .map_err(|interruption| interrupted(interruption, &nodes[entry.idx]))?
+                else {
+                    continue;
+                };
+
+                let mut paths = nodes[entry.idx].paths.clone();
+                paths[agent] = path;
+                let cost = get_sum_of_costs(&paths);
+
+                open_list.push(Reverse(HighLevelEntry { cost, idx: nodes.len() }));
+                nodes.push(CTNode { constraints, paths, cost });
+            }
+        }
+
+        Ok(no_plan)
+    }
+
+    pub fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool {
+        self.gridmap = gridmap.clone();
+        true
+    }
+
+    /// Set the start and goal of every agent, agent `i` moving from `starts[i]` to `goals[i]`.
+    /// Starts and goals must be traversable, and no two agents may share a start or a goal.
+    pub fn update_agents(&mut self, starts: &[(u32, u32)], goals: &[(u32, u32)]) -> bool {
+        if starts.len() != goals.len() {
+            return false;
+        }
+
+        let mut unique_starts: HashSet<(u32, u32)> = HashSet::new();
+        let mut unique_goals: HashSet<(u32, u32)> = HashSet::new();
+
+        for (start, goal) in starts.iter().zip(goals) {
+            if !self.gridmap.xy_is_traversable(*start)
+                || !self.gridmap.xy_is_traversable(*goal)
+                || !unique_starts.insert(*start)
+                || !unique_goals.insert(*goal)
+            {
+                return false;
+            }
+        }
+
+        self.starts = starts.to_vec();
+        self.goals = goals.to_vec();
+        true
+    }
+
+    /// Set the largest number of constraint tree nodes expanded before giving up
+    pub fn update_max_high_level_nodes(&mut self, max_high_level_nodes: usize) -> bool {
+        self.max_high_level_nodes = max_high_level_nodes;
+        true
+    }
+
+    pub fn update_budget(&mut self, budget: &PlanningBudget) -> bool {
+        self.budget = budget.clone();
+        true
+    }
+}
+
+/// Get the position of an agent at a timestep from its timed path, where
+/// it stays at the goal after the path ends
+pub fn get_position_at(path: &[((u32, u32), u32)], time: u32) -> (u32, u32) {
+    path.iter()
+        .find(|(_, path_time)| *path_time <= time)
+        .map_or(path[path.len() - 1].0, |(pos, _)| *pos)
+}
+
+/// Find the earliest vertex or edge conflict between any two timed paths
+pub fn find_first_conflict(paths: &[Vec<((u32, u32), u32)>]) -> Option<Conflict> {
+    let horizon = paths.iter().map(|path| path.len() as u32).max().unwrap_or(0);
+
+    for time in 0..horizon {
+        for first in 0..paths.len() {
+            for second in first + 1..paths.len() {
+                let first_pos = get_position_at(&paths[first], time);
+                let second_pos = get_position_at(&paths[second], time);
+
+                if first_pos == second_pos {
+                    return Some(Conflict::Vertex {
+                        agents: (first, second),
+                        pos: first_pos,
+                        time,
+                    });
+                }
+
+                if time == 0 {
+                    continue;
+                }
+
+                let first_prev = get_position_at(&paths[first], time - 1);
+                let second_prev = get_position_at(&paths[second], time - 1);
+                if first_prev == second_pos && second_prev == first_pos {
+                    return Some(Conflict::Edge {
+                        agents: (first, second),
+                        from: first_prev,
+                        to: first_pos,
+                        time,
+                    });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Plan a single agent with its constraints as reservations and what is left of the
+/// planning budget, None if it cannot reach its goal
+fn plan_agent(
+    planner: &mut SpaceTimeAStarPlanner,
+    constraints: &ReservationTable,
+    budget_tracker: &mut BudgetTracker,
+) -> Result<Option<TimedPath>, PlanningInterruption> {
+    planner.update_reservation_table(constraints);
+    planner.update_budget(&budget_tracker.get_remaining_budget());
+
+    let timed_plan = planner.try_generate_timed_plan().map_err(|error| error.interruption)?;
+    budget_tracker.add_expansions(timed_plan.stats.nodes_expanded);
+
+    Ok((!timed_plan.path.is_empty()).then_some(timed_plan.path))
+}
+
+/// Sum over the paths of the timestep at which the agent reaches its goal
+fn get_sum_of_costs(paths: &[Vec<((u32, u32), u32)>]) -> u64 {
+    paths.iter().map(|path| path[0].1 as u64).sum()
+}
+
+/// Constraint placed on one agent to resolve a conflict
+enum Constraint {
+    /// The agent may not be at the cell at the timestep
+    Vertex((u32, u32), u32),
+    /// The agent may not move between the cells, arriving at the timestep
+    Edge((u32, u32), (u32, u32), u32),
+}
+
+/// Node of the constraint tree
+struct CTNode {
+    /// Constraints of each agent, kept as the reservations it must avoid
+    constraints: Vec<ReservationTable>,
+    paths: Vec<Vec<((u32, u32), u32)>>,
+    cost: u64,
+}
+
+/// Constraint tree node waiting to be expanded, ordered by cost and then by creation
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct HighLevelEntry {
+    cost: u64,
+    idx: usize,
+}
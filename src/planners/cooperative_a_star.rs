@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+
+use super::cbs::{get_position_at, MAPFPlan, MAPFPlanningError};
+use super::planner_base::*;
+use super::planner_common::*;
+use super::space_time_a_star::*;
+use crate::maps::gridmap::Gridmap;
+
+// Cooperative A Star
+
+/// `(cell, timestep)` pairs from goal to start, one entry per timestep
+type TimedPath = Vec<((u32, u32), u32)>;
+
+/// Largest number of timesteps simulated by windowed planning before giving up
+const DEFAULT_MAX_TIMESTEPS: u32 = 1000;
+
+/// Plans several agents sharing a gridmap one at a time in priority order, each
+/// reserving its space-time path so that later agents plan around it. Faster than
+/// Conflict-Based Search, but neither optimal nor guaranteed to find a solution.
+///
+/// With a window set, this becomes Windowed Hierarchical Cooperative A* (WHCA*):
+/// agents only reserve the next `window` timesteps, execute half of that and replan.
+pub struct CooperativeAStarPlanner {
+    gridmap: Gridmap,
+    /// Agents in priority order, the first planned first
+    starts: Vec<(u32, u32)>,
+    goals: Vec<(u32, u32)>,
+    /// Number of timesteps planned ahead per replanning cycle
+    window: u32,
+    max_timesteps: u32,
+    /// Shared by the searches of all agents over every replanning cycle
+    budget: PlanningBudget,
+}
+
+impl CooperativeAStarPlanner {
+    /// Constructor
+    pub fn new(gridmap: &Gridmap) -> CooperativeAStarPlanner {
+        CooperativeAStarPlanner {
+            gridmap: gridmap.clone(),
+            starts: Vec::new(),
+            goals: Vec::new(),
+            window: u32::MAX,
+            max_timesteps: DEFAULT_MAX_TIMESTEPS,
+            budget: PlanningBudget::default(),
+        }
+    }
+
+    /// Retrieve collision-free paths for all agents. The paths are empty if an agent gets
+    /// stuck, does not reach its goal within the timestep limit or the planning budget runs out.
+    pub fn generate_plan(&self) -> MAPFPlan {
+        self.try_generate_plan().unwrap_or(MAPFPlan { paths: Vec::new(), cost: 0 })
+    }
+
+    /// Retrieve collision-free paths for all agents like `generate_plan`, or the paths of
+    /// the agents planned so far if the planning budget runs out first
+    pub fn try_generate_plan(&self) -> Result<MAPFPlan, MAPFPlanningError> {
+        let mut budget_tracker = BudgetTracker::new(&self.budget);
+        let planners: Vec<SpaceTimeAStarPlanner> = self
+            .starts
+            .iter()
+            .zip(&self.goals)
+            .map(|(start, goal)| {
+                let mut planner = SpaceTimeAStarPlanner::new(&self.gridmap);
+                planner.update_start_and_goal(*start, *goal);
+                planner.update_window(self.window);
+                planner
+            })
+            .collect();
+
+        let paths = if self.window == u32::MAX {
+            self.plan_cooperative(planners, &mut budget_tracker)?
+        } else {
+            self.plan_windowed(planners, &mut budget_tracker)?
+        };
+
+        Ok(match paths {
+            Some(paths) => MAPFPlan::from_paths(paths),
+            None => MAPFPlan { paths: Vec::new(), cost: 0 },
+        })
+    }
+
+    pub fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool {
+        self.gridmap = gridmap.clone();
+        true
+    }
+
+    /// Set the start and goal of every agent in priority order, agent `i` moving from
+    /// `starts[i]` to `goals[i]`. Starts and goals must be traversable, and no two
+    /// agents may share a start or a goal.
+    pub fn update_agents(&mut self, starts: &[(u32, u32)], goals: &[(u32, u32)]) -> bool {
+        if starts.len() != goals.len() {
+            return false;
+        }
+
+        let mut unique_starts: HashSet<(u32, u32)> = HashSet::new();
+        let mut unique_goals: HashSet<(u32, u32)> = HashSet::new();
+
+        for (start, goal) in starts.iter().zip(goals) {
+            if !self.gridmap.xy_is_traversable(*start)
+                || !self.gridmap.xy_is_traversable(*goal)
+                || !unique_starts.insert(*start)
+                || !unique_goals.insert(*goal)
+            {
+                return false;
+            }
+        }
+
+        self.starts = starts.to_vec();
+        self.goals = goals.to_vec();
+        true
+    }
+
+    /// Set how many timesteps ahead each agent reserves before replanning,
+    /// at least 2. u32::MAX plans every agent to its goal in one go.
+    pub fn update_window(&mut self, window: u32) -> bool {
+        if window < 2 {
+            return false;
+        }
+        self.window = window;
+        true
+    }
+
+    /// Set the largest number of timesteps simulated by windowed planning
+    pub fn update_max_timesteps(&mut self, max_timesteps: u32) -> bool {
+        self.max_timesteps = max_timesteps;
+        true
+    }
+
+    pub fn update_budget(&mut self, budget: &PlanningBudget) -> bool {
+        self.budget = budget.clone();
+        true
+    }
+
+    /// Plan every agent all the way to its goal in priority order. Agents hold
+    /// their goal once they arrive, so later agents route around them.
+    fn plan_cooperative(
+        &self,
+        mut planners: Vec<SpaceTimeAStarPlanner>,
+        budget_tracker: &mut BudgetTracker,
+    ) -> Result<Option<Vec<TimedPath>>, MAPFPlanningError> {
+        let mut reservation_table = ReservationTable::new();
+        let mut paths: Vec<TimedPath> = Vec::new();
+
+        for planner in planners.iter_mut() {
+            planner.update_reservation_table(&reservation_table);
+
+            let path = plan_agent(planner, budget_tracker).map_err(|interruption| MAPFPlanningError {
+                interruption,
+                partial_plan: Box::new(MAPFPlan::from_paths(paths.clone())),
+            })?;
+            let Some((goal, arrival_time)) = path.first().copied() else {
+                return Ok(None);
+            };
+
+            reservation_table.reserve_path(&path);
+            reservation_table.reserve_cell_from(goal, arrival_time);
+            paths.push(path);
+        }
+
+        Ok(Some(paths))
+    }
+
+    /// Repeatedly plan all agents a window ahead in priority order, then move
+    /// them half a window along their plans, until every agent is at its goal.
+    /// If the planning budget runs out, the partial paths are the moves made so far.
+    fn plan_windowed(
+        &self,
+        mut planners: Vec<SpaceTimeAStarPlanner>,
+        budget_tracker: &mut BudgetTracker,
+    ) -> Result<Option<Vec<TimedPath>>, MAPFPlanningError> {
+        let num_steps = self.window / 2;
+
+        // Positions of each agent at every timestep so far
+        let mut histories: Vec<Vec<(u32, u32)>> = self.starts.iter().map(|start| vec![*start]).collect();
+        let mut time = 0;
+
+        while histories.iter().zip(&self.goals).any(|(history, goal)| history[time as usize] != *goal) {
+            if time >= self.max_timesteps {
+                return Ok(None);
+            }
+            let interrupted = |interruption: PlanningInterruption| MAPFPlanningError {
+                interruption,
+                partial_plan: Box::new(MAPFPlan::from_paths(
+                    histories.iter().map(|history| get_timed_path(&history[..=time as usize])).collect(),
+                )),
+            };
+            budget_tracker.check().map_err(interrupted)?;
+
+            let mut reservation_table = ReservationTable::new();
+            let mut window_paths: Vec<TimedPath> = Vec::new();
+
+            for (planner, history) in planners.iter_mut().zip(&histories) {
+                planner.update_start(history[time as usize]);
+                planner.update_reservation_table(&reservation_table);
+
+                let path = plan_agent(planner, budget_tracker).map_err(interrupted)?;
+                let Some((last_pos, last_time)) = path.first().copied() else {
+                    return Ok(None);
+                };
+
+                // Agents arriving within the window stay at their goal until its end
+                reservation_table.reserve_path(&path);
+                if last_time < self.window {
+                    reservation_table.reserve_cell_from(last_pos, last_time);
+                }
+                window_paths.push(path);
+            }
+
+            for (history, path) in histories.iter_mut().zip(&window_paths) {
+                history.extend((1..=num_steps).map(|step| get_position_at(path, step)));
+            }
+            time += num_steps;
+        }
+
+        // Drop the waiting at the goal after each agent's final arrival, and
+        // turn the histories into timed paths from goal to start
+        let paths = histories
+            .iter()
+            .zip(&self.goals)
+            .map(|(history, goal)| {
+                let arrival_time = history.iter().rposition(|pos| pos != goal).map_or(0, |time| time + 1);
+                get_timed_path(&history[..=arrival_time])
+            })
+            .collect();
+
+        Ok(Some(paths))
+    }
+}
+
+/// Turn the positions of an agent at every timestep into a timed path from goal to start
+fn get_timed_path(history: &[(u32, u32)]) -> TimedPath {
+    history
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(time, pos)| (*pos, time as u32))
+        .collect()
+}
+
+/// Plan a single agent with what is left of the planning budget
+fn plan_agent(
+    planner: &mut SpaceTimeAStarPlanner,
+    budget_tracker: &mut BudgetTracker,
+) -> Result<TimedPath, PlanningInterruption> {
+    planner.update_budget(&budget_tracker.get_remaining_budget());
+
+    let timed_plan = planner.try_generate_timed_plan().map_err(|error| error.interruption)?;
+    budget_tracker.add_expansions(timed_plan.stats.nodes_expanded);
+
+    Ok(timed_plan.path)
+}
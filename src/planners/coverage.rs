@@ -0,0 +1,541 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::time::Instant;
+
+use super::a_star::AStarPlanner;
+use super::planner_base::*;
+use super::planner_common::*;
+use super::tour::*;
+use crate::maps::gridmap::Gridmap;
+
+// Coverage Path Planning
+
+/// Decomposition cells are ordered exactly when there are at most this many
+const MAX_EXACT_ORDERED_CELLS: usize = 12;
+
+/// How the free space is swept
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoveragePattern {
+    /// Lawnmower sweeps within each cell of a boustrophedon decomposition
+    Boustrophedon,
+    /// Inward spirals over blocks of the tool width, jumping to the nearest
+    /// uncovered block whenever the spiral gets stuck
+    Spiral,
+}
+
+/// Orientation of the sweep lines of the boustrophedon pattern
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SweepDirection {
+    /// Sweep lines run along the y-axis, moving across the map along x
+    Vertical,
+    /// Sweep lines run along the x-axis, moving across the map along y
+    Horizontal,
+}
+
+/// A cell of the boustrophedon decomposition, made up of one free
+/// segment per sweep line it spans, given as its two end cells
+#[derive(Clone, Debug)]
+pub struct CoverageCell {
+    pub segments: Vec<((u32, u32), (u32, u32))>,
+}
+
+/// Contains the coverage path and the percentage of free space it covers
+#[derive(Debug)]
+pub struct CoveragePlan {
+    /// Path from the end of the coverage back to the start, like other motion plans
+    pub motion_plan: MotionPlan,
+    /// Percentage of the traversable cells reachable from the start swept by the tool
+    pub coverage: f32,
+    /// Waypoints no path could be found to, which the path skips
+    pub unreachable_waypoints: Vec<(u32, u32)>,
+}
+
+/// Returned when coverage planning is interrupted, holding the best partial result found
+#[derive(Debug)]
+pub struct CoveragePlanningError {
+    pub interruption: PlanningInterruption,
+    /// Path through the waypoints linked so far and the coverage it reaches
+    pub partial_plan: Box<CoveragePlan>,
+}
+
+impl fmt::Display for CoveragePlanningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.interruption)
+    }
+}
+
+impl std::error::Error for CoveragePlanningError {}
+
+/// Plans a path sweeping a square tool over all free space reachable from the start
+pub struct CoveragePlanner {
+    start: (u32, u32),
+    gridmap: Gridmap,
+    pattern: CoveragePattern,
+    sweep_direction: SweepDirection,
+    /// Width of the tool in cells
+    tool_width: u32,
+    /// Shared by the A* searches linking the waypoints
+    budget: PlanningBudget,
+}
+
+impl CoveragePlanner {
+    /// Constructor
+    pub fn new(gridmap: &Gridmap) -> CoveragePlanner {
+        CoveragePlanner {
+            start: (0, 0),
+            gridmap: gridmap.clone(),
+            pattern: CoveragePattern::Boustrophedon,
+            sweep_direction: SweepDirection::Vertical,
+            tool_width: 1,
+            budget: PlanningBudget::default(),
+        }
+    }
+
+    /// Retrieve a path covering all free space reachable from the start. The path
+    /// is empty if the planning budget runs out.
+    pub fn generate_plan(&self) -> CoveragePlan {
+        self.try_generate_plan().unwrap_or_else(|_| CoveragePlan {
+            motion_plan: MotionPlan {
+                path: Vec::new(),
+                closed_list: HashSet::new(),
+                stats: SearchStats::default(),
+                trace: None,
+            },
+            coverage: 0.0,
+            unreachable_waypoints: Vec::new(),
+        })
+    }
+
+    /// Retrieve a path covering all free space like `generate_plan`, or the path
+    /// through the waypoints linked so far if the planning budget runs out first
+    pub fn try_generate_plan(&self) -> Result<CoveragePlan, CoveragePlanningError> {
+        let waypoints = match self.pattern {
+            CoveragePattern::Boustrophedon => self.get_boustrophedon_waypoints(),
+            CoveragePattern::Spiral => self.get_spiral_waypoints(),
+        };
+
+        self.link_waypoints(&waypoints)
+    }
+
+    /// Split the free space into cells that can each be covered by simple back and forth sweeps
+    pub fn get_decomposition(&self) -> Vec<CoverageCell> {
+        self.decompose()
+            .iter()
+            .map(|cell| CoverageCell {
+                segments: cell
+                    .iter()
+                    .map(|(u, v_start, v_end)| (self.to_xy(*u, *v_start), self.to_xy(*u, *v_end)))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Get the percentage of traversable cells reachable from the start
+    /// that are swept by the tool while following a path, which is 0 when the start is blocked
+    pub fn get_coverage(&self, path: &[(u32, u32)]) -> f32 {
+        let cost_field = get_cost_field(&self.gridmap, &[self.start]);
+        let is_reachable = |pos: (u32, u32)| cost_field[self.gridmap.xy_to_idx(pos)] != u32::MAX;
+
+        let num_reachable = cost_field.iter().filter(|cost| **cost != u32::MAX).count();
+        if num_reachable == 0 {
+            return 0.0;
+        }
+
+        let mut covered: HashSet<(u32, u32)> = HashSet::new();
+        for pos in path {
+            for cell in self.get_footprint(*pos) {
+                if is_reachable(cell) {
+                    covered.insert(cell);
+                }
+            }
+        }
+
+        covered.len() as f32 / num_reachable as f32 * 100.0
+    }
+
+    pub fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool {
+        self.gridmap = gridmap.clone();
+        true
+    }
+
+    pub fn update_start(&mut self, start: (u32, u32)) -> bool {
+        if !self.gridmap.xy_in_map(start) {
+            return false;
+        }
+        self.start = start;
+        true
+    }
+
+    pub fn update_pattern(&mut self, pattern: CoveragePattern) -> bool {
+        self.pattern = pattern;
+        true
+    }
+
+    pub fn update_sweep_direction(&mut self, sweep_direction: SweepDirection) -> bool {
+        self.sweep_direction = sweep_direction;
+        true
+    }
+
+    /// Set the width of the tool in cells, which must be at least 1
+    pub fn update_tool_width(&mut self, tool_width: u32) -> bool {
+        if tool_width == 0 {
+            return false;
+        }
+        self.tool_width = tool_width;
+        true
+    }
+
+    pub fn update_budget(&mut self, budget: &PlanningBudget) -> bool {
+        self.budget = budget.clone();
+        true
+    }
+
+    /// Join consecutive waypoints with straight lines along the sweep lines and
+    /// between neighboring blocks, and with A* wherever they are not in line.
+    /// Waypoints A* finds no path to are skipped, and returned alongside the plan.
+    fn link_waypoints(&self, waypoints: &[(u32, u32)]) -> Result<CoveragePlan, CoveragePlanningError> {
+        let start_time = Instant::now();
+        let mut budget_tracker = BudgetTracker::new(&self.budget);
+        let mut path: Vec<(u32, u32)> = vec![waypoints[0]];
+        let mut closed_list: HashSet<(u32, u32)> = HashSet::new();
+        let mut stats = SearchStats::default();
+        let mut planner = AStarPlanner::new(&self.gridmap);
+        let mut unreachable_waypoints: Vec<(u32, u32)> = Vec::new();
+        let mut interruption: Option<PlanningInterruption> = None;
+
+        for &to in waypoints.iter().skip(1) {
+            let from = path[path.len() - 1];
+            if let Err(error) = budget_tracker.check() {
+                interruption = Some(error);
+                break;
+            }
+
+            let line: Vec<(u32, u32)> = if from.0 == to.0 {
+                get_range(from.1, to.1).map(|y| (from.0, y)).collect()
+            } else if from.1 == to.1 {
+                get_range(from.0, to.0).map(|x| (x, from.1)).collect()
+            } else {
+                Vec::new()
+            };
+
+            if !line.is_empty() && line.iter().all(|cell| self.gridmap.xy_is_traversable(*cell)) {
+                path.extend(line.into_iter().skip(1));
+                continue;
+            }
+
+            planner.update_start_and_goal(from, to);
+            planner.update_budget(&budget_tracker.get_remaining_budget());
+            let leg_plan = match planner.try_generate_plan() {
+                Ok(leg_plan) => leg_plan,
+                Err(error) => {
+                    closed_list.extend(error.partial_plan.closed_list);
+                    stats.merge(&error.partial_plan.stats);
+                    interruption = Some(error.interruption);
+                    break;
+                }
+            };
+            budget_tracker.add_expansions(leg_plan.stats.nodes_expanded);
+            closed_list.extend(leg_plan.closed_list);
+            stats.merge(&leg_plan.stats);
+
+            if leg_plan.path.is_empty() && from != to {
+                unreachable_waypoints.push(to);
+                continue;
+            }
+            path.extend(leg_plan.path.into_iter().rev().skip(1));
+        }
+
+        path.reverse();
+        stats.finish(&path, &self.gridmap, start_time);
+        let coverage_plan = CoveragePlan {
+            coverage: self.get_coverage(&path),
+            motion_plan: MotionPlan { path, closed_list, stats, trace: None },
+            unreachable_waypoints,
+        };
+
+        match interruption {
+            Some(interruption) => Err(CoveragePlanningError { interruption, partial_plan: Box::new(coverage_plan) }),
+            None => Ok(coverage_plan),
+        }
+    }
+
+    /// Get the cells swept by the tool when centered on a cell
+    fn get_footprint(&self, pos: (u32, u32)) -> Vec<(u32, u32)> {
+        let (lower, upper) = ((self.tool_width - 1) / 2, self.tool_width / 2);
+
+        let x_range = pos.0.saturating_sub(lower)..=(pos.0 + upper).min(self.gridmap.get_width() - 1);
+        let y_range = pos.1.saturating_sub(lower)..=(pos.1 + upper).min(self.gridmap.get_height() - 1);
+
+        y_range
+            .flat_map(|y| x_range.clone().map(move |x| (x, y)))
+            .collect()
+    }
+
+    /// Convert from sweep coordinates, the sweep line u and position v
+    /// along it, to a 2D position in the gridmap
+    fn to_xy(&self, u: u32, v: u32) -> (u32, u32) {
+        match self.sweep_direction {
+            SweepDirection::Vertical => (u, v),
+            SweepDirection::Horizontal => (v, u),
+        }
+    }
+
+    /// Get the number of sweep lines and the length of each of them
+    fn get_sweep_dimensions(&self) -> (u32, u32) {
+        match self.sweep_direction {
+            SweepDirection::Vertical => (self.gridmap.get_width(), self.gridmap.get_height()),
+            SweepDirection::Horizontal => (self.gridmap.get_height(), self.gridmap.get_width()),
+        }
+    }
+
+    /// Boustrophedon decomposition in sweep coordinates. Each cell is a list of
+    /// (u, v_start, v_end) segments on consecutive sweep lines. A cell ends whenever
+    /// the connectivity of the free segments changes from one sweep line to the next.
+    fn decompose(&self) -> Vec<Vec<(u32, u32, u32)>> {
+        let (num_lines, line_length) = self.get_sweep_dimensions();
+
+        let mut cells: Vec<Vec<(u32, u32, u32)>> = Vec::new();
+        // Segments on the previous sweep line and the cells they belong to
+        let mut prev_segments: Vec<((u32, u32), usize)> = Vec::new();
+
+        for u in 0..num_lines {
+            // Free segments along the sweep line
+            let mut segments: Vec<(u32, u32)> = Vec::new();
+            let mut segment_start: Option<u32> = None;
+            for v in 0..line_length {
+                let is_free = self.gridmap.xy_is_traversable(self.to_xy(u, v));
+                match (is_free, segment_start) {
+                    (true, None) => segment_start = Some(v),
+                    (false, Some(start)) => {
+                        segments.push((start, v - 1));
+                        segment_start = None;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(start) = segment_start {
+                segments.push((start, line_length - 1));
+            }
+
+            let overlaps = |a: &(u32, u32), b: &(u32, u32)| a.0 <= b.1 && b.0 <= a.1;
+
+            let mut cur_segments: Vec<((u32, u32), usize)> = Vec::new();
+            for segment in segments.iter() {
+                let prev_matches: Vec<&((u32, u32), usize)> = prev_segments
+                    .iter()
+                    .filter(|(prev, _)| overlaps(prev, segment))
+                    .collect();
+
+                // Continue a cell only where one segment leads to exactly one other
+                let cell = match prev_matches[..] {
+                    [(prev, cell)] if segments.iter().filter(|other| overlaps(prev, other)).count() == 1 => *cell,
+                    _ => {
+                        cells.push(Vec::new());
+                        cells.len() - 1
+                    }
+                };
+
+                cells[cell].push((u, segment.0, segment.1));
+                cur_segments.push((*segment, cell));
+            }
+
+            prev_segments = cur_segments;
+        }
+
+        cells
+    }
+
+    /// Waypoints visiting the decomposition cells in the cheapest order from the
+    /// start, sweeping back and forth across each cell
+    fn get_boustrophedon_waypoints(&self) -> Vec<(u32, u32)> {
+        let cell_sweeps: Vec<Vec<(u32, u32)>> = self
+            .decompose()
+            .iter()
+            .map(|cell| self.get_cell_sweeps(cell))
+            .collect();
+
+        // Order the cells reachable from the start by their first waypoint
+        let cost_field = get_cost_field(&self.gridmap, &[self.start]);
+        let reachable_sweeps: Vec<&Vec<(u32, u32)>> = cell_sweeps
+            .iter()
+            .filter(|sweeps| cost_field[self.gridmap.xy_to_idx(sweeps[0])] != u32::MAX)
+            .collect();
+
+        let mut entries: Vec<(u32, u32)> = vec![self.start];
+        entries.extend(reachable_sweeps.iter().map(|sweeps| sweeps[0]));
+
+        // Straight line distances keep ordering cheap on maps with many cells
+        let cost_matrix: Vec<Vec<u32>> = entries
+            .iter()
+            .map(|a| entries.iter().map(|b| get_l2_cost(*a, *b)).collect())
+            .collect();
+        let order = if entries.len() <= MAX_EXACT_ORDERED_CELLS {
+            solve_tour_exact(&cost_matrix, false)
+        } else {
+            solve_tour_heuristic(&cost_matrix, false)
+        };
+
+        let mut waypoints: Vec<(u32, u32)> = vec![self.start];
+        for idx in order.unwrap_or_default().iter().skip(1) {
+            waypoints.extend(reachable_sweeps[idx - 1].iter().copied());
+        }
+
+        waypoints
+    }
+
+    /// Waypoints of the back and forth sweeps across a cell, with sweep lines
+    /// spaced a tool width apart so that every sweep line of the cell is swept
+    fn get_cell_sweeps(&self, cell: &[(u32, u32, u32)]) -> Vec<(u32, u32)> {
+        let (u_first, u_last) = (cell[0].0, cell[cell.len() - 1].0);
+        let (lower, upper) = ((self.tool_width - 1) / 2, self.tool_width / 2);
+
+        let mut lines: Vec<u32> = (u_first + lower..=u_last).step_by(self.tool_width as usize).collect();
+        match lines.last() {
+            None => lines.push(u_first + (u_last - u_first) / 2),
+            Some(last) if last + upper < u_last => lines.push(u_last - upper),
+            _ => {}
+        }
+
+        let mut waypoints: Vec<(u32, u32)> = Vec::new();
+        for (i, u) in lines.iter().enumerate() {
+            let (_, v_start, v_end) = cell[(u - u_first) as usize];
+
+            if i % 2 == 0 {
+                waypoints.push(self.to_xy(*u, v_start));
+                waypoints.push(self.to_xy(*u, v_end));
+            } else {
+                waypoints.push(self.to_xy(*u, v_end));
+                waypoints.push(self.to_xy(*u, v_start));
+            }
+        }
+
+        waypoints
+    }
+
+    /// Waypoints at the centers of tool sized blocks, visited in inward spirals
+    /// that keep the covered area and obstacles to their right
+    fn get_spiral_waypoints(&self) -> Vec<(u32, u32)> {
+        let size = self.tool_width;
+        let (blocks_x, blocks_y) = (
+            self.gridmap.get_width().div_ceil(size),
+            self.gridmap.get_height().div_ceil(size),
+        );
+
+        let get_bounds = |block: (u32, u32)| {
+            let origin = (block.0 * size, block.1 * size);
+            let end = (
+                (origin.0 + size).min(self.gridmap.get_width()),
+                (origin.1 + size).min(self.gridmap.get_height()),
+            );
+            (origin, end)
+        };
+        let get_center = |block: (u32, u32)| {
+            let (origin, end) = get_bounds(block);
+            (origin.0 + (end.0 - origin.0 - 1) / 2, origin.1 + (end.1 - origin.1 - 1) / 2)
+        };
+
+        // Blocks the tool fits into entirely and which can be reached from the start
+        let cost_field = get_cost_field(&self.gridmap, &[self.start]);
+        let is_free: Vec<bool> = (0..blocks_x * blocks_y)
+            .map(|idx| {
+                let (origin, end) = get_bounds((idx % blocks_x, idx / blocks_x));
+                (origin.1..end.1).all(|y| (origin.0..end.0).all(|x| self.gridmap.xy_is_traversable((x, y))))
+                    && cost_field[self.gridmap.xy_to_idx(get_center((idx % blocks_x, idx / blocks_x)))] != u32::MAX
+            })
+            .collect();
+
+        let block_idx = |block: (u32, u32)| (block.1 * blocks_x + block.0) as usize;
+        let step = |block: (u32, u32), dir: usize| -> Option<(u32, u32)> {
+            let (dx, dy) = [(1, 0), (0, 1), (-1, 0), (0, -1)][dir];
+            let (x, y) = (block.0 as i64 + dx, block.1 as i64 + dy);
+            if x < 0 || y < 0 || x >= blocks_x as i64 || y >= blocks_y as i64 {
+                return None;
+            }
+            Some((x as u32, y as u32))
+        };
+
+        // Start from the free block nearest the start
+        let Some(mut block) = (0..blocks_x * blocks_y)
+            .filter(|idx| is_free[*idx as usize])
+            .map(|idx| (idx % blocks_x, idx / blocks_x))
+            .min_by_key(|block| cost_field[self.gridmap.xy_to_idx(get_center(*block))])
+        else {
+            return vec![self.start];
+        };
+
+        let mut covered: Vec<bool> = vec![false; (blocks_x * blocks_y) as usize];
+        let mut waypoints: Vec<(u32, u32)> = vec![self.start];
+
+        // Directions are indexed clockwise from +x, so a right turn adds 1
+        let can_enter = |covered: &[bool], block: (u32, u32), dir: usize| {
+            step(block, dir).filter(|next| is_free[block_idx(*next)] && !covered[block_idx(*next)])
+        };
+        let face_wall = |covered: &[bool], block: (u32, u32)| {
+            (0..4).find(|dir| can_enter(covered, block, (dir + 1) % 4).is_none()).unwrap_or(0)
+        };
+        let mut dir = face_wall(&covered, block);
+
+        loop {
+            covered[block_idx(block)] = true;
+            waypoints.push(get_center(block));
+
+            // Prefer turning right, then going straight, then turning left or back
+            let next_move = [1, 0, 3, 2]
+                .iter()
+                .map(|turn| (dir + turn) % 4)
+                .find_map(|next_dir| can_enter(&covered, block, next_dir).map(|next| (next, next_dir)));
+
+            if let Some((next, next_dir)) = next_move {
+                (block, dir) = (next, next_dir);
+                continue;
+            }
+
+            // Stuck, so continue from the nearest uncovered block
+            let mut queue: VecDeque<(u32, u32)> = VecDeque::from([block]);
+            let mut visited: HashSet<(u32, u32)> = HashSet::from([block]);
+            let mut nearest_uncovered: Option<(u32, u32)> = None;
+
+            while let Some(cur_block) = queue.pop_front() {
+                if !covered[block_idx(cur_block)] {
+                    nearest_uncovered = Some(cur_block);
+                    break;
+                }
+                for nb_dir in 0..4 {
+                    if let Some(nb_block) = step(cur_block, nb_dir) {
+                        if is_free[block_idx(nb_block)] && visited.insert(nb_block) {
+                            queue.push_back(nb_block);
+                        }
+                    }
+                }
+            }
+
+            // Blocks may only be connected through gaps narrower than the tool
+            if nearest_uncovered.is_none() && (0..covered.len()).any(|idx| is_free[idx] && !covered[idx]) {
+                let block_cost_field = get_cost_field(&self.gridmap, &[get_center(block)]);
+                nearest_uncovered = (0..blocks_x * blocks_y)
+                    .filter(|idx| is_free[*idx as usize] && !covered[*idx as usize])
+                    .map(|idx| (idx % blocks_x, idx / blocks_x))
+                    .min_by_key(|block| block_cost_field[self.gridmap.xy_to_idx(get_center(*block))]);
+            }
+
+            match nearest_uncovered {
+                Some(next) => {
+                    block = next;
+                    dir = face_wall(&covered, block);
+                }
+                None => break,
+            }
+        }
+
+        waypoints
+    }
+}
+
+/// Iterate from one value to another inclusive, in either direction
+fn get_range(from: u32, to: u32) -> Box<dyn Iterator<Item = u32>> {
+    if from <= to {
+        Box::new(from..=to)
+    } else {
+        Box::new((to..=from).rev())
+    }
+}
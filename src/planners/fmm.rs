@@ -0,0 +1,413 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+use std::time::Instant;
+
+use super::planner_base::*;
+use super::planner_common::*;
+use crate::maps::gridmap::*;
+
+// Fast Marching Method
+
+/// Distance (in cells) moved along the negative gradient per back-tracing step
+const TRACE_STEP_SIZE: f32 = 0.5;
+
+/// Movement cost of crossing a cell at unit speed in the units of `get_l2_cost`, scaling
+/// arrival times into the g costs recorded in search traces
+const ARRIVAL_TIME_COST_SCALE: f32 = 100.0;
+
+pub struct FMMPlanner {
+    start: (u32, u32),
+    goal_region: GoalRegion,
+    gridmap: Gridmap,
+    /// How strongly cell costs slow down the front. A cell with cost `c`
+    /// is traversed at a speed of `1 / (1 + cost_factor * c / INSCRIBED_INFLATED_OBSTACLE)`
+    cost_factor: f32,
+    budget: PlanningBudget,
+    trace_level: TraceLevel,
+}
+
+impl Planner for FMMPlanner {
+    fn new(gridmap: &Gridmap) -> FMMPlanner {
+        FMMPlanner {
+            start: (0, 0),
+            goal_region: GoalRegion::new(&[(0, 0)], 0.0),
+            gridmap: gridmap.clone(),
+            cost_factor: 3.0,
+            budget: PlanningBudget::default(),
+            trace_level: TraceLevel::Off,
+        }
+    }
+
+    fn try_generate_plan(&self) -> Result<MotionPlan, PlanningError> {
+        let start_time = Instant::now();
+        let mut closed_list: HashSet<(u32, u32)> = HashSet::new();
+        let mut stats = SearchStats::default();
+        let mut trace_recorder = TraceRecorder::new(self.trace_level);
+
+        // The front grows from the goal, so there is no partial path to the start yet
+        let arrival_times = match self.compute_arrival_times(&mut closed_list, &mut stats, &mut trace_recorder) {
+            Ok(arrival_times) => arrival_times,
+            Err(interruption) => {
+                stats.finish(&[], &self.gridmap, start_time);
+                let partial_plan = MotionPlan {
+                    path: Vec::new(),
+                    closed_list,
+                    stats,
+                    trace: trace_recorder.finish(),
+                };
+                return Err(PlanningError { interruption, partial_plan: Box::new(partial_plan) });
+            }
+        };
+
+        let mut path: Vec<(u32, u32)> = Vec::new();
+        for point in self.trace_gradient(&arrival_times) {
+            let cell = (point.0.round() as u32, point.1.round() as u32);
+            if path.last() != Some(&cell) {
+                path.push(cell);
+            }
+        }
+
+        stats.finish(&path, &self.gridmap, start_time);
+        Ok(MotionPlan {
+            path,
+            closed_list,
+            stats,
+            trace: trace_recorder.finish(),
+        })
+    }
+
+    fn get_gridmap(&self) -> &Gridmap {
+        &self.gridmap
+    }
+
+    fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool {
+        self.gridmap = gridmap.clone();
+        true
+    }
+
+    fn update_start(&mut self, start: (u32, u32)) -> bool {
+        self.start = start;
+        true
+    }
+
+    fn update_goals(&mut self, goals: &[(u32, u32)]) -> bool {
+        self.goal_region.set_goals(goals)
+    }
+
+    fn update_goal_tolerance(&mut self, tolerance: f32) -> bool {
+        self.goal_region.set_tolerance(tolerance)
+    }
+
+    fn update_budget(&mut self, budget: &PlanningBudget) -> bool {
+        self.budget = budget.clone();
+        true
+    }
+
+    fn update_trace_level(&mut self, trace_level: TraceLevel) -> bool {
+        self.trace_level = trace_level;
+        true
+    }
+}
+
+impl FMMPlanner {
+    /// Set how strongly cell costs slow down the front, must be non-negative
+    pub fn update_cost_factor(&mut self, cost_factor: f32) -> bool {
+        if cost_factor < 0.0 || !cost_factor.is_finite() {
+            return false;
+        }
+        self.cost_factor = cost_factor;
+        true
+    }
+
+    /// Retrieve a continuous path from goal to start, in cell units where
+    /// integer coordinates lie on cell centroids. Empty if the goal is unreachable
+    /// or the planning budget runs out.
+    pub fn generate_smooth_path(&self) -> Vec<(f32, f32)> {
+        let mut trace_recorder = TraceRecorder::new(TraceLevel::Off);
+
+        match self.compute_arrival_times(&mut HashSet::new(), &mut SearchStats::default(), &mut trace_recorder) {
+            Ok(arrival_times) => self.trace_gradient(&arrival_times),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Time taken for the front to cross a cell, the inverse of its speed
+    fn get_traversal_time(&self, idx: usize) -> f32 {
+        let cost = self.gridmap.get_val_idx(idx) as f32 / INSCRIBED_INFLATED_OBSTACLE as f32;
+
+        1.0 + self.cost_factor * cost
+    }
+
+    /// Solve the Eikonal equation outwards from the goal region until the start is reached.
+    /// Returns the arrival time of every cell, adding the frozen cells to the closed list.
+    /// Traced expansions have the arrival time as their cost and the neighbor the front
+    /// arrived from as their parent.
+    fn compute_arrival_times(
+        &self,
+        closed_list: &mut HashSet<(u32, u32)>,
+        stats: &mut SearchStats,
+        trace_recorder: &mut TraceRecorder,
+    ) -> Result<Vec<f32>, PlanningInterruption> {
+        let mut arrival_times: Vec<f32> = vec![f32::INFINITY; self.gridmap.get_cells().len()];
+        let mut open_list: BinaryHeap<Reverse<Cell2D>> = BinaryHeap::new();
+
+        if !self.gridmap.xy_is_traversable(self.start) {
+            return Ok(arrival_times);
+        }
+
+        let mut budget_tracker = BudgetTracker::new(&self.budget);
+
+        for goal in self.goal_region.get_cells(&self.gridmap) {
+            arrival_times[self.gridmap.xy_to_idx(goal)] = 0.0;
+            open_list.push(Reverse(Cell2D::new(goal, 0.0)));
+            stats.nodes_generated += 1;
+        }
+        stats.max_open_list_size = open_list.len();
+
+        while let Some(Reverse(cur_cell)) = open_list.pop() {
+            // Skip stale entries of cells that have already been frozen
+            if !closed_list.insert(cur_cell.pos) {
+                continue;
+            }
+
+            if cur_cell.pos == self.start {
+                break;
+            }
+
+            budget_tracker.expand()?;
+            stats.nodes_expanded += 1;
+
+            let parent = get_neighbors_4_con(cur_cell.pos, &self.gridmap)
+                .into_iter()
+                .filter(|nb_cell_pos| closed_list.contains(nb_cell_pos))
+                .min_by(|a, b| {
+                    arrival_times[self.gridmap.xy_to_idx(*a)].total_cmp(&arrival_times[self.gridmap.xy_to_idx(*b)])
+                })
+                .unwrap_or(cur_cell.pos);
+            trace_recorder.record_expansion(cur_cell.pos, parent, (cur_cell.arrival_time * ARRIVAL_TIME_COST_SCALE).round() as u32, 0);
+
+            for nb_cell_pos in get_neighbors_4_con(cur_cell.pos, &self.gridmap) {
+                if closed_list.contains(&nb_cell_pos) {
+                    continue;
+                }
+
+                let nb_idx = self.gridmap.xy_to_idx(nb_cell_pos);
+                let alt_time = self.solve_eikonal(nb_cell_pos, &arrival_times);
+
+                if alt_time < arrival_times[nb_idx] {
+                    arrival_times[nb_idx] = alt_time;
+                    open_list.push(Reverse(Cell2D::new(nb_cell_pos, alt_time)));
+                    stats.nodes_generated += 1;
+                    stats.max_open_list_size = stats.max_open_list_size.max(open_list.len());
+                }
+            }
+            trace_recorder.record_open_list(|| {
+                let mut entries: Vec<&Cell2D> = open_list.iter().map(|entry| &entry.0).collect();
+                entries.sort_by(|a, b| a.arrival_time.total_cmp(&b.arrival_time));
+                entries.into_iter().map(|entry| entry.pos).collect::<Vec<_>>()
+            });
+        }
+
+        Ok(arrival_times)
+    }
+
+    /// First order upwind update of the arrival time at a cell from its 4 way neighbors
+    fn solve_eikonal(&self, pos: (u32, u32), arrival_times: &[f32]) -> f32 {
+        let traversal_time = self.get_traversal_time(self.gridmap.xy_to_idx(pos));
+
+        let t_x = self.get_min_time_along_axis(pos, arrival_times, (1, 0));
+        let t_y = self.get_min_time_along_axis(pos, arrival_times, (0, 1));
+
+        if (t_x - t_y).abs() >= traversal_time {
+            return t_x.min(t_y) + traversal_time;
+        }
+
+        let diff = t_x - t_y;
+        (t_x + t_y + (2.0 * traversal_time * traversal_time - diff * diff).sqrt()) / 2.0
+    }
+
+    /// Get the smallest arrival time of the two neighbors along an axis
+    fn get_min_time_along_axis(&self, pos: (u32, u32), arrival_times: &[f32], axis: (i64, i64)) -> f32 {
+        [-1, 1]
+            .iter()
+            .filter_map(|dir| self.offset_cell(pos, (axis.0 * dir, axis.1 * dir)))
+            .map(|nb| arrival_times[self.gridmap.xy_to_idx(nb)])
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Get the cell at an offset from pos, if it lies within the map
+    fn offset_cell(&self, pos: (u32, u32), offset: (i64, i64)) -> Option<(u32, u32)> {
+        self.get_cell_in_map((pos.0 as i64 + offset.0, pos.1 as i64 + offset.1))
+    }
+
+    /// Convert signed cell coordinates into a cell, if it lies within the map
+    fn get_cell_in_map(&self, pos: (i64, i64)) -> Option<(u32, u32)> {
+        if pos.0 < 0
+            || pos.1 < 0
+            || pos.0 >= self.gridmap.get_width() as i64
+            || pos.1 >= self.gridmap.get_height() as i64
+        {
+            return None;
+        }
+
+        Some((pos.0 as u32, pos.1 as u32))
+    }
+
+    /// Gradient of the arrival time field at a cell using central differences,
+    /// falling back to one sided differences next to obstacles and unvisited cells
+    fn get_cell_gradient(&self, pos: (u32, u32), arrival_times: &[f32]) -> Option<(f32, f32)> {
+        let time = arrival_times[self.gridmap.xy_to_idx(pos)];
+        if !time.is_finite() {
+            return None;
+        }
+
+        let get_time = |offset: (i64, i64)| {
+            self.offset_cell(pos, offset)
+                .map(|nb| arrival_times[self.gridmap.xy_to_idx(nb)])
+                .filter(|t| t.is_finite())
+        };
+
+        let partial = |lower: Option<f32>, upper: Option<f32>| match (lower, upper) {
+            (Some(lower), Some(upper)) => (upper - lower) / 2.0,
+            (Some(lower), None) => time - lower,
+            (None, Some(upper)) => upper - time,
+            (None, None) => 0.0,
+        };
+
+        Some((
+            partial(get_time((-1, 0)), get_time((1, 0))),
+            partial(get_time((0, -1)), get_time((0, 1))),
+        ))
+    }
+
+    /// Bilinearly interpolate the gradient at a continuous point from the surrounding cells
+    fn interpolate_gradient(&self, point: (f32, f32), arrival_times: &[f32]) -> (f32, f32) {
+        let (x_0, y_0) = (point.0.floor(), point.1.floor());
+        let (frac_x, frac_y) = (point.0 - x_0, point.1 - y_0);
+
+        let mut gradient = (0.0, 0.0);
+        let mut total_weight = 0.0;
+
+        for (dx, dy, weight) in [
+            (0, 0, (1.0 - frac_x) * (1.0 - frac_y)),
+            (1, 0, frac_x * (1.0 - frac_y)),
+            (0, 1, (1.0 - frac_x) * frac_y),
+            (1, 1, frac_x * frac_y),
+        ] {
+            let cell_gradient = self
+                .get_cell_in_map((x_0 as i64 + dx, y_0 as i64 + dy))
+                .and_then(|cell| self.get_cell_gradient(cell, arrival_times));
+
+            if let Some(cell_gradient) = cell_gradient {
+                gradient.0 += weight * cell_gradient.0;
+                gradient.1 += weight * cell_gradient.1;
+                total_weight += weight;
+            }
+        }
+
+        if total_weight > 0.0 {
+            (gradient.0 / total_weight, gradient.1 / total_weight)
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    /// Follow the negative gradient of the arrival time field from start to goal.
+    /// Whenever the continuous step fails to make progress, a discrete step is taken
+    /// to the 4 way neighbor with the lowest arrival time, which always exists.
+    /// The returned path runs from goal to start, like the other planners.
+    fn trace_gradient(&self, arrival_times: &[f32]) -> Vec<(f32, f32)> {
+        let mut path: Vec<(f32, f32)> = Vec::new();
+
+        if !arrival_times[self.gridmap.xy_to_idx(self.start)].is_finite() {
+            return path;
+        }
+
+        let mut point = (self.start.0 as f32, self.start.1 as f32);
+        let mut cell = self.start;
+        let mut steps_in_cell = 0;
+        path.push(point);
+
+        while !self.goal_region.contains(cell) {
+            let cell_time = arrival_times[self.gridmap.xy_to_idx(cell)];
+            let gradient = self.interpolate_gradient(point, arrival_times);
+            let norm = gradient.0.hypot(gradient.1);
+
+            let next_point = (
+                point.0 - TRACE_STEP_SIZE * gradient.0 / norm,
+                point.1 - TRACE_STEP_SIZE * gradient.1 / norm,
+            );
+            let next_cell = self.get_cell_in_map((next_point.0.round() as i64, next_point.1.round() as i64));
+
+            // Moving to another cell must strictly decrease the arrival time, and the
+            // point may only linger within a cell for as long as a straight crossing takes
+            let is_valid_step = norm > f32::EPSILON
+                && next_cell.is_some_and(|next_cell| {
+                    let next_time = arrival_times[self.gridmap.xy_to_idx(next_cell)];
+                    if next_cell == cell {
+                        steps_in_cell < (1.0 / TRACE_STEP_SIZE) as u32 + 1
+                    } else {
+                        next_time < cell_time
+                    }
+                });
+
+            if is_valid_step {
+                let next_cell = next_cell.unwrap();
+                steps_in_cell = if next_cell == cell { steps_in_cell + 1 } else { 0 };
+                point = next_point;
+                cell = next_cell;
+            } else {
+                steps_in_cell = 0;
+                cell = get_neighbors_4_con(cell, &self.gridmap)
+                    .into_iter()
+                    .min_by(|a, b| {
+                        arrival_times[self.gridmap.xy_to_idx(*a)]
+                            .total_cmp(&arrival_times[self.gridmap.xy_to_idx(*b)])
+                    })
+                    .unwrap();
+                point = (cell.0 as f32, cell.1 as f32);
+            }
+
+            path.push(point);
+        }
+
+        let goal = (cell.0 as f32, cell.1 as f32);
+        if path.last() != Some(&goal) {
+            path.push(goal);
+        }
+
+        path.reverse();
+        path
+    }
+}
+
+struct Cell2D {
+    pub pos: (u32, u32),
+    pub arrival_time: f32,
+}
+
+impl Eq for Cell2D {}
+
+impl Ord for Cell2D {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.arrival_time.total_cmp(&other.arrival_time)
+    }
+}
+
+impl PartialOrd for Cell2D {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Cell2D {
+    fn eq(&self, other: &Self) -> bool {
+        self.arrival_time == other.arrival_time
+    }
+}
+
+impl Cell2D {
+    fn new(pos: (u32, u32), arrival_time: f32) -> Cell2D {
+        Cell2D { pos, arrival_time }
+    }
+}
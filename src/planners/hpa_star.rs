@@ -0,0 +1,615 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::time::Instant;
+
+use super::planner_base::*;
+use super::planner_common::*;
+use crate::maps::gridmap::Gridmap;
+
+// Hierarchical Path-Finding A* (HPA*)
+
+/// Entrances narrower than this get a single transition in their middle,
+/// wider ones get a transition at each end
+const MAX_SINGLE_TRANSITION_WIDTH: usize = 6;
+
+/// A pair of adjacent traversable cells on either side of a cluster border
+type Transition = ((u32, u32), (u32, u32));
+
+/// Edges leaving each abstract node of a cluster, with their movement cost
+type ClusterEdges = HashMap<(u32, u32), Vec<((u32, u32), u32)>>;
+
+/// Partitions the gridmap into square clusters and plans on an abstract graph
+/// of cluster entrances, only searching individual cells within a single cluster.
+/// Entrances are formed from 4 way connected crossings of the cluster borders.
+pub struct HPAStarPlanner {
+    start: (u32, u32),
+    goal_region: GoalRegion,
+    gridmap: Gridmap,
+    /// Number of cells along each side of a cluster
+    cluster_size: u32,
+    /// Number of clusters along x-axis
+    clusters_x: u32,
+    /// Number of clusters along y-axis
+    clusters_y: u32,
+    /// Transitions across the border between each cluster and its right neighbor
+    right_transitions: Vec<Vec<Transition>>,
+    /// Transitions across the border between each cluster and its bottom neighbor
+    bottom_transitions: Vec<Vec<Transition>>,
+    /// Abstract graph edges leaving the nodes of each cluster
+    cluster_edges: Vec<ClusterEdges>,
+    budget: PlanningBudget,
+    trace_level: TraceLevel,
+}
+
+impl Planner for HPAStarPlanner {
+    fn new(gridmap: &Gridmap) -> HPAStarPlanner {
+        let mut planner = HPAStarPlanner {
+            start: (0, 0),
+            goal_region: GoalRegion::new(&[(0, 0)], 0.0),
+            gridmap: gridmap.clone(),
+            cluster_size: 16,
+            clusters_x: 0,
+            clusters_y: 0,
+            right_transitions: Vec::new(),
+            bottom_transitions: Vec::new(),
+            cluster_edges: Vec::new(),
+            budget: PlanningBudget::default(),
+            trace_level: TraceLevel::Off,
+        };
+        planner.build_abstract_graph();
+
+        planner
+    }
+
+    fn try_generate_plan(&self) -> Result<MotionPlan, PlanningError> {
+        let start_time = Instant::now();
+        let mut path: Vec<(u32, u32)> = Vec::new();
+        let mut closed_list: HashSet<(u32, u32)> = HashSet::new();
+        let mut stats = SearchStats::default();
+        let mut trace_recorder = TraceRecorder::new(self.trace_level);
+
+        if !self.gridmap.xy_is_traversable(self.start) {
+            return Ok(MotionPlan {
+                path,
+                closed_list,
+                stats,
+                trace: trace_recorder.finish(),
+            });
+        }
+
+        let mut goal_clusters: HashMap<usize, Vec<(u32, u32)>> = HashMap::new();
+        for goal in self.goal_region.get_cells(&self.gridmap) {
+            goal_clusters.entry(self.get_cluster_idx(goal)).or_default().push(goal);
+        }
+
+        // Temporarily connect start and goals to the abstract nodes of their clusters
+        let start_cluster = self.get_cluster_idx(self.start);
+
+        let mut start_targets = self.get_cluster_nodes(start_cluster);
+        if let Some(goals) = goal_clusters.get(&start_cluster) {
+            start_targets.extend(goals.iter().copied());
+        }
+        let start_edges: Vec<((u32, u32), u32)> = self
+            .search_cluster(start_cluster, &[self.start], &start_targets)
+            .get_costs(&start_targets)
+            .into_iter()
+            .filter(|(node, _)| *node != self.start)
+            .collect();
+
+        // Each node in a goal cluster is connected to its nearest goal
+        let mut goal_edges: HashMap<(u32, u32), ((u32, u32), u32)> = HashMap::new();
+        for (goal_cluster, goals) in &goal_clusters {
+            let goal_targets = self.get_cluster_nodes(*goal_cluster);
+            let search = self.search_cluster(*goal_cluster, goals, &goal_targets);
+
+            for (node, cost) in search.get_costs(&goal_targets) {
+                let goal = search.trace_path(node)[0];
+                if goal != node {
+                    goal_edges.insert(node, (goal, cost));
+                }
+            }
+        }
+
+        // A* over the abstract graph
+        let mut open_list: BinaryHeap<Reverse<Node>> = BinaryHeap::new();
+        let mut parents: HashMap<(u32, u32), (u32, u32)> = HashMap::new();
+        let mut g_cost: HashMap<(u32, u32), u32> = HashMap::new();
+
+        g_cost.insert(self.start, 0);
+        open_list.push(Reverse(Node::new(self.start, self.goal_region.get_l2_cost(self.start))));
+        parents.insert(self.start, self.start);
+
+        // Abstract nodes count as expansions, as cells are only searched within clusters
+        let mut budget_tracker = BudgetTracker::new(&self.budget);
+        let mut closest_node = self.start;
+        stats.nodes_generated = 1;
+        stats.max_open_list_size = 1;
+
+        while let Some(Reverse(cur_node)) = open_list.pop() {
+            if self.goal_region.contains(cur_node.pos) {
+                path = self.refine_path(&trace_path(&cur_node.pos, &parents));
+                break;
+            }
+
+            if !closed_list.insert(cur_node.pos) {
+                continue;
+            }
+
+            if self.goal_region.get_l2_cost(cur_node.pos) < self.goal_region.get_l2_cost(closest_node) {
+                closest_node = cur_node.pos;
+            }
+            if let Err(interruption) = budget_tracker.expand() {
+                let partial_path = self.refine_path(&trace_path(&closest_node, &parents));
+                stats.finish(&partial_path, &self.gridmap, start_time);
+                let partial_plan = MotionPlan {
+                    path: partial_path,
+                    closed_list,
+                    stats,
+                    trace: trace_recorder.finish(),
+                };
+                return Err(PlanningError { interruption, partial_plan: Box::new(partial_plan) });
+            }
+            stats.nodes_expanded += 1;
+            trace_recorder.record_expansion(
+                cur_node.pos,
+                parents[&cur_node.pos],
+                g_cost[&cur_node.pos],
+                self.goal_region.get_l2_cost(cur_node.pos),
+            );
+
+            let mut nb_edges: Vec<((u32, u32), u32)> = Vec::new();
+            if cur_node.pos == self.start {
+                nb_edges.extend(start_edges.iter().copied());
+            }
+            if let Some(edges) = self.cluster_edges[self.get_cluster_idx(cur_node.pos)].get(&cur_node.pos) {
+                nb_edges.extend(edges.iter().copied());
+            }
+            if let Some(goal_edge) = goal_edges.get(&cur_node.pos) {
+                nb_edges.push(*goal_edge);
+            }
+
+            for (nb_node_pos, edge_cost) in nb_edges {
+                if closed_list.contains(&nb_node_pos) {
+                    continue;
+                }
+
+                let alt_g_cost = g_cost[&cur_node.pos] + edge_cost;
+
+                if alt_g_cost < *g_cost.get(&nb_node_pos).unwrap_or(&u32::MAX) {
+                    g_cost.insert(nb_node_pos, alt_g_cost);
+                    parents.insert(nb_node_pos, cur_node.pos);
+                    open_list.push(Reverse(Node::new(
+                        nb_node_pos,
+                        alt_g_cost + self.goal_region.get_l2_cost(nb_node_pos),
+                    )));
+                    stats.nodes_generated += 1;
+                    stats.max_open_list_size = stats.max_open_list_size.max(open_list.len());
+                }
+            }
+            trace_recorder.record_open_list(|| {
+                let mut entries: Vec<&Node> = open_list.iter().map(|entry| &entry.0).collect();
+                entries.sort_by_key(|entry| entry.f_cost);
+                entries.into_iter().map(|entry| entry.pos).collect::<Vec<_>>()
+            });
+        }
+
+        stats.finish(&path, &self.gridmap, start_time);
+        Ok(MotionPlan {
+            path,
+            closed_list,
+            stats,
+            trace: trace_recorder.finish(),
+        })
+    }
+
+    fn get_gridmap(&self) -> &Gridmap {
+        &self.gridmap
+    }
+
+    fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool {
+        let same_size = gridmap.get_width() == self.gridmap.get_width()
+            && gridmap.get_height() == self.gridmap.get_height();
+        let same_resolution = gridmap.get_resolution() == self.gridmap.get_resolution();
+
+        if !same_size || !same_resolution {
+            self.gridmap = gridmap.clone();
+            self.build_abstract_graph();
+            return true;
+        }
+
+        let changed_cells: Vec<((u32, u32), u8)> = (0..gridmap.get_cells().len())
+            .filter(|idx| gridmap.get_val_idx(*idx) != self.gridmap.get_val_idx(*idx))
+            .map(|idx| (gridmap.idx_to_xy(idx as u32), gridmap.get_val_idx(idx)))
+            .collect();
+
+        self.update_cells(&changed_cells)
+    }
+
+    fn update_start(&mut self, start: (u32, u32)) -> bool {
+        self.start = start;
+        true
+    }
+
+    fn update_goals(&mut self, goals: &[(u32, u32)]) -> bool {
+        self.goal_region.set_goals(goals)
+    }
+
+    fn update_goal_tolerance(&mut self, tolerance: f32) -> bool {
+        self.goal_region.set_tolerance(tolerance)
+    }
+
+    fn update_budget(&mut self, budget: &PlanningBudget) -> bool {
+        self.budget = budget.clone();
+        true
+    }
+
+    /// Traces record the expansions of abstract nodes
+    fn update_trace_level(&mut self, trace_level: TraceLevel) -> bool {
+        self.trace_level = trace_level;
+        true
+    }
+}
+
+impl HPAStarPlanner {
+    /// Set the number of cells along each side of a cluster and rebuild the abstract graph
+    pub fn update_cluster_size(&mut self, cluster_size: u32) -> bool {
+        if cluster_size < 2 {
+            return false;
+        }
+
+        self.cluster_size = cluster_size;
+        self.build_abstract_graph();
+        true
+    }
+
+    /// Set the values of individual cells, only rebuilding the clusters they affect.
+    /// Returns false without changing anything if any cell lies outside the map.
+    pub fn update_cells(&mut self, cells: &[((u32, u32), u8)]) -> bool {
+        let in_map = |pos: (u32, u32)| pos.0 < self.gridmap.get_width() && pos.1 < self.gridmap.get_height();
+        if !cells.iter().all(|(pos, _)| in_map(*pos)) {
+            return false;
+        }
+
+        let mut dirty_clusters: HashSet<usize> = HashSet::new();
+        for (pos, val) in cells {
+            self.gridmap.set_val_xy(*val, *pos);
+            dirty_clusters.insert(self.get_cluster_idx(*pos));
+        }
+
+        self.rebuild_clusters(&dirty_clusters);
+        true
+    }
+
+    /// Get the number of nodes in the abstract graph
+    pub fn get_abstract_node_count(&self) -> usize {
+        self.cluster_edges.iter().map(|edges| edges.len()).sum()
+    }
+
+    /// Partition the gridmap into clusters and build the abstract graph from scratch
+    fn build_abstract_graph(&mut self) {
+        self.clusters_x = self.gridmap.get_width().div_ceil(self.cluster_size);
+        self.clusters_y = self.gridmap.get_height().div_ceil(self.cluster_size);
+
+        let num_clusters = (self.clusters_x * self.clusters_y) as usize;
+        self.right_transitions = vec![Vec::new(); num_clusters];
+        self.bottom_transitions = vec![Vec::new(); num_clusters];
+        self.cluster_edges = vec![HashMap::new(); num_clusters];
+
+        self.rebuild_clusters(&(0..num_clusters).collect());
+    }
+
+    /// Recompute the entrances on the borders of the dirty clusters, then the
+    /// abstract edges of the dirty clusters and their neighbors
+    fn rebuild_clusters(&mut self, dirty_clusters: &HashSet<usize>) {
+        let mut affected_clusters: HashSet<usize> = HashSet::new();
+
+        for cluster in dirty_clusters {
+            let (cx, cy) = self.get_cluster_xy(*cluster);
+
+            self.right_transitions[*cluster] = self.find_transitions(*cluster, (1, 0));
+            self.bottom_transitions[*cluster] = self.find_transitions(*cluster, (0, 1));
+            affected_clusters.insert(*cluster);
+
+            if cx > 0 {
+                let left = *cluster - 1;
+                self.right_transitions[left] = self.find_transitions(left, (1, 0));
+                affected_clusters.insert(left);
+            }
+            if cy > 0 {
+                let top = *cluster - self.clusters_x as usize;
+                self.bottom_transitions[top] = self.find_transitions(top, (0, 1));
+                affected_clusters.insert(top);
+            }
+            if cx + 1 < self.clusters_x {
+                affected_clusters.insert(*cluster + 1);
+            }
+            if cy + 1 < self.clusters_y {
+                affected_clusters.insert(*cluster + self.clusters_x as usize);
+            }
+        }
+
+        for cluster in affected_clusters {
+            self.cluster_edges[cluster] = self.build_cluster_edges(cluster);
+        }
+    }
+
+    /// Find the transitions across the right (1, 0) or bottom (0, 1) border of a cluster
+    fn find_transitions(&self, cluster: usize, direction: (u32, u32)) -> Vec<Transition> {
+        let (cx, cy) = self.get_cluster_xy(cluster);
+        if cx + direction.0 >= self.clusters_x || cy + direction.1 >= self.clusters_y {
+            return Vec::new();
+        }
+
+        let (origin, (width, height)) = self.get_cluster_bounds(cluster);
+
+        // Cells along the border on this cluster's side
+        let border_cells: Vec<(u32, u32)> = if direction == (1, 0) {
+            (origin.1..origin.1 + height).map(|y| (origin.0 + width - 1, y)).collect()
+        } else {
+            (origin.0..origin.0 + width).map(|x| (x, origin.1 + height - 1)).collect()
+        };
+
+        let mut transitions: Vec<Transition> = Vec::new();
+        let mut entrance: Vec<Transition> = Vec::new();
+
+        for cell in border_cells {
+            let nb_cell = (cell.0 + direction.0, cell.1 + direction.1);
+
+            if self.gridmap.xy_is_traversable(cell) && self.gridmap.xy_is_traversable(nb_cell) {
+                entrance.push((cell, nb_cell));
+            } else if !entrance.is_empty() {
+                transitions.extend(get_entrance_transitions(&entrance));
+                entrance.clear();
+            }
+        }
+        transitions.extend(get_entrance_transitions(&entrance));
+
+        transitions
+    }
+
+    /// Connect every abstract node of a cluster to the others it can reach within
+    /// the cluster, and to its counterpart across each transition
+    fn build_cluster_edges(&self, cluster: usize) -> ClusterEdges {
+        let mut edges: ClusterEdges = HashMap::new();
+
+        for (node, nb_node) in self.get_cluster_transitions(cluster) {
+            edges.entry(node).or_default().push((nb_node, get_l2_cost(node, nb_node)));
+        }
+
+        let nodes: Vec<(u32, u32)> = edges.keys().copied().collect();
+
+        for node in &nodes {
+            let search = self.search_cluster(cluster, &[*node], &nodes);
+
+            for (nb_node, cost) in search.get_costs(&nodes) {
+                if nb_node != *node {
+                    edges.get_mut(node).unwrap().push((nb_node, cost));
+                }
+            }
+        }
+
+        edges
+    }
+
+    /// Get transitions touching a cluster, as (cell in cluster, cell in neighbor) pairs
+    fn get_cluster_transitions(&self, cluster: usize) -> Vec<Transition> {
+        let (cx, cy) = self.get_cluster_xy(cluster);
+        let mut transitions: Vec<Transition> = Vec::new();
+
+        transitions.extend(self.right_transitions[cluster].iter().copied());
+        transitions.extend(self.bottom_transitions[cluster].iter().copied());
+
+        if cx > 0 {
+            transitions.extend(self.right_transitions[cluster - 1].iter().map(|(a, b)| (*b, *a)));
+        }
+        if cy > 0 {
+            let top = cluster - self.clusters_x as usize;
+            transitions.extend(self.bottom_transitions[top].iter().map(|(a, b)| (*b, *a)));
+        }
+
+        transitions
+    }
+
+    /// Get the abstract nodes within a cluster
+    fn get_cluster_nodes(&self, cluster: usize) -> Vec<(u32, u32)> {
+        self.cluster_edges[cluster].keys().copied().collect()
+    }
+
+    /// Expand the abstract path into a path of cells, from goal to start
+    fn refine_path(&self, abstract_path: &[(u32, u32)]) -> Vec<(u32, u32)> {
+        let mut path: Vec<(u32, u32)> = vec![abstract_path[0]];
+
+        for pair in abstract_path.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let cluster = self.get_cluster_idx(from);
+
+            if cluster != self.get_cluster_idx(to) {
+                // Transition between neighboring clusters
+                path.push(to);
+            } else {
+                let search = self.search_cluster(cluster, &[from], &[to]);
+                path.extend(search.trace_path(to).into_iter().skip(1));
+            }
+        }
+
+        path
+    }
+
+    /// Dijkstra search from one or more sources confined to a single cluster,
+    /// which stops once every target is settled
+    fn search_cluster(&self, cluster: usize, sources: &[(u32, u32)], targets: &[(u32, u32)]) -> ClusterSearch {
+        let (origin, (width, height)) = self.get_cluster_bounds(cluster);
+        let mut search = ClusterSearch::new(origin, width, height);
+
+        let in_cluster = |pos: &(u32, u32)| {
+            pos.0 >= origin.0 && pos.1 >= origin.1 && pos.0 < origin.0 + width && pos.1 < origin.1 + height
+        };
+
+        let mut open_list: BinaryHeap<Reverse<Node>> = BinaryHeap::new();
+        let mut closed_list: Vec<bool> = vec![false; (width * height) as usize];
+        let mut remaining_targets: HashSet<(u32, u32)> = targets.iter().copied().collect();
+
+        for source in sources {
+            let source_idx = search.xy_to_idx(*source);
+            search.g_cost[source_idx] = 0;
+            search.parents[source_idx] = source_idx;
+            open_list.push(Reverse(Node::new(*source, 0)));
+        }
+
+        while let Some(Reverse(cur_cell)) = open_list.pop() {
+            let cur_idx = search.xy_to_idx(cur_cell.pos);
+            if closed_list[cur_idx] {
+                continue;
+            }
+            closed_list[cur_idx] = true;
+
+            remaining_targets.remove(&cur_cell.pos);
+            if remaining_targets.is_empty() {
+                break;
+            }
+
+            for nb_cell_pos in get_neighbors_8_con(cur_cell.pos, &self.gridmap) {
+                if !in_cluster(&nb_cell_pos) {
+                    continue;
+                }
+
+                let nb_idx = search.xy_to_idx(nb_cell_pos);
+                if closed_list[nb_idx] {
+                    continue;
+                }
+
+                let alt_g_cost = search.g_cost[cur_idx] + get_l2_cost(cur_cell.pos, nb_cell_pos);
+
+                if alt_g_cost < search.g_cost[nb_idx] {
+                    search.g_cost[nb_idx] = alt_g_cost;
+                    search.parents[nb_idx] = cur_idx;
+                    open_list.push(Reverse(Node::new(nb_cell_pos, alt_g_cost)));
+                }
+            }
+        }
+
+        search
+    }
+
+    /// Get the index of the cluster containing a cell
+    fn get_cluster_idx(&self, pos: (u32, u32)) -> usize {
+        let (cx, cy) = (pos.0 / self.cluster_size, pos.1 / self.cluster_size);
+
+        (cy * self.clusters_x + cx) as usize
+    }
+
+    /// Convert from cluster index to its 2D position in the grid of clusters
+    fn get_cluster_xy(&self, cluster: usize) -> (u32, u32) {
+        (cluster as u32 % self.clusters_x, cluster as u32 / self.clusters_x)
+    }
+
+    /// Get the top left cell and (width, height) of a cluster, which is
+    /// smaller than the cluster size along the right and bottom edges of the map
+    fn get_cluster_bounds(&self, cluster: usize) -> ((u32, u32), (u32, u32)) {
+        let (cx, cy) = self.get_cluster_xy(cluster);
+        let origin = (cx * self.cluster_size, cy * self.cluster_size);
+
+        (
+            origin,
+            (
+                self.cluster_size.min(self.gridmap.get_width() - origin.0),
+                self.cluster_size.min(self.gridmap.get_height() - origin.1),
+            ),
+        )
+    }
+}
+
+/// Pick the transitions representing an entrance, a run of adjacent transitions
+fn get_entrance_transitions(entrance: &[Transition]) -> Vec<Transition> {
+    if entrance.is_empty() {
+        Vec::new()
+    } else if entrance.len() < MAX_SINGLE_TRANSITION_WIDTH {
+        vec![entrance[entrance.len() / 2]]
+    } else {
+        vec![entrance[0], entrance[entrance.len() - 1]]
+    }
+}
+
+/// Result of a search within a single cluster, indexed relative to the cluster's origin
+struct ClusterSearch {
+    origin: (u32, u32),
+    width: u32,
+    g_cost: Vec<u32>,
+    parents: Vec<usize>,
+}
+
+impl ClusterSearch {
+    fn new(origin: (u32, u32), width: u32, height: u32) -> ClusterSearch {
+        ClusterSearch {
+            origin,
+            width,
+            g_cost: vec![u32::MAX; (width * height) as usize],
+            parents: vec![usize::MAX; (width * height) as usize],
+        }
+    }
+
+    fn xy_to_idx(&self, pos: (u32, u32)) -> usize {
+        ((pos.1 - self.origin.1) * self.width + (pos.0 - self.origin.0)) as usize
+    }
+
+    fn idx_to_xy(&self, idx: usize) -> (u32, u32) {
+        (
+            idx as u32 % self.width + self.origin.0,
+            idx as u32 / self.width + self.origin.1,
+        )
+    }
+
+    /// Get the cost to each reached target
+    fn get_costs(&self, targets: &[(u32, u32)]) -> Vec<((u32, u32), u32)> {
+        targets
+            .iter()
+            .map(|target| (*target, self.g_cost[self.xy_to_idx(*target)]))
+            .filter(|(_, cost)| *cost != u32::MAX)
+            .collect()
+    }
+
+    /// Get the path from the nearest source to a reached target
+    fn trace_path(&self, target: (u32, u32)) -> Vec<(u32, u32)> {
+        let mut path: Vec<(u32, u32)> = Vec::new();
+        let mut cur_idx = self.xy_to_idx(target);
+        path.push(target);
+
+        while self.parents[cur_idx] != cur_idx {
+            cur_idx = self.parents[cur_idx];
+            path.push(self.idx_to_xy(cur_idx));
+        }
+
+        path.reverse();
+        path
+    }
+}
+
+#[derive(Eq)]
+struct Node {
+    pub pos: (u32, u32),
+    pub f_cost: u32,
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.f_cost.cmp(&other.f_cost)
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.f_cost == other.f_cost
+    }
+}
+
+impl Node {
+    fn new(pos: (u32, u32), f_cost: u32) -> Node {
+        Node { pos, f_cost }
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
 use crate::maps::gridmap::Gridmap;
 use super::planner_common::*;
 
@@ -5,18 +8,124 @@ pub trait Planner {
     
     fn new(gridmap: &Gridmap) -> Self;
 
-    /// Retrieve a motion plan given start and goal location
-    fn generate_plan(&self) -> MotionPlan;
+    /// Retrieve a motion plan given start and goal location, or the best partial
+    /// result if the planning budget runs out first
+    fn try_generate_plan(&self) -> Result<MotionPlan, PlanningError>;
+
+    /// Retrieve a motion plan given start and goal location. The path is empty
+    /// if the goal cannot be reached or the planning budget runs out.
+    fn generate_plan(&self) -> MotionPlan {
+        self.try_generate_plan().unwrap_or_else(|error| MotionPlan {
+            path: Vec::new(),
+            closed_list: error.partial_plan.closed_list,
+            stats: error.partial_plan.stats,
+            trace: error.partial_plan.trace,
+        })
+    }
 
     // Default method definitions
     fn update_start_and_goal(&mut self, start: (u32, u32), goal: (u32, u32)) -> bool{
         self.update_start(start) && self.update_goal(goal)
     }
 
+    /// Plan through each waypoint in order, starting every leg from where the
+    /// previous one ended. The path runs from the last waypoint to the first,
+    /// and is empty if any leg fails. Leaves the planner's goals set to the last waypoint.
+    fn generate_route_plan(&mut self, waypoints: &[(u32, u32)]) -> MotionPlan
+    where
+        Self: Sized,
+    {
+        self.try_generate_route_plan(waypoints).unwrap_or_else(|error| MotionPlan {
+            path: Vec::new(),
+            closed_list: error.partial_plan.closed_list,
+            stats: error.partial_plan.stats,
+            trace: error.partial_plan.trace,
+        })
+    }
+
+    /// Plan a route like `generate_route_plan`, or the best partial result if the planning
+    /// budget runs out during a leg. The partial path covers the legs finished before it.
+    fn try_generate_route_plan(&mut self, waypoints: &[(u32, u32)]) -> Result<MotionPlan, PlanningError>
+    where
+        Self: Sized,
+    {
+        let start_time = Instant::now();
+        let mut path: Vec<(u32, u32)> = Vec::new();
+        let mut closed_list: HashSet<(u32, u32)> = HashSet::new();
+        let mut stats = SearchStats::default();
+        let mut trace: Option<SearchTrace> = None;
+
+        let Some(mut leg_start) = waypoints.first().copied() else {
+            return Ok(MotionPlan { path, closed_list, stats, trace });
+        };
+
+        for waypoint in waypoints.iter().skip(1) {
+            self.update_start_and_goal(leg_start, *waypoint);
+            let (leg_plan, interruption) = match self.try_generate_plan() {
+                Ok(leg_plan) => (leg_plan, None),
+                Err(error) => (*error.partial_plan, Some(error.interruption)),
+            };
+            closed_list.extend(leg_plan.closed_list);
+            stats.merge(&leg_plan.stats);
+            if let Some(leg_trace) = leg_plan.trace {
+                trace.get_or_insert_with(SearchTrace::default).append(leg_trace);
+            }
+
+            if let Some(interruption) = interruption {
+                stats.finish(&path, self.get_gridmap(), start_time);
+                let partial_plan = MotionPlan { path, closed_list, stats, trace };
+                return Err(PlanningError { interruption, partial_plan: Box::new(partial_plan) });
+            }
+
+            if leg_plan.path.is_empty() {
+                stats.finish(&[], self.get_gridmap(), start_time);
+                return Ok(MotionPlan { path: Vec::new(), closed_list, stats, trace });
+            }
+
+            // Legs run from their goal to their start, so prepend each one
+            // and drop the start cell shared with the previous leg
+            let mut leg_path = leg_plan.path;
+            leg_start = leg_path[0];
+            if !path.is_empty() {
+                leg_path.pop();
+            }
+            path = [leg_path, path].concat();
+        }
+
+        if path.is_empty() {
+            path.push(leg_start);
+        }
+
+        stats.finish(&path, self.get_gridmap(), start_time);
+        Ok(MotionPlan { path, closed_list, stats, trace })
+    }
+
+    fn update_goal(&mut self, goal: (u32, u32)) -> bool {
+        self.update_goals(&[goal])
+    }
+
+    /// Convert the tolerance from meters to cells using the gridmap resolution
+    fn update_goal_tolerance_m(&mut self, tolerance: f32) -> bool {
+        let resolution = self.get_gridmap().get_resolution();
+        self.update_goal_tolerance(tolerance / resolution)
+    }
+
+    fn get_gridmap(&self) -> &Gridmap;
+
     fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool;
 
     fn update_start(&mut self, start: (u32, u32)) -> bool;
 
-    fn update_goal(&mut self, goal: (u32, u32)) -> bool;
+    /// Plan to whichever of the goals is cheapest to reach
+    fn update_goals(&mut self, goals: &[(u32, u32)]) -> bool;
+
+    /// Accept any cell within a radius (in cells) of a goal
+    fn update_goal_tolerance(&mut self, tolerance: f32) -> bool;
+
+    /// Limit the time and expansions of each plan, or allow it to be cancelled
+    fn update_budget(&mut self, budget: &PlanningBudget) -> bool;
+
+    /// Set how much of each search is recorded in the plan's trace
+    fn update_trace_level(&mut self, trace_level: TraceLevel) -> bool;
 
 }
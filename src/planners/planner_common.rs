@@ -1,11 +1,452 @@
 use crate::maps::gridmap::Gridmap;
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Contains the path from start to goal and the list of visited cells
 #[derive(Debug)]
 pub struct MotionPlan {
     pub path: Vec<(u32, u32)>,
     pub closed_list: HashSet<(u32, u32)>,
+    pub stats: SearchStats,
+    /// Ordered record of the search, None unless the planner's trace level asks for it
+    pub trace: Option<SearchTrace>,
+}
+
+/// Statistics gathered while searching for a plan
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchStats {
+    /// Number of cells (or nodes) whose neighbors were explored
+    pub nodes_expanded: usize,
+    /// Number of cells (or nodes) pushed onto the open list
+    pub nodes_generated: usize,
+    pub max_open_list_size: usize,
+    /// Number of expansions of cells that had already been expanded before. Stale open list
+    /// entries are skipped rather than expanded again, so this is 0 for the grid planners.
+    pub reopenings: usize,
+    /// Movement cost of the path, in the units of `get_l2_cost`
+    pub path_cost: u32,
+    pub path_length_m: f32,
+    pub elapsed_time: Duration,
+}
+
+impl SearchStats {
+    /// Fill in the cost and length of the path, and the time taken since planning started
+    pub fn finish(&mut self, path: &[(u32, u32)], gridmap: &Gridmap, start_time: Instant) {
+        self.path_cost = path.windows(2).map(|pair| get_l2_cost(pair[0], pair[1])).sum();
+        self.path_length_m = self.path_cost as f32 / 100.0 * gridmap.get_resolution();
+        self.elapsed_time = start_time.elapsed();
+    }
+
+    /// Add the search effort of another plan, such as a leg of a route
+    pub fn merge(&mut self, other: &SearchStats) {
+        self.nodes_expanded += other.nodes_expanded;
+        self.nodes_generated += other.nodes_generated;
+        self.max_open_list_size = self.max_open_list_size.max(other.max_open_list_size);
+        self.reopenings += other.reopenings;
+    }
+}
+
+/// How much of the search a planner records in `MotionPlan::trace`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TraceLevel {
+    #[default]
+    Off,
+    /// Record every expansion in order
+    Expansions,
+    /// Also snapshot the open list after every expansion, which is slow on large maps
+    OpenLists,
+}
+
+/// A single expansion of a cell (or node)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpansionRecord {
+    /// Number of expansions before this one
+    pub iteration: usize,
+    pub cell: (u32, u32),
+    /// Movement cost from the start, in the units of `get_l2_cost`. For FMM this is the
+    /// arrival time of the front, which grows by 100 per cell crossed at unit speed.
+    pub g_cost: u32,
+    /// Heuristic cost to the goal, zero for uninformed searches
+    pub h_cost: u32,
+    pub f_cost: u32,
+    /// Cell the expanded one was reached from, the cell itself for the start
+    pub parent: (u32, u32),
+}
+
+/// Ordered record of a search, for replaying it step by step
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchTrace {
+    pub expansions: Vec<ExpansionRecord>,
+    /// Cells on the open list right after each expansion, those the planner takes
+    /// first coming first. Empty unless open lists are traced.
+    pub open_list_snapshots: Vec<Vec<(u32, u32)>>,
+}
+
+impl SearchTrace {
+    /// Get the movement cost of every expanded cell, for plotting as a heatmap. Cells
+    /// expanded more than once, such as by several legs of a route, keep the cost of their
+    /// last expansion. Cells that were never expanded are infinite.
+    pub fn get_g_cost_field(&self, gridmap: &Gridmap) -> Vec<f32> {
+        self.get_cost_field(gridmap, |record| record.g_cost)
+    }
+
+    /// Get the heuristic cost of every expanded cell, infinite for the others
+    pub fn get_h_cost_field(&self, gridmap: &Gridmap) -> Vec<f32> {
+        self.get_cost_field(gridmap, |record| record.h_cost)
+    }
+
+    fn get_cost_field(&self, gridmap: &Gridmap, get_cost: impl Fn(&ExpansionRecord) -> u32) -> Vec<f32> {
+        let mut field: Vec<f32> = vec![f32::INFINITY; gridmap.get_cells().len()];
+
+        for record in &self.expansions {
+            field[gridmap.xy_to_idx(record.cell)] = get_cost(record) as f32;
+        }
+
+        field
+    }
+
+    /// Add the expansions of a later search, such as the next leg of a route
+    pub fn append(&mut self, other: SearchTrace) {
+        let offset = self.expansions.len();
+        self.expansions.extend(other.expansions.into_iter().map(|mut record| {
+            record.iteration += offset;
+            record
+        }));
+        self.open_list_snapshots.extend(other.open_list_snapshots);
+    }
+}
+
+/// Records a single plan's search at a trace level, doing nothing when tracing is off
+pub struct TraceRecorder {
+    trace_level: TraceLevel,
+    trace: SearchTrace,
+    /// Movement cost of each expanded cell, for planners that do not keep it themselves
+    g_costs: HashMap<(u32, u32), u32>,
+}
+
+impl TraceRecorder {
+    /// Constructor
+    pub fn new(trace_level: TraceLevel) -> TraceRecorder {
+        TraceRecorder {
+            trace_level,
+            trace: SearchTrace::default(),
+            g_costs: HashMap::new(),
+        }
+    }
+
+    /// Record the expansion of a cell
+    pub fn record_expansion(&mut self, cell: (u32, u32), parent: (u32, u32), g_cost: u32, h_cost: u32) {
+        if self.trace_level == TraceLevel::Off {
+            return;
+        }
+
+        self.g_costs.insert(cell, g_cost);
+        self.trace.expansions.push(ExpansionRecord {
+            iteration: self.trace.expansions.len(),
+            cell,
+            g_cost,
+            h_cost,
+            f_cost: g_cost + h_cost,
+            parent,
+        });
+    }
+
+    /// Record the expansion of a cell, taking its movement cost as that of its
+    /// parent when it was expanded plus the step between them
+    pub fn record_expansion_from_parent(&mut self, cell: (u32, u32), parent: (u32, u32), h_cost: u32) {
+        let g_cost = match self.g_costs.get(&parent) {
+            Some(parent_g_cost) if parent != cell => parent_g_cost + get_l2_cost(parent, cell),
+            _ => 0,
+        };
+        self.record_expansion(cell, parent, g_cost, h_cost);
+    }
+
+    /// Snapshot the open list, only collecting its cells when open lists are traced
+    pub fn record_open_list<I: IntoIterator<Item = (u32, u32)>>(&mut self, get_cells: impl FnOnce() -> I) {
+        if self.trace_level == TraceLevel::OpenLists {
+            self.trace.open_list_snapshots.push(get_cells().into_iter().collect());
+        }
+    }
+
+    /// Get the recorded trace, None when tracing is off
+    pub fn finish(self) -> Option<SearchTrace> {
+        (self.trace_level != TraceLevel::Off).then_some(self.trace)
+    }
+}
+
+/// Shared flag used to cancel planning from another thread. Clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Constructor
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Ask every planner holding this token to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Allow planning again after a cancellation
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Limits on a single call to plan, each of which is unlimited when unset
+#[derive(Clone, Debug, Default)]
+pub struct PlanningBudget {
+    /// Wall-clock time allowed per plan
+    pub time_limit: Option<Duration>,
+    /// Number of cells (or nodes) allowed to be expanded per plan
+    pub max_expansions: Option<usize>,
+    pub cancellation_token: Option<CancellationToken>,
+}
+
+/// Reason a planner stopped before finishing its search
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanningInterruption {
+    Cancelled,
+    TimedOut,
+    ExpansionLimitReached,
+}
+
+/// Returned when planning is interrupted, holding the best partial result found
+#[derive(Debug)]
+pub struct PlanningError {
+    pub interruption: PlanningInterruption,
+    /// Cells expanded so far, and the path from the start to the expanded cell
+    /// closest to the goal for planners that search outwards from the start
+    pub partial_plan: Box<MotionPlan>,
+}
+
+impl fmt::Display for PlanningInterruption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlanningInterruption::Cancelled => write!(f, "planning was cancelled"),
+            PlanningInterruption::TimedOut => write!(f, "planning ran out of time"),
+            PlanningInterruption::ExpansionLimitReached => write!(f, "planning ran out of expansions"),
+        }
+    }
+}
+
+impl fmt::Display for PlanningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.interruption)
+    }
+}
+
+impl std::error::Error for PlanningError {}
+
+/// Keeps track of a single plan's use of a planning budget
+pub struct BudgetTracker<'a> {
+    budget: &'a PlanningBudget,
+    start_time: Instant,
+    num_expansions: usize,
+}
+
+impl BudgetTracker<'_> {
+    /// Start tracking a plan from now
+    pub fn new(budget: &PlanningBudget) -> BudgetTracker<'_> {
+        BudgetTracker {
+            budget,
+            start_time: Instant::now(),
+            num_expansions: 0,
+        }
+    }
+
+    /// Count the expansion of a cell, and check whether planning may go on
+    pub fn expand(&mut self) -> Result<(), PlanningInterruption> {
+        self.num_expansions += 1;
+        self.check()
+    }
+
+    /// Count the cells expanded by a search nested within this plan
+    pub fn add_expansions(&mut self, num_expansions: usize) {
+        self.num_expansions += num_expansions;
+    }
+
+    /// Check whether planning may go on, without counting an expansion
+    pub fn check(&self) -> Result<(), PlanningInterruption> {
+        if self.budget.cancellation_token.as_ref().is_some_and(|token| token.is_cancelled()) {
+            return Err(PlanningInterruption::Cancelled);
+        }
+        if self.budget.time_limit.is_some_and(|time_limit| self.start_time.elapsed() > time_limit) {
+            return Err(PlanningInterruption::TimedOut);
+        }
+        if self.budget.max_expansions.is_some_and(|max_expansions| self.num_expansions > max_expansions) {
+            return Err(PlanningInterruption::ExpansionLimitReached);
+        }
+
+        Ok(())
+    }
+
+    /// Budget left for a search nested within this plan, sharing its deadline,
+    /// its remaining expansions and its cancellation token
+    pub fn get_remaining_budget(&self) -> PlanningBudget {
+        PlanningBudget {
+            time_limit: self.budget.time_limit.map(|time_limit| time_limit.saturating_sub(self.start_time.elapsed())),
+            max_expansions: self
+                .budget
+                .max_expansions
+                .map(|max_expansions| max_expansions.saturating_sub(self.num_expansions)),
+            cancellation_token: self.budget.cancellation_token.clone(),
+        }
+    }
+}
+
+/// Set of acceptable goal cells, each accepting cells within a tolerance radius of it
+#[derive(Clone, Debug)]
+pub struct GoalRegion {
+    goals: Vec<(u32, u32)>,
+    /// Radius in cells around each goal that is also accepted
+    tolerance: f32,
+}
+
+impl GoalRegion {
+    /// Constructor
+    pub fn new(goals: &[(u32, u32)], tolerance: f32) -> GoalRegion {
+        GoalRegion {
+            goals: goals.to_vec(),
+            tolerance,
+        }
+    }
+
+    /// Get the goal cells
+    pub fn get_goals(&self) -> &Vec<(u32, u32)> {
+        &self.goals
+    }
+
+    /// Get the tolerance radius in cells
+    pub fn get_tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Set the goal cells, which must not be empty
+    pub fn set_goals(&mut self, goals: &[(u32, u32)]) -> bool {
+        if goals.is_empty() {
+            return false;
+        }
+        self.goals = goals.to_vec();
+        true
+    }
+
+    /// Set the tolerance radius in cells, which must be non-negative
+    pub fn set_tolerance(&mut self, tolerance: f32) -> bool {
+        if tolerance < 0.0 || !tolerance.is_finite() {
+            return false;
+        }
+        self.tolerance = tolerance;
+        true
+    }
+
+    /// Check if a cell is within the tolerance of any goal
+    pub fn contains(&self, pos: (u32, u32)) -> bool {
+        self.goals.iter().any(|goal| {
+            let dx = pos.0 as f32 - goal.0 as f32;
+            let dy = pos.1 as f32 - goal.1 as f32;
+            dx.hypot(dy) <= self.tolerance
+        })
+    }
+
+    /// Get the Euclidean (L2) Distance to the nearest accepted cell, scaled like `get_l2_cost`
+    pub fn get_l2_cost(&self, pos: (u32, u32)) -> u32 {
+        let tolerance_cost = (self.tolerance * 100.0) as u32;
+
+        self.goals
+            .iter()
+            .map(|goal| get_l2_cost(pos, *goal).saturating_sub(tolerance_cost))
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Get all traversable cells of the gridmap accepted by the region
+    pub fn get_cells(&self, gridmap: &Gridmap) -> Vec<(u32, u32)> {
+        let radius = self.tolerance.floor() as i64;
+        let mut cells: Vec<(u32, u32)> = Vec::new();
+
+        for goal in &self.goals {
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    let (x, y) = (goal.0 as i64 + dx, goal.1 as i64 + dy);
+                    if x < 0 || y < 0 || x >= gridmap.get_width() as i64 || y >= gridmap.get_height() as i64 {
+                        continue;
+                    }
+
+                    let cell = (x as u32, y as u32);
+                    if self.contains(cell) && gridmap.xy_is_traversable(cell) {
+                        cells.push(cell);
+                    }
+                }
+            }
+        }
+
+        cells.sort();
+        cells.dedup();
+        cells
+    }
+}
+
+/// Moves a planner may take between cells
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Up, down, left and right, like FMM and Space-Time A*
+    FourWay,
+    /// Also diagonally, cutting past the corners of obstacles, like the other planners
+    EightWay,
+    /// Also diagonally, but only where both cells beside the diagonal move are traversable,
+    /// as assumed by the optimal lengths of MovingAI scenarios
+    EightWayNoCornerCutting,
+}
+
+impl Neighborhood {
+    /// Get the traversable neighbors of a cell
+    pub fn get_neighbors(&self, pos: (u32, u32), gridmap: &Gridmap) -> Vec<(u32, u32)> {
+        match self {
+            Neighborhood::FourWay => get_neighbors_4_con(pos, gridmap),
+            Neighborhood::EightWay => get_neighbors_8_con(pos, gridmap),
+            Neighborhood::EightWayNoCornerCutting => get_neighbors_8_con(pos, gridmap)
+                .into_iter()
+                .filter(|nb_pos| !cuts_corner(pos, *nb_pos, gridmap))
+                .collect(),
+        }
+    }
+
+    /// Whether a single move leads from one cell to another
+    pub fn is_adjacent(&self, pos_1: (u32, u32), pos_2: (u32, u32)) -> bool {
+        let (dx, dy) = (pos_1.0.abs_diff(pos_2.0), pos_1.1.abs_diff(pos_2.1));
+
+        match self {
+            Neighborhood::FourWay => dx + dy == 1,
+            Neighborhood::EightWay | Neighborhood::EightWayNoCornerCutting => dx.max(dy) == 1,
+        }
+    }
+
+    /// Whether a single move leads from one cell to another past the obstacles of the map
+    pub fn is_move(&self, pos_1: (u32, u32), pos_2: (u32, u32), gridmap: &Gridmap) -> bool {
+        self.is_adjacent(pos_1, pos_2)
+            && !(*self == Neighborhood::EightWayNoCornerCutting && cuts_corner(pos_1, pos_2, gridmap))
+    }
+}
+
+/// Whether a diagonal move passes a cell beside it that is not traversable
+fn cuts_corner(pos_1: (u32, u32), pos_2: (u32, u32), gridmap: &Gridmap) -> bool {
+    pos_1.0 != pos_2.0
+        && pos_1.1 != pos_2.1
+        && (!gridmap.xy_is_traversable((pos_2.0, pos_1.1)) || !gridmap.xy_is_traversable((pos_1.0, pos_2.1)))
 }
 
 /// Get 4 way connected neighbors (Up, Down, Left and Right)
@@ -17,31 +458,23 @@ pub fn get_neighbors_4_con(pos: (u32, u32), gridmap: &Gridmap) -> Vec<(u32, u32)
     let pos_0_non_max = pos.0 < gridmap.get_width() - 1;
     let pos_1_non_max = pos.1 < gridmap.get_height() - 1;
 
-    if pos_0_non_zero {
-        if gridmap.xy_is_traversable((pos.0 - 1, pos.1)) {
-            neighbors.push((pos.0.checked_sub(1).unwrap(), pos.1)); // Left (x-1, y))
-        }
+    if pos_0_non_zero && gridmap.xy_is_traversable((pos.0 - 1, pos.1)) {
+        neighbors.push((pos.0.checked_sub(1).unwrap(), pos.1)); // Left (x-1, y))
     }
 
-    if pos_0_non_max {
-        if gridmap.xy_is_traversable((pos.0 + 1, pos.1)) {
-            neighbors.push((pos.0 + 1, pos.1)); // Right (x+1, y)
-        }
+    if pos_0_non_max && gridmap.xy_is_traversable((pos.0 + 1, pos.1)) {
+        neighbors.push((pos.0 + 1, pos.1)); // Right (x+1, y)
     }
 
-    if pos_1_non_zero {
-        if gridmap.xy_is_traversable((pos.0, pos.1 - 1)) {
-            neighbors.push((pos.0, pos.1.checked_sub(1).unwrap())); // Top (x, y-1)
-        }
+    if pos_1_non_zero && gridmap.xy_is_traversable((pos.0, pos.1 - 1)) {
+        neighbors.push((pos.0, pos.1.checked_sub(1).unwrap())); // Top (x, y-1)
     }
 
-    if pos_1_non_max {
-        if gridmap.xy_is_traversable((pos.0, pos.1 + 1)) {
-            neighbors.push((pos.0, pos.1 + 1)); // Bottom (x, y+1), (idx)
-        }
+    if pos_1_non_max && gridmap.xy_is_traversable((pos.0, pos.1 + 1)) {
+        neighbors.push((pos.0, pos.1 + 1)); // Bottom (x, y+1), (idx)
     }
 
-    return neighbors;
+    neighbors
 }
 
 /// Get 8 way connected neighbors (4 Way + diagonals)
@@ -111,25 +544,85 @@ pub fn get_neighbors_8_con(pos: (u32, u32), gridmap: &Gridmap) -> Vec<(u32, u32)
         }
     }
 
-    return neighbors;
+    neighbors
 }
 
-pub fn trace_path(
-    goal_cell: &(u32, u32),
-    parents: &HashMap<(u32, u32), (u32, u32)>,
-) -> Vec<(u32, u32)> {
-    let mut path: Vec<(u32, u32)> = Vec::new();
+/// Follow the parents from a cell back to the start, whose parent is itself.
+/// Works for any search state, such as cells or `(cell, timestep)` pairs.
+pub fn trace_path<T: Copy + Eq + Hash>(goal_cell: &T, parents: &HashMap<T, T>) -> Vec<T> {
+    let mut path: Vec<T> = Vec::new();
     let mut cur_cell = goal_cell;
     path.push(*cur_cell);
 
-    while parents.get(&cur_cell).unwrap() != cur_cell {
-        cur_cell = parents.get(&cur_cell).unwrap();
+    while parents.get(cur_cell).unwrap() != cur_cell {
+        cur_cell = parents.get(cur_cell).unwrap();
         path.push(*cur_cell);
     }
 
     path
 }
 
+/// Get the movement cost from the nearest source to every cell using a
+/// multi-source Dijkstra search. Unreachable cells have a cost of u32::MAX.
+pub fn get_cost_field(gridmap: &Gridmap, sources: &[(u32, u32)]) -> Vec<u32> {
+    let mut g_cost: Vec<u32> = vec![u32::MAX; gridmap.get_cells().len()];
+    let mut open_list: BinaryHeap<Reverse<(u32, (u32, u32))>> = BinaryHeap::new();
+
+    for source in sources {
+        if gridmap.xy_is_traversable(*source) {
+            g_cost[gridmap.xy_to_idx(*source)] = 0;
+            open_list.push(Reverse((0, *source)));
+        }
+    }
+
+    while let Some(Reverse((cur_g_cost, cur_pos))) = open_list.pop() {
+        // Skip stale entries of cells that have been reached more cheaply since
+        if cur_g_cost > g_cost[gridmap.xy_to_idx(cur_pos)] {
+            continue;
+        }
+
+        for nb_cell_pos in get_neighbors_8_con(cur_pos, gridmap) {
+            let nb_idx = gridmap.xy_to_idx(nb_cell_pos);
+            let alt_g_cost = cur_g_cost + get_l2_cost(cur_pos, nb_cell_pos);
+
+            if alt_g_cost < g_cost[nb_idx] {
+                g_cost[nb_idx] = alt_g_cost;
+                open_list.push(Reverse((alt_g_cost, nb_cell_pos)));
+            }
+        }
+    }
+
+    g_cost
+}
+
+/// Get the number of 4 way moves from the nearest source to every cell using a
+/// multi-source breadth-first search. Unreachable cells have a distance of u32::MAX.
+pub fn get_distance_field(gridmap: &Gridmap, sources: &[(u32, u32)]) -> Vec<u32> {
+    let mut distance_field: Vec<u32> = vec![u32::MAX; gridmap.get_cells().len()];
+    let mut open_list: VecDeque<(u32, u32)> = VecDeque::new();
+
+    for source in sources {
+        if gridmap.xy_is_traversable(*source) {
+            distance_field[gridmap.xy_to_idx(*source)] = 0;
+            open_list.push_back(*source);
+        }
+    }
+
+    while let Some(cur_pos) = open_list.pop_front() {
+        let cur_distance = distance_field[gridmap.xy_to_idx(cur_pos)];
+
+        for nb_cell_pos in get_neighbors_4_con(cur_pos, gridmap) {
+            let nb_idx = gridmap.xy_to_idx(nb_cell_pos);
+            if distance_field[nb_idx] == u32::MAX {
+                distance_field[nb_idx] = cur_distance + 1;
+                open_list.push_back(nb_cell_pos);
+            }
+        }
+    }
+
+    distance_field
+}
+
 // Common methods to calculate costs
 
 /// Get the Euclidean (L2) Distance
@@ -0,0 +1,374 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::time::Instant;
+
+use super::planner_base::*;
+use super::planner_common::*;
+use crate::maps::gridmap::Gridmap;
+
+// Space-Time A Star
+
+/// A cell and the timestep at which it is occupied
+type SpaceTimeCell = ((u32, u32), u32);
+
+/// A move between two cells and the timestep at which it arrives
+type SpaceTimeMove = ((u32, u32), (u32, u32), u32);
+
+/// Contains the timed path from goal to start and the list of visited space-time cells
+#[derive(Debug)]
+pub struct TimedMotionPlan {
+    /// `(cell, timestep)` pairs with one entry per timestep, waits repeating the cell
+    pub path: Vec<((u32, u32), u32)>,
+    pub closed_list: HashSet<((u32, u32), u32)>,
+    pub stats: SearchStats,
+    /// Ordered record of the search, with timesteps as movement costs
+    pub trace: Option<SearchTrace>,
+}
+
+/// Cells and moves occupied by other agents at each timestep
+#[derive(Clone, Default)]
+pub struct ReservationTable {
+    cells: HashSet<SpaceTimeCell>,
+    edges: HashSet<SpaceTimeMove>,
+    /// Cells held from a timestep onwards, such as by agents parked at their goal
+    held_cells: HashMap<(u32, u32), u32>,
+    /// Latest timestep at which each cell is reserved, not counting held cells
+    last_cell_times: HashMap<(u32, u32), u32>,
+    last_time: u32,
+}
+
+impl ReservationTable {
+    /// Constructor
+    pub fn new() -> ReservationTable {
+        ReservationTable::default()
+    }
+
+    /// Reserve a cell at a single timestep
+    pub fn reserve_cell(&mut self, pos: (u32, u32), time: u32) {
+        self.cells.insert((pos, time));
+
+        let last_cell_time = self.last_cell_times.entry(pos).or_insert(time);
+        *last_cell_time = (*last_cell_time).max(time);
+        self.last_time = self.last_time.max(time);
+    }
+
+    /// Reserve the move between two cells arriving at a timestep
+    pub fn reserve_edge(&mut self, from: (u32, u32), to: (u32, u32), time: u32) {
+        self.edges.insert((from, to, time));
+        self.last_time = self.last_time.max(time);
+    }
+
+    /// Reserve a cell from a timestep onwards
+    pub fn reserve_cell_from(&mut self, pos: (u32, u32), time: u32) {
+        let held_time = self.held_cells.entry(pos).or_insert(time);
+        *held_time = (*held_time).min(time);
+        self.last_time = self.last_time.max(time);
+    }
+
+    /// Reserve every cell of a timed path, and the opposite of each of its moves so
+    /// that no one swaps cells with the agent following it. The path may run in either direction.
+    pub fn reserve_path(&mut self, path: &[((u32, u32), u32)]) {
+        for (pos, time) in path {
+            self.reserve_cell(*pos, *time);
+        }
+
+        for pair in path.windows(2) {
+            let (earlier, later) = if pair[0].1 < pair[1].1 { (pair[0], pair[1]) } else { (pair[1], pair[0]) };
+            if earlier.0 != later.0 {
+                self.reserve_edge(later.0, earlier.0, later.1);
+            }
+        }
+    }
+
+    pub fn is_cell_reserved(&self, pos: (u32, u32), time: u32) -> bool {
+        self.cells.contains(&(pos, time)) || self.held_cells.get(&pos).is_some_and(|held_time| *held_time <= time)
+    }
+
+    /// Whether moving (or waiting) from one cell to another, arriving at a timestep, is blocked
+    pub fn is_move_reserved(&self, from: (u32, u32), to: (u32, u32), time: u32) -> bool {
+        self.is_cell_reserved(to, time) || self.edges.contains(&(from, to, time))
+    }
+
+    /// Whether an agent can arrive at a cell at a timestep and stay there for good
+    pub fn can_hold_cell(&self, pos: (u32, u32), time: u32) -> bool {
+        !self.held_cells.contains_key(&pos)
+            && self.last_cell_times.get(&pos).is_none_or(|last_time| *last_time < time)
+    }
+
+    /// Latest timestep of any reservation
+    pub fn get_last_time(&self) -> u32 {
+        self.last_time
+    }
+
+    pub fn clear(&mut self) {
+        *self = ReservationTable::default();
+    }
+}
+
+/// A* over cells and timesteps, moving to a 4 way neighbor or waiting in place
+/// each timestep while avoiding the reservations of other agents. The plan stops
+/// at the first goal cell where the agent can stay without running into a later
+/// reservation, and minimises the arrival timestep.
+pub struct SpaceTimeAStarPlanner {
+    start: (u32, u32),
+    goal_region: GoalRegion,
+    gridmap: Gridmap,
+    reservation_table: ReservationTable,
+    /// Number of timesteps planned ahead
+    window: u32,
+    budget: PlanningBudget,
+    trace_level: TraceLevel,
+}
+
+impl Planner for SpaceTimeAStarPlanner {
+    fn new(gridmap: &Gridmap) -> SpaceTimeAStarPlanner {
+        SpaceTimeAStarPlanner {
+            start: (0, 0),
+            goal_region: GoalRegion::new(&[(0, 0)], 0.0),
+            gridmap: gridmap.clone(),
+            reservation_table: ReservationTable::new(),
+            window: u32::MAX,
+            budget: PlanningBudget::default(),
+            trace_level: TraceLevel::Off,
+        }
+    }
+
+    /// The path holds one cell per timestep, so waits repeat a cell
+    fn try_generate_plan(&self) -> Result<MotionPlan, PlanningError> {
+        let timed_plan = self.try_generate_timed_plan()?;
+
+        Ok(MotionPlan {
+            path: timed_plan.path.iter().map(|(pos, _)| *pos).collect(),
+            closed_list: timed_plan.closed_list.iter().map(|(pos, _)| *pos).collect(),
+            stats: timed_plan.stats,
+            trace: timed_plan.trace,
+        })
+    }
+
+    fn get_gridmap(&self) -> &Gridmap {
+        &self.gridmap
+    }
+
+    fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool {
+        self.gridmap = gridmap.clone();
+        true
+    }
+
+    fn update_start(&mut self, start: (u32, u32)) -> bool {
+        self.start = start;
+        true
+    }
+
+    fn update_goals(&mut self, goals: &[(u32, u32)]) -> bool {
+        self.goal_region.set_goals(goals)
+    }
+
+    fn update_goal_tolerance(&mut self, tolerance: f32) -> bool {
+        self.goal_region.set_tolerance(tolerance)
+    }
+
+    fn update_budget(&mut self, budget: &PlanningBudget) -> bool {
+        self.budget = budget.clone();
+        true
+    }
+
+    fn update_trace_level(&mut self, trace_level: TraceLevel) -> bool {
+        self.trace_level = trace_level;
+        true
+    }
+}
+
+impl SpaceTimeAStarPlanner {
+    /// Retrieve a timed plan from start to goal that avoids the reservations. Plans that
+    /// do not reach the goal within the window end at the most promising cell at its edge.
+    /// The path is empty if no such plan exists or the planning budget runs out.
+    pub fn generate_timed_plan(&self) -> TimedMotionPlan {
+        self.try_generate_timed_plan().unwrap_or_else(|error| TimedMotionPlan {
+            path: Vec::new(),
+            closed_list: HashSet::new(),
+            stats: error.partial_plan.stats,
+            trace: error.partial_plan.trace,
+        })
+    }
+
+    /// Retrieve a timed plan like `generate_timed_plan`, or the best partial result if
+    /// the planning budget runs out first. The partial path leads to the expanded cell
+    /// closest to the goal, one cell per timestep.
+    pub fn try_generate_timed_plan(&self) -> Result<TimedMotionPlan, PlanningError> {
+        let start_time = Instant::now();
+        let mut path: Vec<SpaceTimeCell> = Vec::new();
+        let mut stats = SearchStats::default();
+        let mut trace_recorder = TraceRecorder::new(self.trace_level);
+
+        let mut open_list: BinaryHeap<Reverse<Node>> = BinaryHeap::new();
+        let mut closed_list: HashSet<SpaceTimeCell> = HashSet::new();
+        let mut parents: HashMap<SpaceTimeCell, SpaceTimeCell> = HashMap::new();
+
+        // Number of moves to the goal region, ignoring other agents
+        let distance_field = get_distance_field(&self.gridmap, &self.goal_region.get_cells(&self.gridmap));
+        if !self.gridmap.xy_in_map(self.start) || distance_field[self.gridmap.xy_to_idx(self.start)] == u32::MAX {
+            return Ok(TimedMotionPlan {
+                path,
+                closed_list,
+                stats,
+                trace: trace_recorder.finish(),
+            });
+        }
+
+        // Once all reservations have passed, waiting any longer than it takes
+        // to visit every free cell never helps
+        let num_free = distance_field.iter().filter(|distance| **distance != u32::MAX).count() as u32;
+        let max_time = self.reservation_table.get_last_time() + num_free;
+
+        open_list.push(Reverse(Node::new(
+            self.start,
+            0,
+            distance_field[self.gridmap.xy_to_idx(self.start)],
+        )));
+        parents.insert((self.start, 0), (self.start, 0));
+
+        let mut budget_tracker = BudgetTracker::new(&self.budget);
+        let mut closest_state = (self.start, 0);
+        stats.nodes_generated = 1;
+        stats.max_open_list_size = 1;
+
+        while let Some(Reverse(cur_node)) = open_list.pop() {
+            let cur_state = (cur_node.pos, cur_node.time);
+            if !closed_list.insert(cur_state) {
+                continue;
+            }
+
+            if self.goal_region.contains(cur_node.pos)
+                && self.reservation_table.can_hold_cell(cur_node.pos, cur_node.time)
+            {
+                path = trace_path(&cur_state, &parents);
+                break;
+            }
+
+            if cur_node.time >= self.window {
+                path = trace_path(&cur_state, &parents);
+                break;
+            }
+
+            if cur_node.time >= max_time {
+                continue;
+            }
+
+            if distance_field[self.gridmap.xy_to_idx(cur_node.pos)]
+                < distance_field[self.gridmap.xy_to_idx(closest_state.0)]
+            {
+                closest_state = cur_state;
+            }
+            if let Err(interruption) = budget_tracker.expand() {
+                let partial_path: Vec<(u32, u32)> =
+                    trace_path(&closest_state, &parents).iter().map(|(pos, _)| *pos).collect();
+                stats.finish(&partial_path, &self.gridmap, start_time);
+                let partial_plan = MotionPlan {
+                    path: partial_path,
+                    closed_list: closed_list.iter().map(|(pos, _)| *pos).collect(),
+                    stats,
+                    trace: trace_recorder.finish(),
+                };
+                return Err(PlanningError { interruption, partial_plan: Box::new(partial_plan) });
+            }
+            stats.nodes_expanded += 1;
+            trace_recorder.record_expansion(
+                cur_node.pos,
+                parents[&cur_state].0,
+                cur_node.time,
+                distance_field[self.gridmap.xy_to_idx(cur_node.pos)],
+            );
+
+            // Explore neighbors and waiting in place, all taking one timestep.
+            // Every state is reached at its own timestep, so the first parent found is as good as any.
+            let next_time = cur_node.time + 1;
+            let mut next_cells = get_neighbors_4_con(cur_node.pos, &self.gridmap);
+            next_cells.push(cur_node.pos);
+
+            for next_pos in next_cells {
+                let next_state = (next_pos, next_time);
+                if parents.contains_key(&next_state)
+                    || self.reservation_table.is_move_reserved(cur_node.pos, next_pos, next_time)
+                {
+                    continue;
+                }
+
+                parents.insert(next_state, cur_state);
+                open_list.push(Reverse(Node::new(
+                    next_pos,
+                    next_time,
+                    next_time + distance_field[self.gridmap.xy_to_idx(next_pos)],
+                )));
+                stats.nodes_generated += 1;
+                stats.max_open_list_size = stats.max_open_list_size.max(open_list.len());
+            }
+            trace_recorder.record_open_list(|| {
+                let mut entries: Vec<&Node> = open_list.iter().map(|entry| &entry.0).collect();
+                entries.sort();
+                entries.into_iter().map(|entry| entry.pos).collect::<Vec<_>>()
+            });
+        }
+
+        let cells: Vec<(u32, u32)> = path.iter().map(|(pos, _)| *pos).collect();
+        stats.finish(&cells, &self.gridmap, start_time);
+        Ok(TimedMotionPlan {
+            path,
+            closed_list,
+            stats,
+            trace: trace_recorder.finish(),
+        })
+    }
+
+    /// Set the reservations of other agents to avoid
+    pub fn update_reservation_table(&mut self, reservation_table: &ReservationTable) -> bool {
+        self.reservation_table = reservation_table.clone();
+        true
+    }
+
+    pub fn get_reservation_table(&self) -> &ReservationTable {
+        &self.reservation_table
+    }
+
+    /// Set how many timesteps ahead to plan, must be positive
+    pub fn update_window(&mut self, window: u32) -> bool {
+        if window == 0 {
+            return false;
+        }
+        self.window = window;
+        true
+    }
+}
+
+#[derive(Eq)]
+struct Node {
+    pub pos: (u32, u32),
+    pub time: u32,
+    pub f_cost: u32,
+}
+
+impl Ord for Node {
+    // Break ties towards later timesteps, which are closer to the goal
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.f_cost
+            .cmp(&other.f_cost)
+            .then_with(|| other.time.cmp(&self.time))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.f_cost == other.f_cost && self.time == other.time
+    }
+}
+
+impl Node {
+    fn new(pos: (u32, u32), time: u32, f_cost: u32) -> Node {
+        Node { pos, time, f_cost }
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use super::planner_base::*;
+use super::planner_common::*;
+use crate::maps::gridmap::Gridmap;
+
+// Travelling Salesman Tour
+
+/// Tours with at most this many waypoints are solved exactly by default
+const DEFAULT_MAX_EXACT_WAYPOINTS: usize = 12;
+
+/// Upper limit on exactly solved tours, as the solver's memory grows with 2^N
+const MAX_EXACT_WAYPOINTS_LIMIT: usize = 20;
+
+/// Maximum number of improvement passes made by the heuristic solver
+const MAX_IMPROVEMENT_PASSES: usize = 100;
+
+/// Longest run of consecutive waypoints moved at once by Or-opt
+const MAX_OR_OPT_SEGMENT: usize = 3;
+
+/// Cost given to unreachable legs by the heuristic solver, larger than any reachable tour
+const UNREACHABLE_COST: i64 = 1 << 48;
+
+/// Contains the order the waypoints are visited in, the cost of the tour and its motion plan
+#[derive(Debug)]
+pub struct TourPlan {
+    /// Indices of the waypoints in visiting order, beginning with the first waypoint
+    pub order: Vec<usize>,
+    /// Total movement cost of the tour, in the units of `get_l2_cost`
+    pub cost: u64,
+    /// Path through the waypoints, from the end of the tour to its start
+    pub motion_plan: MotionPlan,
+}
+
+/// Returned when tour planning is interrupted, holding the best partial result found
+#[derive(Debug)]
+pub struct TourPlanningError {
+    pub interruption: PlanningInterruption,
+    /// Best order found and its cost, empty if the waypoints were not ordered yet, with
+    /// the path through the legs planned so far
+    pub partial_plan: Box<TourPlan>,
+}
+
+impl fmt::Display for TourPlanningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.interruption)
+    }
+}
+
+impl std::error::Error for TourPlanningError {}
+
+/// Visits every waypoint in the cheapest order found, beginning at the first waypoint.
+/// Pairwise costs come from a Dijkstra search out of each waypoint, and the legs of
+/// the resulting tour are planned with the grid planner `P`.
+pub struct TourPlanner<P: Planner> {
+    planner: P,
+    gridmap: Gridmap,
+    waypoints: Vec<(u32, u32)>,
+    /// Whether the tour ends back at the first waypoint
+    return_to_start: bool,
+    /// Tours with more waypoints than this are solved heuristically
+    max_exact_waypoints: usize,
+    /// Checked between computing the costs, ordering the waypoints and planning the
+    /// legs, which share what is left of it
+    budget: PlanningBudget,
+}
+
+impl<P: Planner> TourPlanner<P> {
+    /// Constructor
+    pub fn new(gridmap: &Gridmap) -> TourPlanner<P> {
+        TourPlanner {
+            planner: P::new(gridmap),
+            gridmap: gridmap.clone(),
+            waypoints: Vec::new(),
+            return_to_start: false,
+            max_exact_waypoints: DEFAULT_MAX_EXACT_WAYPOINTS,
+            budget: PlanningBudget::default(),
+        }
+    }
+
+    /// Retrieve the cheapest tour found through all waypoints. The order and path are empty
+    /// if there are no waypoints, any of them cannot be reached or the planning budget runs out.
+    pub fn generate_plan(&mut self) -> TourPlan {
+        self.try_generate_plan().unwrap_or_else(|_| get_empty_tour_plan())
+    }
+
+    /// Retrieve the cheapest tour found like `generate_plan`, or the best order found
+    /// if the planning budget runs out first
+    pub fn try_generate_plan(&mut self) -> Result<TourPlan, TourPlanningError> {
+        let budget_tracker = BudgetTracker::new(&self.budget);
+        let cost_matrix = self.get_cost_matrix();
+        budget_tracker.check().map_err(|interruption| TourPlanningError {
+            interruption,
+            partial_plan: Box::new(get_empty_tour_plan()),
+        })?;
+
+        let order = if self.waypoints.len() <= self.max_exact_waypoints {
+            solve_tour_exact(&cost_matrix, self.return_to_start)
+        } else {
+            solve_tour_heuristic(&cost_matrix, self.return_to_start)
+        };
+
+        let Some(order) = order else {
+            return Ok(get_empty_tour_plan());
+        };
+
+        let cost = get_tour_cost(&cost_matrix, &order, self.return_to_start).unwrap();
+        if let Err(interruption) = budget_tracker.check() {
+            let partial_plan = TourPlan { order, cost, motion_plan: get_empty_tour_plan().motion_plan };
+            return Err(TourPlanningError { interruption, partial_plan: Box::new(partial_plan) });
+        }
+
+        let mut route: Vec<(u32, u32)> = order.iter().map(|idx| self.waypoints[*idx]).collect();
+        if self.return_to_start {
+            route.push(self.waypoints[0]);
+        }
+        self.planner.update_budget(&budget_tracker.get_remaining_budget());
+        match self.planner.try_generate_route_plan(&route) {
+            Ok(motion_plan) => Ok(TourPlan { order, cost, motion_plan }),
+            Err(error) => {
+                let partial_plan = TourPlan { order, cost, motion_plan: *error.partial_plan };
+                Err(TourPlanningError { interruption: error.interruption, partial_plan: Box::new(partial_plan) })
+            }
+        }
+    }
+
+    /// Get the movement cost between every pair of waypoints, u32::MAX if unreachable
+    pub fn get_cost_matrix(&self) -> Vec<Vec<u32>> {
+        get_cost_matrix(&self.gridmap, &self.waypoints)
+    }
+
+    pub fn update_gridmap(&mut self, gridmap: &Gridmap) -> bool {
+        self.gridmap = gridmap.clone();
+        self.planner.update_gridmap(gridmap)
+    }
+
+    /// Set the waypoints to visit, the first of which is where the tour begins
+    pub fn update_waypoints(&mut self, waypoints: &[(u32, u32)]) -> bool {
+        if waypoints.iter().any(|waypoint| !self.gridmap.xy_in_map(*waypoint)) {
+            return false;
+        }
+        self.waypoints = waypoints.to_vec();
+        true
+    }
+
+    /// Set whether the tour ends back at the first waypoint
+    pub fn update_return_to_start(&mut self, return_to_start: bool) -> bool {
+        self.return_to_start = return_to_start;
+        true
+    }
+
+    /// Set the largest number of waypoints solved exactly, at most 20
+    pub fn update_max_exact_waypoints(&mut self, max_exact_waypoints: usize) -> bool {
+        if max_exact_waypoints > MAX_EXACT_WAYPOINTS_LIMIT {
+            return false;
+        }
+        self.max_exact_waypoints = max_exact_waypoints;
+        true
+    }
+
+    pub fn update_budget(&mut self, budget: &PlanningBudget) -> bool {
+        self.budget = budget.clone();
+        true
+    }
+}
+
+/// Tour plan with no order or path, for tours that cannot be planned
+fn get_empty_tour_plan() -> TourPlan {
+    TourPlan {
+        order: Vec::new(),
+        cost: 0,
+        motion_plan: MotionPlan {
+            path: Vec::new(),
+            closed_list: HashSet::new(),
+            stats: SearchStats::default(),
+            trace: None,
+        },
+    }
+}
+
+/// Get the movement cost between every pair of waypoints using a Dijkstra
+/// search out of each of them, u32::MAX if unreachable
+pub fn get_cost_matrix(gridmap: &Gridmap, waypoints: &[(u32, u32)]) -> Vec<Vec<u32>> {
+    waypoints
+        .iter()
+        .map(|waypoint| {
+            let cost_field = get_cost_field(gridmap, &[*waypoint]);
+            waypoints
+                .iter()
+                .map(|other| cost_field[gridmap.xy_to_idx(*other)])
+                .collect()
+        })
+        .collect()
+}
+
+/// Get the total cost of visiting the waypoints in order, None if any leg is unreachable
+pub fn get_tour_cost(cost_matrix: &[Vec<u32>], order: &[usize], return_to_start: bool) -> Option<u64> {
+    let mut legs: Vec<(usize, usize)> = order.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    if return_to_start && order.len() > 1 {
+        legs.push((order[order.len() - 1], order[0]));
+    }
+
+    legs.iter().try_fold(0, |total: u64, (from, to)| match cost_matrix[*from][*to] {
+        u32::MAX => None,
+        cost => Some(total + cost as u64),
+    })
+}
+
+/// Find the optimal visiting order beginning at waypoint 0 with the Held-Karp
+/// dynamic program. Returns None if no tour visits every waypoint.
+pub fn solve_tour_exact(cost_matrix: &[Vec<u32>], return_to_start: bool) -> Option<Vec<usize>> {
+    let num_waypoints = cost_matrix.len();
+    if num_waypoints <= 1 {
+        return Some((0..num_waypoints).collect());
+    }
+
+    let get_cost = |from: usize, to: usize| match cost_matrix[from][to] {
+        u32::MAX => u64::MAX,
+        cost => cost as u64,
+    };
+
+    // Waypoint 0 is always visited first, so the subsets range over the
+    // others, with bit j representing waypoint j + 1
+    let num_others = num_waypoints - 1;
+    let num_subsets = 1 << num_others;
+
+    // Cheapest cost of visiting a subset and ending at one of its waypoints, and the previous waypoint
+    let mut min_cost: Vec<u64> = vec![u64::MAX; num_subsets * num_others];
+    let mut prev: Vec<usize> = vec![usize::MAX; num_subsets * num_others];
+
+    for last in 0..num_others {
+        min_cost[(1 << last) * num_others + last] = get_cost(0, last + 1);
+    }
+
+    for subset in 1..num_subsets {
+        for last in 0..num_others {
+            let cur_cost = min_cost[subset * num_others + last];
+            if subset & (1 << last) == 0 || cur_cost == u64::MAX {
+                continue;
+            }
+
+            for next in 0..num_others {
+                if subset & (1 << next) != 0 {
+                    continue;
+                }
+
+                let leg_cost = get_cost(last + 1, next + 1);
+                if leg_cost == u64::MAX {
+                    continue;
+                }
+
+                let next_idx = (subset | (1 << next)) * num_others + next;
+                if cur_cost + leg_cost < min_cost[next_idx] {
+                    min_cost[next_idx] = cur_cost + leg_cost;
+                    prev[next_idx] = last;
+                }
+            }
+        }
+    }
+
+    let full_subset = num_subsets - 1;
+    let (best_cost, mut last) = (0..num_others)
+        .map(|last| {
+            let cost = min_cost[full_subset * num_others + last];
+            let return_cost = if return_to_start { get_cost(last + 1, 0) } else { 0 };
+            (cost.saturating_add(return_cost), last)
+        })
+        .min()?;
+
+    if best_cost == u64::MAX {
+        return None;
+    }
+
+    // Walk back through the subsets to recover the order
+    let mut order: Vec<usize> = Vec::new();
+    let mut subset = full_subset;
+    while last != usize::MAX {
+        order.push(last + 1);
+        let prev_last = prev[subset * num_others + last];
+        subset &= !(1 << last);
+        last = prev_last;
+    }
+    order.push(0);
+    order.reverse();
+
+    Some(order)
+}
+
+/// Find a good visiting order beginning at waypoint 0 with a nearest neighbor tour,
+/// improved by 2-opt and Or-opt moves. Moves are evaluated assuming symmetric costs,
+/// as between cells of a gridmap. Returns None if no tour visits every waypoint.
+pub fn solve_tour_heuristic(cost_matrix: &[Vec<u32>], return_to_start: bool) -> Option<Vec<usize>> {
+    let num_waypoints = cost_matrix.len();
+    if num_waypoints <= 1 {
+        return Some((0..num_waypoints).collect());
+    }
+
+    // Nearest neighbor construction
+    let mut order: Vec<usize> = vec![0];
+    let mut visited: Vec<bool> = vec![false; num_waypoints];
+    visited[0] = true;
+
+    while order.len() < num_waypoints {
+        let last = order[order.len() - 1];
+        let next = (0..num_waypoints)
+            .filter(|idx| !visited[*idx] && cost_matrix[last][*idx] != u32::MAX)
+            .min_by_key(|idx| cost_matrix[last][*idx])?;
+
+        visited[next] = true;
+        order.push(next);
+    }
+
+    get_tour_cost(cost_matrix, &order, return_to_start)?;
+
+    // Cost of the edge between two waypoints, where None stands for the
+    // end of an open tour and unreachable legs are never worth adding
+    let get_cost = |from: Option<usize>, to: Option<usize>| -> i64 {
+        match (from, to) {
+            (Some(from), Some(to)) if cost_matrix[from][to] == u32::MAX => UNREACHABLE_COST,
+            (Some(from), Some(to)) => cost_matrix[from][to] as i64,
+            _ => 0,
+        }
+    };
+
+    for _ in 0..MAX_IMPROVEMENT_PASSES {
+        let mut improved = false;
+
+        // Waypoint after a tour position, wrapping around to the start of a closed tour
+        let get_next = |order: &[usize], pos: usize| match pos + 1 {
+            next if next < num_waypoints => Some(order[next]),
+            _ if return_to_start => Some(order[0]),
+            _ => None,
+        };
+
+        // 2-opt, reverse the waypoints between positions i and k
+        for i in 1..num_waypoints - 1 {
+            for k in i + 1..num_waypoints {
+                let (prev, first, last, next) = (
+                    Some(order[i - 1]),
+                    Some(order[i]),
+                    Some(order[k]),
+                    get_next(&order, k),
+                );
+
+                let delta = get_cost(prev, last) + get_cost(first, next)
+                    - get_cost(prev, first)
+                    - get_cost(last, next);
+
+                if delta < 0 {
+                    order[i..=k].reverse();
+                    improved = true;
+                }
+            }
+        }
+
+        // Or-opt, move a short run of waypoints between two others elsewhere in the tour
+        for segment_len in 1..=MAX_OR_OPT_SEGMENT.min(num_waypoints - 1) {
+            for i in 1..=num_waypoints - segment_len {
+                let segment_end = i + segment_len - 1;
+                let (prev, first, last, next) = (
+                    Some(order[i - 1]),
+                    Some(order[i]),
+                    Some(order[segment_end]),
+                    get_next(&order, segment_end),
+                );
+                let removal_delta = get_cost(prev, next) - get_cost(prev, first) - get_cost(last, next);
+
+                let best_insertion = (0..num_waypoints)
+                    .filter(|pos| *pos + 1 < i || *pos > segment_end)
+                    .map(|pos| {
+                        let (before, after) = (Some(order[pos]), get_next(&order, pos));
+                        let insertion_delta = get_cost(before, first) + get_cost(last, after) - get_cost(before, after);
+                        (removal_delta + insertion_delta, pos)
+                    })
+                    .min();
+
+                if let Some((delta, pos)) = best_insertion {
+                    if delta < 0 {
+                        let segment: Vec<usize> = order.drain(i..=segment_end).collect();
+                        let insert_at = if pos < i { pos + 1 } else { pos + 1 - segment_len };
+                        order.splice(insert_at..insert_at, segment);
+                        improved = true;
+                    }
+                }
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    Some(order)
+}
@@ -0,0 +1,111 @@
+//! Checks of a path against a gridmap, for tests and for catching bad plans before
+//! they are sent to a controller
+use std::fmt;
+
+use super::planner_common::Neighborhood;
+use crate::maps::gridmap::Gridmap;
+
+/// Problems found along a path. Points are given by their index in the path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathValidation {
+    /// First point that is not a single move from the one before it. Repeating a
+    /// point, as timed paths do to wait, counts as a move.
+    pub first_invalid_step: Option<usize>,
+    /// First point whose footprint overlaps a cell that is not traversable
+    pub first_collision: Option<usize>,
+    /// Every point lying outside the map
+    pub out_of_bounds: Vec<usize>,
+    /// First and last points of the path, None if it is empty
+    pub endpoints: Option<((u32, u32), (u32, u32))>,
+}
+
+impl PathValidation {
+    /// Whether the path is non-empty, contiguous, within the map and free of collisions
+    pub fn is_valid(&self) -> bool {
+        self.endpoints.is_some()
+            && self.first_invalid_step.is_none()
+            && self.first_collision.is_none()
+            && self.out_of_bounds.is_empty()
+    }
+
+    /// Whether the path runs from the goal back to the start, like the planners' paths
+    pub fn endpoints_match(&self, start: (u32, u32), goal: (u32, u32)) -> bool {
+        self.endpoints == Some((goal, start))
+    }
+}
+
+impl fmt::Display for PathValidation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut problems: Vec<String> = Vec::new();
+
+        if self.endpoints.is_none() {
+            problems.push("path is empty".to_string());
+        }
+        if let Some(idx) = self.first_invalid_step {
+            problems.push(format!("point {} is not a single move from the one before it", idx));
+        }
+        if let Some(idx) = self.first_collision {
+            problems.push(format!("point {} collides with an obstacle", idx));
+        }
+        if let Some(idx) = self.out_of_bounds.first() {
+            problems.push(format!("{} points lie outside the map, the first being point {}", self.out_of_bounds.len(), idx));
+        }
+
+        if problems.is_empty() {
+            write!(f, "path is valid")
+        } else {
+            write!(f, "{}", problems.join(", "))
+        }
+    }
+}
+
+/// Check every step of a path against the moves of a neighborhood, and every point
+/// against the map. The footprint is a radius in cells around each point, 0 checking
+/// the point's own cell only. Parts of a footprint beyond the map are not checked.
+pub fn validate_path(
+    gridmap: &Gridmap,
+    path: &[(u32, u32)],
+    neighborhood: &Neighborhood,
+    footprint: f32,
+) -> PathValidation {
+    let mut validation = PathValidation {
+        endpoints: path.first().zip(path.last()).map(|(first, last)| (*first, *last)),
+        ..Default::default()
+    };
+
+    validation.first_invalid_step = path
+        .windows(2)
+        .position(|pair| pair[0] != pair[1] && !neighborhood.is_move(pair[0], pair[1], gridmap))
+        .map(|idx| idx + 1);
+
+    for (idx, point) in path.iter().enumerate() {
+        if !gridmap.xy_in_map(*point) {
+            validation.out_of_bounds.push(idx);
+        } else if validation.first_collision.is_none() && footprint_collides(gridmap, *point, footprint) {
+            validation.first_collision = Some(idx);
+        }
+    }
+
+    validation
+}
+
+/// Whether any cell of the map within a radius of a point is not traversable
+fn footprint_collides(gridmap: &Gridmap, point: (u32, u32), footprint: f32) -> bool {
+    let radius = footprint.max(0.0).floor() as i64;
+
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let (x, y) = (point.0 as i64 + dx, point.1 as i64 + dy);
+            let in_map = (0..gridmap.get_width() as i64).contains(&x) && (0..gridmap.get_height() as i64).contains(&y);
+
+            if in_map
+                && ((dx * dx + dy * dy) as f32).sqrt() <= footprint
+                && !gridmap.xy_is_traversable((x as u32, y as u32))
+            {
+                return true;
+            }
+        }
+    }
+
+    false
+}
@@ -0,0 +1,2 @@
+pub mod shortcut;
+pub mod smoother;
@@ -0,0 +1,151 @@
+//! Removal of redundant waypoints from grid paths, keeping only those needed for straight
+//! segments with line of sight between them
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::maps::gridmap::Gridmap;
+
+/// How the cells under a straight segment are found
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineOfSight {
+    /// Bresenham's line, a single cell per step along the longer axis. Diagonal steps
+    /// may cut past the corners of obstacles, like the 8 way planners.
+    Bresenham,
+    /// Every cell the segment passes through, including both cells beside a corner it
+    /// passes exactly through, so no part of the segment crosses an obstacle
+    #[default]
+    Supercover,
+}
+
+/// Get the cells under a segment from one cell centroid to another, in order
+pub fn get_line_cells(from: (u32, u32), to: (u32, u32), line_of_sight: LineOfSight) -> Vec<(u32, u32)> {
+    match line_of_sight {
+        LineOfSight::Bresenham => get_bresenham_cells(from, to),
+        LineOfSight::Supercover => get_supercover_cells(from, to),
+    }
+}
+
+fn get_bresenham_cells(from: (u32, u32), to: (u32, u32)) -> Vec<(u32, u32)> {
+    let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+    let (dx, dy) = ((to.0 as i64 - x).abs(), -(to.1 as i64 - y).abs());
+    let (step_x, step_y) = ((to.0 as i64 - x).signum(), (to.1 as i64 - y).signum());
+    let mut error = dx + dy;
+    let mut cells = vec![from];
+
+    while (x, y) != (to.0 as i64, to.1 as i64) {
+        let doubled_error = 2 * error;
+        if doubled_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+        cells.push((x as u32, y as u32));
+    }
+
+    cells
+}
+
+fn get_supercover_cells(from: (u32, u32), to: (u32, u32)) -> Vec<(u32, u32)> {
+    let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+    let (num_x, num_y) = ((to.0 as i64 - x).abs(), (to.1 as i64 - y).abs());
+    let (step_x, step_y) = ((to.0 as i64 - x).signum(), (to.1 as i64 - y).signum());
+    let (mut moved_x, mut moved_y) = (0, 0);
+    let mut cells = vec![from];
+
+    while moved_x < num_x || moved_y < num_y {
+        // Compare where the segment crosses the next vertical and horizontal cell borders
+        let decision = (1 + 2 * moved_x) * num_y - (1 + 2 * moved_y) * num_x;
+
+        if decision == 0 {
+            cells.push(((x + step_x) as u32, y as u32));
+            cells.push((x as u32, (y + step_y) as u32));
+            x += step_x;
+            y += step_y;
+            moved_x += 1;
+            moved_y += 1;
+        } else if decision < 0 {
+            x += step_x;
+            moved_x += 1;
+        } else {
+            y += step_y;
+            moved_y += 1;
+        }
+        cells.push((x as u32, y as u32));
+    }
+
+    cells
+}
+
+/// Whether every cell under a segment between two cells is traversable
+pub fn has_line_of_sight(gridmap: &Gridmap, from: (u32, u32), to: (u32, u32), line_of_sight: LineOfSight) -> bool {
+    get_line_cells(from, to, line_of_sight)
+        .into_iter()
+        .all(|cell| gridmap.xy_in_map(cell) && gridmap.xy_is_traversable(cell))
+}
+
+/// Walk along the path, jumping each time to the furthest later point in line of sight.
+/// Consecutive points of the input are always kept joined, so the result has no
+/// collisions the input did not have, and keeps its first and last points.
+pub fn shortcut_greedy(gridmap: &Gridmap, path: &[(u32, u32)], line_of_sight: LineOfSight) -> Vec<(u32, u32)> {
+    let Some(first) = path.first() else {
+        return Vec::new();
+    };
+
+    let mut waypoints = vec![*first];
+    let mut cur_idx = 0;
+
+    while cur_idx + 1 < path.len() {
+        cur_idx = (cur_idx + 1..path.len())
+            .rev()
+            .find(|idx| *idx == cur_idx + 1 || has_line_of_sight(gridmap, path[cur_idx], path[*idx], line_of_sight))
+            .unwrap_or(cur_idx + 1);
+        waypoints.push(path[cur_idx]);
+    }
+
+    waypoints
+}
+
+/// Repeatedly pick two random points of the path and drop those between them if they
+/// are in line of sight. Finds shortcuts the greedy walk misses, such as when its first
+/// jump leads into a worse route. The same seed always gives the same result.
+pub fn shortcut_random(
+    gridmap: &Gridmap,
+    path: &[(u32, u32)],
+    line_of_sight: LineOfSight,
+    iterations: usize,
+    seed: u64,
+) -> Vec<(u32, u32)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut waypoints = path.to_vec();
+
+    for _ in 0..iterations {
+        if waypoints.len() < 3 {
+            break;
+        }
+
+        let first_idx = rng.gen_range(0..waypoints.len() - 2);
+        let last_idx = rng.gen_range(first_idx + 2..waypoints.len());
+
+        if has_line_of_sight(gridmap, waypoints[first_idx], waypoints[last_idx], line_of_sight) {
+            waypoints.drain(first_idx + 1..last_idx);
+        }
+    }
+
+    waypoints
+}
+
+/// Fill in the cells under each segment between waypoints, giving a path with a single
+/// move between consecutive cells. Bresenham's cells all lie under the segment, so they
+/// are clear whenever the waypoints are in line of sight of either kind.
+pub fn get_dense_path(waypoints: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let mut path: Vec<(u32, u32)> = waypoints.iter().take(1).copied().collect();
+
+    for pair in waypoints.windows(2) {
+        path.extend(get_bresenham_cells(pair[0], pair[1]).into_iter().skip(1));
+    }
+
+    path
+}
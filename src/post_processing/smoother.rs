@@ -0,0 +1,181 @@
+//! Gradient descent smoothing of paths into sub-cell points, in the spirit of the Nav2
+//! simple and constrained smoothers. Points are in cell units, where integer coordinates
+//! lie on cell centroids, like the FMM smooth path and the path metrics.
+use crate::maps::gridmap::Gridmap;
+use crate::metrics;
+
+/// Weights and limits of the smoother, with distances in cells
+#[derive(Clone, Debug, PartialEq)]
+pub struct SmootherConfig {
+    /// Pull of each point towards the middle of its neighbors
+    pub smooth_weight: f32,
+    /// Pull of each point back towards where it started
+    pub data_weight: f32,
+    /// Pull towards the middle of the neighbors at points curving more than the maximum
+    pub curvature_weight: f32,
+    /// Largest curvature left alone, in 1/cells
+    pub max_curvature: f32,
+    /// Push away from obstacles closer than the clearance
+    pub obstacle_weight: f32,
+    pub clearance: f32,
+    /// Distance between the points the path is resampled into before smoothing, None to
+    /// keep the points given
+    pub spacing: Option<f32>,
+    pub max_iterations: usize,
+    /// Smoothing stops once the points move less than this in total in an iteration
+    pub tolerance: f32,
+}
+
+impl Default for SmootherConfig {
+    fn default() -> SmootherConfig {
+        SmootherConfig {
+            smooth_weight: 0.3,
+            data_weight: 0.2,
+            curvature_weight: 0.1,
+            max_curvature: 0.5,
+            obstacle_weight: 0.2,
+            clearance: 3.0,
+            spacing: Some(1.0),
+            max_iterations: 1000,
+            tolerance: 1e-4,
+        }
+    }
+}
+
+/// Smooth a path, keeping its first and last points fixed. Points are only moved to
+/// traversable cells, so the smoothed points stay clear of obstacles.
+pub fn smooth_path(gridmap: &Gridmap, path: &[(f32, f32)], config: &SmootherConfig) -> Vec<(f32, f32)> {
+    let original = match config.spacing {
+        Some(spacing) => resample_path(path, spacing),
+        None => path.to_vec(),
+    };
+    let distance_field = metrics::get_obstacle_distance_field(gridmap);
+    let mut smoothed = original.clone();
+
+    for _ in 0..config.max_iterations {
+        let mut change = 0.0;
+
+        // Update in place, so each point already sees the moves of the one before it
+        for idx in 1..smoothed.len().saturating_sub(1) {
+            let (prev, cur, next) = (smoothed[idx - 1], smoothed[idx], smoothed[idx + 1]);
+            let middle = ((prev.0 + next.0) / 2.0, (prev.1 + next.1) / 2.0);
+
+            let mut step = (
+                config.data_weight * (original[idx].0 - cur.0) + 2.0 * config.smooth_weight * (middle.0 - cur.0),
+                config.data_weight * (original[idx].1 - cur.1) + 2.0 * config.smooth_weight * (middle.1 - cur.1),
+            );
+
+            let curvature = get_curvature(prev, cur, next);
+            if curvature > config.max_curvature {
+                let excess = config.curvature_weight * (curvature - config.max_curvature) / curvature;
+                step.0 += excess * (middle.0 - cur.0);
+                step.1 += excess * (middle.1 - cur.1);
+            }
+
+            let distance = get_distance(gridmap, &distance_field, cur, config.clearance);
+            if distance < config.clearance {
+                let gradient = get_distance_gradient(gridmap, &distance_field, cur, config.clearance);
+                step.0 += config.obstacle_weight * (config.clearance - distance) * gradient.0;
+                step.1 += config.obstacle_weight * (config.clearance - distance) * gradient.1;
+            }
+
+            let point = (cur.0 + step.0, cur.1 + step.1);
+            if is_traversable(gridmap, point) {
+                smoothed[idx] = point;
+                change += step.0.hypot(step.1);
+            }
+        }
+
+        if change < config.tolerance {
+            break;
+        }
+    }
+
+    smoothed
+}
+
+/// Resample a path into points evenly spaced along it, at most the spacing apart and
+/// keeping its first and last points
+pub fn resample_path(path: &[(f32, f32)], spacing: f32) -> Vec<(f32, f32)> {
+    let length = metrics::get_length_m(path, 1.0);
+    if path.len() < 2 || length == 0.0 || spacing <= 0.0 {
+        return path.to_vec();
+    }
+
+    let num_segments = (length / spacing).ceil() as usize;
+    let spacing = length / num_segments as f32;
+    let mut points = vec![path[0]];
+    let mut segment_idx = 0;
+    let mut segment_start = 0.0;
+
+    for point_idx in 1..num_segments {
+        let target = point_idx as f32 * spacing;
+
+        // Move on to the segment the target distance falls in
+        loop {
+            let (from, to) = (path[segment_idx], path[segment_idx + 1]);
+            let segment_length = (to.0 - from.0).hypot(to.1 - from.1);
+
+            if target <= segment_start + segment_length || segment_idx + 2 == path.len() {
+                let ratio = if segment_length > 0.0 { (target - segment_start) / segment_length } else { 0.0 };
+                points.push((from.0 + ratio * (to.0 - from.0), from.1 + ratio * (to.1 - from.1)));
+                break;
+            }
+
+            segment_start += segment_length;
+            segment_idx += 1;
+        }
+    }
+
+    points.push(path[path.len() - 1]);
+    points
+}
+
+/// Curvature of the circle through three points, zero if they are on a line
+fn get_curvature(prev: (f32, f32), cur: (f32, f32), next: (f32, f32)) -> f32 {
+    let cross = (cur.0 - prev.0) * (next.1 - cur.1) - (cur.1 - prev.1) * (next.0 - cur.0);
+    let sides = (cur.0 - prev.0).hypot(cur.1 - prev.1)
+        * (next.0 - cur.0).hypot(next.1 - cur.1)
+        * (next.0 - prev.0).hypot(next.1 - prev.1);
+
+    if sides > 0.0 {
+        2.0 * cross.abs() / sides
+    } else {
+        0.0
+    }
+}
+
+/// Bilinearly interpolate the distance to the nearest obstacle at a point, capped at a
+/// maximum so that maps without obstacles give finite distances
+fn get_distance(gridmap: &Gridmap, distance_field: &[f32], point: (f32, f32), max_distance: f32) -> f32 {
+    let max_x = gridmap.get_width().saturating_sub(1) as f32;
+    let max_y = gridmap.get_height().saturating_sub(1) as f32;
+    let (x, y) = (point.0.clamp(0.0, max_x), point.1.clamp(0.0, max_y));
+    let (x_0, y_0) = (x.floor(), y.floor());
+    let (frac_x, frac_y) = (x - x_0, y - y_0);
+
+    let get_cell_distance = |dx: f32, dy: f32| {
+        let cell = ((x_0 + dx).min(max_x) as u32, (y_0 + dy).min(max_y) as u32);
+        distance_field[gridmap.xy_to_idx(cell)].min(max_distance)
+    };
+
+    (1.0 - frac_x) * (1.0 - frac_y) * get_cell_distance(0.0, 0.0)
+        + frac_x * (1.0 - frac_y) * get_cell_distance(1.0, 0.0)
+        + (1.0 - frac_x) * frac_y * get_cell_distance(0.0, 1.0)
+        + frac_x * frac_y * get_cell_distance(1.0, 1.0)
+}
+
+/// Gradient of the interpolated distance by central differences, pointing away from obstacles
+fn get_distance_gradient(gridmap: &Gridmap, distance_field: &[f32], point: (f32, f32), max_distance: f32) -> (f32, f32) {
+    const STEP: f32 = 0.5;
+    let get = |dx: f32, dy: f32| get_distance(gridmap, distance_field, (point.0 + dx, point.1 + dy), max_distance);
+
+    ((get(STEP, 0.0) - get(-STEP, 0.0)) / (2.0 * STEP), (get(0.0, STEP) - get(0.0, -STEP)) / (2.0 * STEP))
+}
+
+/// Whether the cell a point lies in is within the map and traversable
+fn is_traversable(gridmap: &Gridmap, point: (f32, f32)) -> bool {
+    let (x, y) = (point.0.round(), point.1.round());
+
+    x >= 0.0 && y >= 0.0 && gridmap.xy_in_map((x as u32, y as u32)) && gridmap.xy_is_traversable((x as u32, y as u32))
+}
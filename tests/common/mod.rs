@@ -1,23 +1,27 @@
+#![allow(dead_code)]
+
 const O: u8 = 0;// freespace
 const X: u8 = 255;// occupied
 
 pub fn setup() {
-  // some setup code, like creating required files/directories, starting
-  // servers, etc.
+  // Plots are written to directories that are not tracked
+  for dir in ["fmm", "hpa_star", "coverage", "comparison", "heatmaps"] {
+    std::fs::create_dir_all(format!("test_maps/planners/{}", dir)).unwrap();
+  }
 }
 
 pub fn create_snake_arr_map() -> Vec<Vec<u8>> {
-  return vec![
+  vec![
     vec![O, O, O, X, O, O, O],
     vec![O, X, O, X, O, X, O],
     vec![O, X, O, X, O, X, O],
     vec![O, X, O, X, O, X, O],
     vec![O, X, O, O, O, X, O],
-  ];
+  ]
 }
 
 pub fn create_maze_0_arr_map() -> Vec<Vec<u8>> {
-  return vec![
+  vec![
     vec![O, O, O, X, O, X, O, O, X, O],
     vec![O, X, O, O, O, O, O, O, X, O],
     vec![O, X, X, X, X, X, X, O, X, O],
@@ -28,11 +32,11 @@ pub fn create_maze_0_arr_map() -> Vec<Vec<u8>> {
     vec![O, O, X, O, X, O, X, O, O, O],
     vec![X, X, X, O, X, O, X, O, O, O],
     vec![O, O, O, O, X, O, O, O, O, O],
-  ];
+  ]
 }
 
 pub fn create_maze_1_arr_map() -> Vec<Vec<u8>> {
-  return vec![
+  vec![
     vec![O, O, O, X, O, X, O, O, X, O],
     vec![O, X, O, O, O, O, O, O, X, O],
     vec![O, X, X, X, X, X, X, O, X, O],
@@ -43,11 +47,11 @@ pub fn create_maze_1_arr_map() -> Vec<Vec<u8>> {
     vec![O, O, X, O, X, O, X, O, O, O],
     vec![X, X, X, O, X, O, X, O, O, O],
     vec![O, O, O, O, X, O, O, O, O, O],
-  ];
+  ]
 }
 
 pub fn create_maze_2_arr_map() -> Vec<Vec<u8>> {
-  return vec![
+  vec![
     vec![O, O, O, O, O, O, O, O, O, O],
     vec![O, O, O, O, O, O, O, O, O, O],
     vec![O, O, O, O, O, O, O, O, O, O],
@@ -58,5 +62,5 @@ pub fn create_maze_2_arr_map() -> Vec<Vec<u8>> {
     vec![O, O, O, O, O, O, O, O, O, O],
     vec![O, O, O, O, O, O, O, O, O, O],
     vec![O, O, O, O, O, O, O, O, O, O],
-  ];
+  ]
 }
\ No newline at end of file
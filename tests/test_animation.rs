@@ -0,0 +1,67 @@
+mod common;
+
+use std::fs::File;
+
+use image::codecs::gif::GifDecoder;
+use image::AnimationDecoder;
+use ruth_planner::maps::builder;
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::TraceLevel;
+
+fn count_frames(file_path: &str) -> usize {
+    let decoder = GifDecoder::new(File::open(file_path).unwrap()).unwrap();
+    decoder.into_frames().count()
+}
+
+#[test]
+fn test_animation_plan1() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+    let file_path = "test_maps/planners/a_star/test_plan1_animation.gif";
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    planner.update_trace_level(TraceLevel::OpenLists);
+
+    let motion_plan = planner.generate_plan();
+    let trace = motion_plan.trace.unwrap();
+
+    let options = builder::AnimationOptions {
+        cells_per_frame: 4,
+        final_frames: 5,
+        ..Default::default()
+    };
+    builder::animate_plan(&gridmap, &start_cell, &goal_cell, &trace, &motion_plan.path, file_path, &options);
+
+    // A frame per group of expansions, then the path
+    assert_eq!(count_frames(file_path), trace.expansions.len().div_ceil(4) + 5);
+}
+
+#[test]
+fn test_animation_plan_ros2() {
+    let gridmap =
+        builder::build_gridmap_from_pgm("test_maps/nav2_maps/ostc_map.pgm").unwrap();
+    let (start_cell, goal_cell) = ((5, 5), (60, 35));
+    let file_path = "test_maps/planners/a_star/test_plan_ros2_animation.gif";
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    planner.update_trace_level(TraceLevel::Expansions);
+
+    let motion_plan = planner.generate_plan();
+    let trace = motion_plan.trace.unwrap();
+
+    // Around 10 frames of search, as each frame of a large map takes a while to draw
+    let cells_per_frame = trace.expansions.len().div_ceil(10);
+    let options = builder::AnimationOptions {
+        cells_per_frame,
+        cell_size: 2,
+        final_frames: 10,
+        ..Default::default()
+    };
+    builder::animate_plan(&gridmap, &start_cell, &goal_cell, &trace, &motion_plan.path, file_path, &options);
+
+    assert_eq!(count_frames(file_path), trace.expansions.len().div_ceil(cells_per_frame) + 10);
+}
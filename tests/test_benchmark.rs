@@ -0,0 +1,132 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ruth_planner::benchmark::*;
+use ruth_planner::dijkstra::DijkstraPlanner;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::PlanningBudget;
+use ruth_planner::Gridmap;
+
+/// Open map split by a wall with a gap at the bottom
+fn create_wall_map() -> Gridmap {
+    let mut gridmap = Gridmap::new(40, 30, 0.05);
+    for y in 0..25 {
+        gridmap.set_val_xy(254, (20, y));
+    }
+    gridmap
+}
+
+fn create_config() -> BenchmarkConfig {
+    BenchmarkConfig {
+        num_pairs: 6,
+        side_buffer: 2,
+        min_distance_m: 0.5,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_benchmark_random_pairs() {
+    let gridmap = create_wall_map();
+    let config = create_config();
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    for _ in 0..100 {
+        let start = get_random_start(&gridmap, &config, &mut rng).unwrap();
+        let goal = get_random_goal(&gridmap, start, &config, &mut rng).unwrap();
+
+        for cell in [start, goal] {
+            assert!(gridmap.xy_is_traversable(cell));
+            assert!((2..38).contains(&cell.0) && (2..28).contains(&cell.1));
+        }
+        let distance = (start.0 as f32 - goal.0 as f32).hypot(start.1 as f32 - goal.1 as f32);
+        assert!(distance > 10.0);
+    }
+
+    // No cell is left once the buffer covers the map
+    let config = BenchmarkConfig {
+        side_buffer: 15,
+        ..config
+    };
+    assert_eq!(get_random_start(&gridmap, &config, &mut rng), None);
+}
+
+#[test]
+fn test_benchmark_run() {
+    let gridmap = create_wall_map();
+    let config = create_config();
+
+    let mut planners = get_default_planners(&gridmap, &PlanningBudget::default());
+    let mut planner = DijkstraPlanner::new(&gridmap);
+    planner.update_goal_tolerance(3.0);
+    planners.push(BenchmarkPlanner::from_planner("Dijkstra with tolerance", planner));
+    let results = run_benchmark(&gridmap, &mut planners, &config);
+
+    assert_eq!(results.len(), 6 * 8);
+    assert!(results.iter().all(|result| result.success && result.path_length_m > 0.0));
+
+    // Optimal planners agree on the length of every path
+    for pair in results.chunks(8) {
+        assert!(pair.iter().all(|result| (result.start, result.goal) == (pair[0].start, pair[0].goal)));
+        assert_eq!(pair[0].path_length_m, pair[1].path_length_m);
+        assert!(pair[7].path_length_m < pair[0].path_length_m);
+    }
+
+    // The same seed draws the same pairs
+    let mut planners = get_default_planners(&gridmap, &PlanningBudget::default());
+    let rerun = run_benchmark(&gridmap, &mut planners, &config);
+    assert_eq!(rerun[0].start, results[0].start);
+    assert_eq!(rerun.last().unwrap().goal, results.last().unwrap().goal);
+
+    let summaries = summarize(&results);
+    let names: Vec<&str> = summaries.iter().map(|summary| summary.planner.as_str()).collect();
+    assert_eq!(
+        names,
+        ["Dijkstra", "A*", "FMM", "HPA*", "BFS", "DFS", "Space-Time A*", "Dijkstra with tolerance"]
+    );
+    assert!(summaries.iter().all(|summary| summary.success_rate == 1.0));
+
+    // A header and separator, then a row per planner
+    let table = get_summary_table(&summaries);
+    assert_eq!(table.lines().count(), 2 + 8);
+    let row = table.lines().nth(2).unwrap();
+    assert!(row.starts_with("Dijkstra ") && row.contains(" 1.00 "));
+
+    assert_eq!(get_results_csv(&results).lines().count(), 1 + results.len());
+    assert_eq!(get_results_json(&results).matches("\"planner\"").count(), results.len());
+
+    // Planner names are escaped within the JSON strings
+    let mut result = results[0].clone();
+    result.planner = "A* \"weighted\" C:\\planners".to_string();
+    let json = get_results_json(&[result]);
+    assert!(json.contains("\"planner\": \"A* \\\"weighted\\\" C:\\\\planners\","));
+}
+
+#[test]
+fn test_benchmark_failures() {
+    // Closing the gap leaves pairs either side of the wall unreachable
+    let mut gridmap = create_wall_map();
+    for y in 25..30 {
+        gridmap.set_val_xy(254, (20, y));
+    }
+
+    let config = create_config();
+    let mut planners = get_default_planners(&gridmap, &PlanningBudget::default());
+    let results = run_benchmark(&gridmap, &mut planners, &config);
+
+    assert_eq!(results.len(), 6 * 7);
+    assert!(results
+        .iter()
+        .all(|result| (result.start.0 < 20) == (result.goal.0 < 20)));
+
+    // Running out of expansions counts as a failure
+    let budget = PlanningBudget {
+        max_expansions: Some(5),
+        ..Default::default()
+    };
+    let mut planners = vec![
+        BenchmarkPlanner::new::<DijkstraPlanner>("Dijkstra", &gridmap, &PlanningBudget::default()),
+        BenchmarkPlanner::new::<DijkstraPlanner>("Limited Dijkstra", &gridmap, &budget),
+    ];
+    let summaries = summarize(&run_benchmark(&gridmap, &mut planners, &config));
+    assert_eq!(summaries[1].success_rate, 0.0);
+}
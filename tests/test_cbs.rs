@@ -0,0 +1,137 @@
+mod common;
+
+use ruth_planner::maps::builder;
+use ruth_planner::cbs::*;
+use ruth_planner::Gridmap;
+
+fn assert_valid_plan(mapf_plan: &MAPFPlan, starts: &[(u32, u32)], goals: &[(u32, u32)]) {
+    assert_eq!(mapf_plan.paths.len(), starts.len());
+    assert_eq!(find_first_conflict(&mapf_plan.paths), None);
+
+    for ((path, start), goal) in mapf_plan.paths.iter().zip(starts).zip(goals) {
+        assert_eq!(path.first().map(|(pos, _)| *pos), Some(*goal));
+        assert_eq!(path.last(), Some(&(*start, 0)));
+
+        // One timestep per entry, moving to a 4 way neighbor or waiting
+        for pair in path.windows(2) {
+            let ((pos, time), (prev_pos, prev_time)) = (pair[0], pair[1]);
+            assert_eq!(time, prev_time + 1);
+            assert!(pos.0.abs_diff(prev_pos.0) + pos.1.abs_diff(prev_pos.1) <= 1);
+        }
+    }
+}
+
+#[test]
+fn test_cbs_independent_agents() {
+    let gridmap = Gridmap::new(10, 10, 0.05);
+    let (starts, goals) = ([(0, 0), (0, 9)], [(4, 0), (0, 5)]);
+
+    let mut planner = CBSPlanner::new(&gridmap);
+    assert!(planner.update_agents(&starts, &goals));
+
+    let mapf_plan = planner.generate_plan();
+
+    assert_valid_plan(&mapf_plan, &starts, &goals);
+    assert_eq!(mapf_plan.cost, 8);
+}
+
+#[test]
+fn test_cbs_vertex_conflict() {
+    let gridmap = Gridmap::new(3, 3, 0.05);
+    let (starts, goals) = ([(0, 1), (1, 0)], [(2, 1), (1, 2)]);
+
+    let mut planner = CBSPlanner::new(&gridmap);
+    planner.update_agents(&starts, &goals);
+
+    let mapf_plan = planner.generate_plan();
+
+    // Both shortest paths meet in the centre, so one agent takes a step longer
+    assert_valid_plan(&mapf_plan, &starts, &goals);
+    assert_eq!(mapf_plan.cost, 5);
+}
+
+#[test]
+fn test_cbs_corridor_swap() {
+    let arr_map: Vec<Vec<u8>> = vec![
+        vec![255, 255, 0, 255, 255],
+        vec![0, 0, 0, 0, 0],
+    ];
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (starts, goals) = ([(0, 1), (4, 1)], [(4, 1), (0, 1)]);
+
+    let mut planner = CBSPlanner::new(&gridmap);
+    planner.update_agents(&starts, &goals);
+
+    let mapf_plan = planner.generate_plan();
+
+    // One agent steps into the side pocket to let the other pass
+    assert_valid_plan(&mapf_plan, &starts, &goals);
+    assert!(mapf_plan.paths.iter().any(|path| path.iter().any(|(pos, _)| *pos == (2, 0))));
+    assert_eq!(mapf_plan.cost, 11);
+}
+
+#[test]
+fn test_cbs_maze() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_2_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let starts = [(0, 9), (9, 0), (0, 0), (9, 9)];
+    let goals = [(9, 0), (0, 9), (9, 9), (0, 0)];
+
+    let mut planner = CBSPlanner::new(&gridmap);
+    planner.update_agents(&starts, &goals);
+
+    let mapf_plan = planner.generate_plan();
+
+    assert_valid_plan(&mapf_plan, &starts, &goals);
+}
+
+#[test]
+fn test_cbs_unreachable() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_1_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    let mut planner = CBSPlanner::new(&gridmap);
+    planner.update_agents(&[(0, 9), (0, 0)], &[(9, 0), (2, 0)]);
+
+    let mapf_plan = planner.generate_plan();
+
+    assert_eq!(mapf_plan.paths.len(), 0);
+
+    // Shared goals, obstacles and mismatched lengths are rejected
+    assert!(!planner.update_agents(&[(0, 0), (1, 0)], &[(2, 0), (2, 0)]));
+    assert!(!planner.update_agents(&[(3, 0)], &[(2, 0)]));
+    assert!(!planner.update_agents(&[(0, 0)], &[]));
+}
+
+#[test]
+fn test_cbs_find_conflicts() {
+    // Timed paths run from goal to start
+    let swap = vec![
+        vec![((1, 0), 1), ((0, 0), 0)],
+        vec![((0, 0), 1), ((1, 0), 0)],
+    ];
+    assert_eq!(
+        find_first_conflict(&swap),
+        Some(Conflict::Edge {
+            agents: (0, 1),
+            from: (0, 0),
+            to: (1, 0),
+            time: 1
+        })
+    );
+
+    // The first agent stays at its goal after arriving
+    let arrived = vec![
+        vec![((1, 0), 1), ((0, 0), 0)],
+        vec![((1, 0), 3), ((1, 1), 2), ((2, 1), 1), ((2, 0), 0)],
+    ];
+    assert_eq!(
+        find_first_conflict(&arrived),
+        Some(Conflict::Vertex {
+            agents: (0, 1),
+            pos: (1, 0),
+            time: 3
+        })
+    );
+    assert_eq!(get_position_at(&arrived[0], 5), (1, 0));
+}
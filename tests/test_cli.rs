@@ -0,0 +1,99 @@
+use std::fs;
+use std::process::{Command, Output};
+
+fn run_cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ruth_planner")).args(args).output().unwrap()
+}
+
+#[test]
+fn test_cli_csv() {
+    let file_path = "test_maps/planners/cli/test_plan_ros2.csv";
+    fs::create_dir_all("test_maps/planners/cli").unwrap();
+
+    let output = run_cli(&[
+        "--map", "test_maps/nav2_maps/ostc_map.pgm",
+        "--start", "5,5",
+        "--goal", "60,35",
+        "--planner", "dijkstra",
+        "--output", file_path,
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("nodes_expanded: "));
+    assert!(stdout.contains("reopenings: 0"));
+
+    // The path runs from start to goal
+    let csv = fs::read_to_string(file_path).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows[0], "x,y,x_m,y_m");
+    assert!(rows[1].starts_with("5,5,"));
+    assert!(rows.last().unwrap().starts_with("60,35,"));
+}
+
+#[test]
+fn test_cli_yaml_meters() {
+    let file_path = "test_maps/planners/cli/test_plan_turtlebot3.json";
+    let plot_path = "test_maps/planners/cli/test_plan_turtlebot3.png";
+    fs::create_dir_all("test_maps/planners/cli").unwrap();
+
+    // The map's origin is at (-10, -10), with 384 cells of 5 cm a side
+    let output = run_cli(&[
+        "--map", "test_maps/nav2_maps/turtlebot3_world.yaml",
+        "--start", "-2.0,-0.5",
+        "--goal", "2.0,0.5",
+        "--meters",
+        "--goal-tolerance", "0.1",
+        "--planner", "hpa_star",
+        "--cluster-size", "16",
+        "--output", file_path,
+        "--plot", plot_path,
+    ]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let json = fs::read_to_string(file_path).unwrap();
+    assert!(json.contains("\"path\": [{\"x\": 160, \"y\": 193, \"x_m\": -1.97"));
+    assert!(json.contains("\"stats\": {\"nodes_expanded\": "));
+    assert!(json.contains("\"reopenings\": 0, "));
+    assert!(fs::metadata(plot_path).is_ok());
+}
+
+#[test]
+fn test_cli_errors() {
+    // Missing arguments and unknown planners are errors
+    let output = run_cli(&["--map", "test_maps/nav2_maps/ostc_map.pgm", "--start", "5,5"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = run_cli(&[
+        "--map", "test_maps/nav2_maps/ostc_map.pgm",
+        "--start", "5,5",
+        "--goal", "60,35",
+        "--planner", "rrt",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("unknown planner 'rrt'"));
+
+    // Files that are not images are reported rather than panicking
+    let output = run_cli(&["--map", "README.md", "--start", "5,5", "--goal", "60,35"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("could not load map 'README.md'"));
+
+    let output = run_cli(&[
+        "--map", "test_maps/nav2_maps/ostc_map.pgm",
+        "--start", "5,5",
+        "--goal", "60,35",
+        "--plot", "test_maps/planners/missing/test_plan.png",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("could not write"));
+
+    // Running out of expansions finds no path
+    let output = run_cli(&[
+        "--map", "test_maps/nav2_maps/ostc_map.pgm",
+        "--start", "5,5",
+        "--goal", "60,35",
+        "--max-expansions", "10",
+        "--render",
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stdout).unwrap().contains("nodes_expanded: 10"));
+}
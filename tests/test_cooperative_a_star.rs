@@ -0,0 +1,93 @@
+mod common;
+
+use ruth_planner::maps::builder;
+use ruth_planner::cbs::*;
+use ruth_planner::cooperative_a_star::*;
+use ruth_planner::Gridmap;
+
+fn assert_valid_plan(mapf_plan: &MAPFPlan, starts: &[(u32, u32)], goals: &[(u32, u32)]) {
+    assert_eq!(mapf_plan.paths.len(), starts.len());
+    assert_eq!(find_first_conflict(&mapf_plan.paths), None);
+
+    for ((path, start), goal) in mapf_plan.paths.iter().zip(starts).zip(goals) {
+        assert_eq!(path.first().map(|(pos, _)| *pos), Some(*goal));
+        assert_eq!(path.last(), Some(&(*start, 0)));
+
+        // One timestep per entry, moving to a 4 way neighbor or waiting
+        for pair in path.windows(2) {
+            let ((pos, time), (prev_pos, prev_time)) = (pair[0], pair[1]);
+            assert_eq!(time, prev_time + 1);
+            assert!(pos.0.abs_diff(prev_pos.0) + pos.1.abs_diff(prev_pos.1) <= 1);
+        }
+    }
+}
+
+#[test]
+fn test_cooperative_a_star_vertex_conflict() {
+    let gridmap = Gridmap::new(3, 3, 0.05);
+    let (starts, goals) = ([(0, 1), (1, 0)], [(2, 1), (1, 2)]);
+
+    let mut planner = CooperativeAStarPlanner::new(&gridmap);
+    assert!(planner.update_agents(&starts, &goals));
+
+    let mapf_plan = planner.generate_plan();
+
+    // The first agent keeps its shortest path and the second one gives way
+    assert_valid_plan(&mapf_plan, &starts, &goals);
+    assert_eq!(mapf_plan.paths[0][0].1, 2);
+    assert_eq!(mapf_plan.cost, 5);
+}
+
+#[test]
+fn test_cooperative_a_star_maze() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_2_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let starts = [(0, 9), (9, 0), (0, 0), (9, 9)];
+    let goals = [(9, 0), (0, 9), (9, 9), (0, 0)];
+
+    let mut planner = CooperativeAStarPlanner::new(&gridmap);
+    planner.update_agents(&starts, &goals);
+
+    let mapf_plan = planner.generate_plan();
+
+    assert_valid_plan(&mapf_plan, &starts, &goals);
+}
+
+#[test]
+fn test_cooperative_a_star_windowed() {
+    let gridmap = Gridmap::new(20, 20, 0.05);
+    let starts: Vec<(u32, u32)> = (0..8).map(|idx| (0, idx * 2)).collect();
+    let goals: Vec<(u32, u32)> = (0..8).map(|idx| (19, 19 - idx * 2)).collect();
+
+    let mut planner = CooperativeAStarPlanner::new(&gridmap);
+    planner.update_agents(&starts, &goals);
+    assert!(planner.update_window(8));
+
+    let mapf_plan = planner.generate_plan();
+
+    assert_valid_plan(&mapf_plan, &starts, &goals);
+
+    assert!(!planner.update_window(1));
+}
+
+#[test]
+fn test_cooperative_a_star_incomplete() {
+    let arr_map: Vec<Vec<u8>> = vec![
+        vec![255, 255, 0, 255, 255],
+        vec![0, 0, 0, 0, 0],
+    ];
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (starts, goals) = ([(0, 1), (4, 1)], [(4, 1), (0, 1)]);
+
+    let mut planner = CooperativeAStarPlanner::new(&gridmap);
+    planner.update_agents(&starts, &goals);
+
+    // The first agent rushes down the corridor before the second can reach the
+    // side pocket, which Conflict-Based Search avoids
+    assert_eq!(planner.generate_plan().paths.len(), 0);
+
+    let mut cbs_planner = CBSPlanner::new(&gridmap);
+    cbs_planner.update_agents(&starts, &goals);
+
+    assert_valid_plan(&cbs_planner.generate_plan(), &starts, &goals);
+}
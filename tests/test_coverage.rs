@@ -0,0 +1,180 @@
+mod common;
+
+use ruth_planner::maps::builder;
+use ruth_planner::coverage::*;
+use ruth_planner::Gridmap;
+
+#[test]
+fn test_coverage_empty_map() {
+    let gridmap = Gridmap::new(10, 6, 0.05);
+
+    let mut planner = CoveragePlanner::new(&gridmap);
+    planner.update_start((0, 0));
+
+    let coverage_plan = planner.generate_plan();
+
+    assert_eq!(planner.get_decomposition().len(), 1);
+    assert_eq!(coverage_plan.coverage, 100.0);
+    assert_eq!(coverage_plan.motion_plan.path.last(), Some(&(0, 0)));
+    // Every cell is visited exactly once by the back and forth sweeps
+    assert_eq!(coverage_plan.motion_plan.path.len(), 60);
+}
+
+#[test]
+fn test_coverage_decomposition() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_2_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    let mut planner = CoveragePlanner::new(&gridmap);
+
+    // The wall splits the sweep lines that cross it, and its end on either side
+    // changes the connectivity, creating cells before, above, below and after it
+    let decomposition = planner.get_decomposition();
+    assert_eq!(decomposition.len(), 4);
+
+    let num_segment_cells: u32 = decomposition
+        .iter()
+        .flat_map(|cell| cell.segments.iter())
+        .map(|(a, b)| b.1 - a.1 + 1)
+        .sum();
+    assert_eq!(num_segment_cells, 100 - 7);
+
+    planner.update_sweep_direction(SweepDirection::Horizontal);
+    for cell in planner.get_decomposition() {
+        for (a, b) in cell.segments {
+            assert_eq!(a.1, b.1);
+        }
+    }
+}
+
+#[test]
+fn test_coverage_boustrophedon_maze() {
+    common::setup();
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let start_cell = (0, 9);
+
+    let mut planner = CoveragePlanner::new(&gridmap);
+    planner.update_start(start_cell);
+
+    let coverage_plan = planner.generate_plan();
+
+    builder::plot_gridmap(
+        &gridmap,
+        &start_cell,
+        coverage_plan.motion_plan.path.first().unwrap(),
+        &coverage_plan.motion_plan,
+        "test_maps/planners/coverage/test_plan_boustrophedon.png",
+        &30,
+    );
+
+    assert_eq!(coverage_plan.coverage, 100.0);
+    for pair in coverage_plan.motion_plan.path.windows(2) {
+        assert!(pair[0].0.abs_diff(pair[1].0) <= 1 && pair[0].1.abs_diff(pair[1].1) <= 1);
+        assert!(gridmap.xy_is_traversable(pair[0]));
+    }
+}
+
+#[test]
+fn test_coverage_unreachable_space() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_1_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    let mut planner = CoveragePlanner::new(&gridmap);
+    planner.update_start((0, 9));
+
+    for pattern in [CoveragePattern::Boustrophedon, CoveragePattern::Spiral] {
+        planner.update_pattern(pattern);
+        let coverage_plan = planner.generate_plan();
+
+        // The walled off top right corner is left out
+        assert_eq!(coverage_plan.coverage, 100.0);
+        assert!(!coverage_plan.motion_plan.path.contains(&(9, 0)));
+        assert!(coverage_plan.unreachable_waypoints.is_empty());
+
+        // Each move of the path is to a neighboring cell
+        for pair in coverage_plan.motion_plan.path.windows(2) {
+            assert!(pair[0].0.abs_diff(pair[1].0) <= 1 && pair[0].1.abs_diff(pair[1].1) <= 1);
+        }
+    }
+}
+
+#[test]
+fn test_coverage_blocked_start() {
+    let mut gridmap = Gridmap::new(10, 6, 0.05);
+    gridmap.set_val_xy(254, (0, 0));
+
+    let mut planner = CoveragePlanner::new(&gridmap);
+    planner.update_start((0, 0));
+
+    // Nothing is reachable from a start on an obstacle, so nothing is covered
+    let coverage_plan = planner.generate_plan();
+    assert_eq!(coverage_plan.coverage, 0.0);
+    assert_eq!(planner.get_coverage(&[(0, 0), (1, 0)]), 0.0);
+}
+
+#[test]
+fn test_coverage_tool_width() {
+    let gridmap = Gridmap::new(20, 20, 0.05);
+
+    let mut planner = CoveragePlanner::new(&gridmap);
+    planner.update_start((0, 0));
+    assert!(!planner.update_tool_width(0));
+
+    let narrow_plan = planner.generate_plan();
+
+    assert!(planner.update_tool_width(3));
+    let wide_plan = planner.generate_plan();
+
+    assert_eq!(wide_plan.coverage, 100.0);
+    assert!(wide_plan.motion_plan.path.len() * 2 < narrow_plan.motion_plan.path.len());
+}
+
+#[test]
+fn test_coverage_spiral() {
+    let gridmap = Gridmap::new(8, 8, 0.05);
+
+    let mut planner = CoveragePlanner::new(&gridmap);
+    planner.update_start((0, 0));
+    planner.update_pattern(CoveragePattern::Spiral);
+
+    let coverage_plan = planner.generate_plan();
+
+    // A spiral over an empty square never needs to revisit a cell
+    assert_eq!(coverage_plan.coverage, 100.0);
+    assert_eq!(coverage_plan.motion_plan.path.len(), 64);
+    // and ends near the middle
+    let end = coverage_plan.motion_plan.path[0];
+    assert!((3..=4).contains(&end.0) && (3..=4).contains(&end.1));
+}
+
+#[test]
+fn test_coverage_plan_ros1() {
+    common::setup();
+    let gridmap =
+        builder::build_gridmap_from_pgm("test_maps/nav2_maps/turtlebot3_world.pgm").unwrap();
+    let start_cell = (165, 220);
+
+    let mut planner = CoveragePlanner::new(&gridmap);
+    planner.update_start(start_cell);
+    planner.update_tool_width(4);
+
+    for (pattern, file_path) in [
+        (CoveragePattern::Boustrophedon, "test_maps/planners/coverage/test_plan_ros1_boustrophedon.png"),
+        (CoveragePattern::Spiral, "test_maps/planners/coverage/test_plan_ros1_spiral.png"),
+    ] {
+        planner.update_pattern(pattern);
+        let coverage_plan = planner.generate_plan();
+
+        builder::plot_gridmap(
+            &gridmap,
+            &start_cell,
+            coverage_plan.motion_plan.path.first().unwrap(),
+            &coverage_plan.motion_plan,
+            file_path,
+            &2,
+        );
+
+        assert!(coverage_plan.coverage > 80.0);
+    }
+}
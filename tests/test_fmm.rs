@@ -0,0 +1,189 @@
+mod common;
+
+use ruth_planner::maps::builder;
+use ruth_planner::fmm::FMMPlanner;
+use ruth_planner::planners::planner_base::Planner;
+
+#[test]
+fn test_fmm_plan0() {
+    let arr_map: Vec<Vec<u8>> = common::create_snake_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 4), (6, 4));
+
+    let mut planner = FMMPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    assert_eq!(motion_plan.path.first(), Some(&goal_cell));
+    assert_eq!(motion_plan.path.last(), Some(&start_cell));
+    for cell in &motion_plan.path {
+        assert!(gridmap.xy_is_traversable(*cell));
+    }
+}
+
+#[test]
+fn test_fmm_plan1() {
+    common::setup();
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = FMMPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    builder::plot_gridmap(
+        &gridmap,
+        &start_cell,
+        &goal_cell,
+        &motion_plan,
+        "test_maps/planners/fmm/test_plan1.png",
+        &30,
+    );
+
+    assert_ne!(motion_plan.path.len(), 0);
+}
+
+#[test]
+fn test_fmm_plan2() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_1_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = FMMPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    assert_eq!(motion_plan.path.len(), 0);
+    assert_eq!(planner.generate_smooth_path().len(), 0);
+}
+
+#[test]
+fn test_fmm_plan3() {
+    common::setup();
+    let arr_map: Vec<Vec<u8>> = common::create_maze_2_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = FMMPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    builder::plot_gridmap(
+        &gridmap,
+        &start_cell,
+        &goal_cell,
+        &motion_plan,
+        "test_maps/planners/fmm/test_plan3.png",
+        &30,
+    );
+
+    assert_ne!(motion_plan.path.len(), 0);
+}
+
+#[test]
+fn test_fmm_smooth_path() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_2_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 7));
+
+    let mut planner = FMMPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let smooth_path = planner.generate_smooth_path();
+
+    assert_eq!(smooth_path.first(), Some(&(9.0, 7.0)));
+    assert_eq!(smooth_path.last(), Some(&(0.0, 9.0)));
+
+    // Consecutive points never jump by more than a diagonal cell
+    for pair in smooth_path.windows(2) {
+        let dist = (pair[0].0 - pair[1].0).hypot(pair[0].1 - pair[1].1);
+        assert!(dist <= 2.0_f32.sqrt() + 1e-3);
+    }
+
+    // Shorter than the 7 straight and 2 diagonal moves an 8 connected path needs
+    let length: f32 = smooth_path
+        .windows(2)
+        .map(|pair| (pair[0].0 - pair[1].0).hypot(pair[0].1 - pair[1].1))
+        .sum();
+    assert!(length < 7.0 + 2.0 * 2.0_f32.sqrt());
+}
+
+#[test]
+fn test_fmm_cost_factor() {
+    let mut gridmap = builder::build_gridmap_from_2d_arr(&common::create_maze_2_arr_map()).unwrap();
+    let (start_cell, goal_cell) = ((0, 0), (9, 0));
+
+    // Expensive band across the top rows makes the front prefer going around it
+    for x in 1..9 {
+        for y in 0..2 {
+            gridmap.set_val_xy(200, (x, y));
+        }
+    }
+
+    let mut planner = FMMPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    assert!(!planner.update_cost_factor(-1.0));
+
+    assert!(planner.update_cost_factor(0.0));
+    let direct_path = planner.generate_plan().path;
+    assert!(direct_path.iter().all(|cell| cell.1 == 0));
+
+    assert!(planner.update_cost_factor(20.0));
+    let detour_path = planner.generate_plan().path;
+    assert!(detour_path.iter().any(|cell| cell.1 >= 2));
+}
+
+#[test]
+fn test_fmm_plan_ros1() {
+    common::setup();
+    let gridmap =
+        builder::build_gridmap_from_pgm("test_maps/nav2_maps/turtlebot3_world.pgm").unwrap();
+
+    let (start_cell, goal_cell) = ((165, 220), (230, 155));
+
+    let mut planner = FMMPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    builder::plot_gridmap(
+        &gridmap,
+        &start_cell,
+        &goal_cell,
+        &motion_plan,
+        "test_maps/planners/fmm/test_plan_ros1.png",
+        &2,
+    );
+
+    assert_ne!(motion_plan.path.len(), 0);
+}
+
+#[test]
+fn test_fmm_plan_ros2() {
+    common::setup();
+    let gridmap =
+        builder::build_gridmap_from_pgm("test_maps/nav2_maps/ostc_map.pgm").unwrap();
+
+    let (start_cell, goal_cell) = ((5, 5), (60, 35));
+
+    let mut planner = FMMPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    builder::plot_gridmap(
+        &gridmap,
+        &start_cell,
+        &goal_cell,
+        &motion_plan,
+        "test_maps/planners/fmm/test_plan_ros2.png",
+        &5,
+    );
+
+    assert_ne!(motion_plan.path.len(), 0);
+}
@@ -0,0 +1,143 @@
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::maps::generators::*;
+use ruth_planner::maps::gridmap::INSCRIBED_INFLATED_OBSTACLE;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::Gridmap;
+
+/// Count the traversable cells reachable from a cell, moving in four directions
+fn count_reachable(gridmap: &Gridmap, start: (u32, u32)) -> usize {
+    let mut visited = vec![false; gridmap.get_cells().len()];
+    let mut stack = vec![start];
+    let mut count = 0;
+    visited[gridmap.xy_to_idx(start)] = true;
+
+    while let Some((x, y)) = stack.pop() {
+        count += 1;
+        let neighbors = [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)];
+        for nb in neighbors {
+            if gridmap.xy_in_map(nb) && gridmap.xy_is_traversable(nb) && !visited[gridmap.xy_to_idx(nb)] {
+                visited[gridmap.xy_to_idx(nb)] = true;
+                stack.push(nb);
+            }
+        }
+    }
+
+    count
+}
+
+fn count_traversable(gridmap: &Gridmap) -> usize {
+    (0..gridmap.get_cells().len())
+        .filter(|idx| gridmap.idx_is_traversable(*idx))
+        .count()
+}
+
+#[test]
+fn test_generators_maze() {
+    let algorithms = [
+        MazeAlgorithm::RecursiveBacktracker,
+        MazeAlgorithm::Prim,
+        MazeAlgorithm::Kruskal,
+    ];
+
+    for algorithm in algorithms {
+        let gridmap = generate_maze(41, 31, algorithm, 7);
+        assert_eq!((gridmap.get_width(), gridmap.get_height()), (41, 31));
+
+        // A spanning tree of 20 by 15 maze cells opens one wall fewer than it has cells
+        let cells = 20 * 15;
+        assert_eq!(count_traversable(&gridmap), 2 * cells - 1);
+        assert_eq!(count_reachable(&gridmap, (1, 1)), 2 * cells - 1);
+
+        let mut planner = AStarPlanner::new(&gridmap);
+        planner.update_start_and_goal((1, 1), (39, 29));
+        assert!(!planner.generate_plan().path.is_empty());
+
+        assert_eq!(generate_maze(41, 31, algorithm, 7).get_cells(), gridmap.get_cells());
+        assert_ne!(generate_maze(41, 31, algorithm, 8).get_cells(), gridmap.get_cells());
+    }
+
+    // An even size leaves a wall along the right and bottom
+    let gridmap = generate_maze(10, 8, MazeAlgorithm::Kruskal, 1);
+    assert!((0..8).all(|y| !gridmap.xy_is_traversable((9, y))));
+    assert!((0..10).all(|x| !gridmap.xy_is_traversable((x, 7))));
+}
+
+#[test]
+fn test_generators_forest() {
+    for shape in [ObstacleShape::Rectangle, ObstacleShape::Circle] {
+        let gridmap = generate_forest(60, 40, shape, 0.2, 3, 5);
+        let occupied = 60 * 40 - count_traversable(&gridmap);
+
+        // Stops placing obstacles once the density is reached, so overshoots by less than one
+        assert!((480..480 + 7 * 7).contains(&occupied));
+        assert_eq!(
+            generate_forest(60, 40, shape, 0.2, 3, 5).get_cells(),
+            gridmap.get_cells()
+        );
+    }
+
+    assert_eq!(
+        count_traversable(&generate_forest(20, 20, ObstacleShape::Circle, 0.0, 3, 5)),
+        400
+    );
+}
+
+#[test]
+fn test_generators_cave() {
+    let gridmap = generate_cave(60, 40, 0.45, 4, 3);
+    let free = count_traversable(&gridmap);
+    assert!(free > 0 && free < 60 * 40);
+
+    // Smoothing leaves no free cell surrounded by walls
+    for y in 1..39 {
+        for x in 1..59 {
+            if gridmap.xy_is_traversable((x, y)) {
+                let walls = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .iter()
+                    .filter(|nb| !gridmap.xy_is_traversable(**nb))
+                    .count();
+                assert!(walls < 4);
+            }
+        }
+    }
+
+    assert_eq!(generate_cave(60, 40, 0.45, 4, 3).get_cells(), gridmap.get_cells());
+    assert_eq!(count_traversable(&generate_cave(30, 20, 1.0, 4, 3)), 0);
+}
+
+#[test]
+fn test_generators_rooms() {
+    let gridmap = generate_rooms(60, 40, 6, (4, 8), 11);
+
+    // Every room is joined to the others and the map border stays closed
+    let free = count_traversable(&gridmap);
+    assert!(free >= 6 * 4 * 4);
+    let start = (0..60 * 40).find(|idx| gridmap.idx_is_traversable(*idx)).unwrap();
+    assert_eq!(count_reachable(&gridmap, gridmap.idx_to_xy(start as u32)), free);
+    assert!((0..60).all(|x| !gridmap.xy_is_traversable((x, 0)) && !gridmap.xy_is_traversable((x, 39))));
+
+    assert_eq!(generate_rooms(60, 40, 6, (4, 8), 11).get_cells(), gridmap.get_cells());
+    assert_eq!(get_room_center((2, 4, 5, 6)), (4, 7));
+}
+
+#[test]
+fn test_generators_noise_costs() {
+    let gridmap = generate_noise_costs(64, 48, 8.0, 2);
+
+    assert_eq!(count_traversable(&gridmap), 64 * 48);
+    let cells = gridmap.get_cells();
+    assert!(cells.iter().all(|cost| *cost < INSCRIBED_INFLATED_OBSTACLE));
+    assert!(cells.iter().any(|cost| *cost > 150) && cells.iter().any(|cost| *cost < 100));
+
+    // Noise is smooth, so neighbouring costs are close
+    for y in 0..48 {
+        for x in 1..64 {
+            let step = gridmap.get_val_xy((x, y)) as i32 - gridmap.get_val_xy((x - 1, y)) as i32;
+            assert!(step.abs() < 64);
+        }
+    }
+
+    // Noise is zero at lattice points
+    assert_eq!(gridmap.get_val_xy((0, 0)), (INSCRIBED_INFLATED_OBSTACLE - 1) / 2);
+    assert_ne!(generate_noise_costs(64, 48, 8.0, 3).get_cells(), cells);
+}
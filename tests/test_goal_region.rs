@@ -0,0 +1,144 @@
+mod common;
+
+use ruth_planner::maps::builder;
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::bfs::BFSPlanner;
+use ruth_planner::dijkstra::DijkstraPlanner;
+use ruth_planner::fmm::FMMPlanner;
+use ruth_planner::hpa_star::HPAStarPlanner;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::GoalRegion;
+use ruth_planner::Gridmap;
+
+/// Plan from the bottom left of maze 1 to a set of goals where only (0, 0) is reachable
+fn plan_to_goal_set<P: Planner>() {
+    let gridmap = builder::build_gridmap_from_2d_arr(&common::create_maze_1_arr_map()).unwrap();
+
+    let mut planner = P::new(&gridmap);
+    planner.update_start((0, 9));
+    assert!(!planner.update_goals(&[]));
+    assert!(planner.update_goals(&[(9, 0), (0, 0), (9, 9)]));
+
+    let motion_plan = planner.generate_plan();
+
+    assert_eq!(motion_plan.path.first(), Some(&(0, 0)));
+    assert_eq!(motion_plan.path.last(), Some(&(0, 9)));
+}
+
+/// Plan across an open map to a goal with a tolerance of 3 cells
+fn plan_with_tolerance<P: Planner>() {
+    let gridmap = Gridmap::new(10, 10, 0.05);
+
+    let mut planner = P::new(&gridmap);
+    planner.update_start_and_goal((0, 5), (9, 5));
+    assert!(!planner.update_goal_tolerance(-1.0));
+    assert!(planner.update_goal_tolerance(3.0));
+
+    let motion_plan = planner.generate_plan();
+    let reached = *motion_plan.path.first().unwrap();
+
+    assert!(reached.0 >= 6 && reached.0 < 9);
+    assert!(GoalRegion::new(&[(9, 5)], 3.0).contains(reached));
+}
+
+#[test]
+fn test_goal_set_bfs() {
+    plan_to_goal_set::<BFSPlanner>();
+}
+
+#[test]
+fn test_goal_set_dijkstra() {
+    plan_to_goal_set::<DijkstraPlanner>();
+}
+
+#[test]
+fn test_goal_set_a_star() {
+    plan_to_goal_set::<AStarPlanner>();
+}
+
+#[test]
+fn test_goal_set_fmm() {
+    plan_to_goal_set::<FMMPlanner>();
+}
+
+#[test]
+fn test_goal_set_hpa_star() {
+    plan_to_goal_set::<HPAStarPlanner>();
+}
+
+#[test]
+fn test_goal_tolerance_dijkstra() {
+    plan_with_tolerance::<DijkstraPlanner>();
+}
+
+#[test]
+fn test_goal_tolerance_a_star() {
+    plan_with_tolerance::<AStarPlanner>();
+}
+
+#[test]
+fn test_goal_tolerance_fmm() {
+    plan_with_tolerance::<FMMPlanner>();
+}
+
+#[test]
+fn test_goal_tolerance_hpa_star() {
+    plan_with_tolerance::<HPAStarPlanner>();
+}
+
+#[test]
+fn test_goal_set_picks_cheapest() {
+    let gridmap = builder::build_gridmap_from_2d_arr(&common::create_maze_2_arr_map()).unwrap();
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start((0, 9));
+    planner.update_goals(&[(9, 0), (5, 5), (9, 9)]);
+
+    let motion_plan = planner.generate_plan();
+
+    assert_eq!(motion_plan.path.first(), Some(&(5, 5)));
+}
+
+#[test]
+fn test_goal_tolerance_m() {
+    let gridmap = Gridmap::new(10, 10, 0.05);
+
+    let mut planner = DijkstraPlanner::new(&gridmap);
+    planner.update_start_and_goal((0, 5), (9, 5));
+    assert!(planner.update_goal_tolerance_m(0.1));
+
+    let motion_plan = planner.generate_plan();
+
+    assert_eq!(motion_plan.path.first(), Some(&(7, 5)));
+}
+
+#[test]
+fn test_route_plan() {
+    let gridmap = builder::build_gridmap_from_2d_arr(&common::create_maze_0_arr_map()).unwrap();
+    let waypoints = [(0, 9), (5, 9), (9, 9), (9, 0)];
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    let motion_plan = planner.generate_route_plan(&waypoints);
+
+    assert_eq!(motion_plan.path.first(), Some(&(9, 0)));
+    assert_eq!(motion_plan.path.last(), Some(&(0, 9)));
+    for waypoint in waypoints {
+        assert!(motion_plan.path.contains(&waypoint));
+    }
+
+    // Legs are joined without repeating the shared waypoint
+    for pair in motion_plan.path.windows(2) {
+        assert_ne!(pair[0], pair[1]);
+        assert!(pair[0].0.abs_diff(pair[1].0) <= 1 && pair[0].1.abs_diff(pair[1].1) <= 1);
+    }
+}
+
+#[test]
+fn test_route_plan_unreachable_leg() {
+    let gridmap = builder::build_gridmap_from_2d_arr(&common::create_maze_1_arr_map()).unwrap();
+
+    let mut planner = DijkstraPlanner::new(&gridmap);
+
+    assert_eq!(planner.generate_route_plan(&[(0, 9), (0, 0), (9, 0)]).path.len(), 0);
+    assert_eq!(planner.generate_route_plan(&[(0, 9)]).path, vec![(0, 9)]);
+}
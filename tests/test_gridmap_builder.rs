@@ -3,10 +3,11 @@ mod common;
 use ruth_planner::*;
 
 use maps::builder;
+use maps::gridmap::{FREE_SPACE, LETHAL_OBSTACLE, NO_INFORMATION};
 
 #[test]
 fn test_build_gridmap_from_pgm() {
-  let gridmap = builder::build_gridmap_from_pgm(
+  let _gridmap = builder::build_gridmap_from_pgm(
     "test_maps/test_maps/blank_map_with_obstacle.pgm").unwrap();
 
   //TODO: Test assert pixels in gridmap 
@@ -17,9 +18,29 @@ fn test_build_gridmap_from_2d_arr(){
 
   let snake_arr_map: Vec<Vec<u8>> = common::create_snake_arr_map();
 
-  let gridmap = builder::build_gridmap_from_2d_arr(&snake_arr_map).unwrap();
+  let _gridmap = builder::build_gridmap_from_2d_arr(&snake_arr_map).unwrap();
 
   //TODO: Test assert pixels in gridmap 
 
   // builder::save_gridmap(&gridmap, "test_maps/snake_map.pgm");
 }
+
+#[test]
+fn test_build_gridmap_from_yaml() {
+  let file_path = "test_maps/nav2_maps/turtlebot3_world.yaml";
+
+  let metadata = builder::read_map_yaml(file_path).unwrap();
+  assert_eq!(metadata.image, "turtlebot3_world.pgm");
+  assert_eq!(metadata.origin, (-10.0, -10.0, 0.0));
+  assert_eq!(metadata.mode, builder::MapMode::Trinary);
+
+  let gridmap = builder::build_gridmap_from_yaml(file_path).unwrap();
+  assert_eq!((gridmap.get_width(), gridmap.get_height()), (384, 384));
+  assert_eq!(gridmap.get_resolution(), 0.05);
+
+  // Pixels are thresholded into free, lethal and unknown cells
+  let cells = gridmap.get_cells();
+  assert!(cells.iter().all(|val| [FREE_SPACE, LETHAL_OBSTACLE, NO_INFORMATION].contains(val)));
+  assert_eq!(gridmap.get_val_xy((0, 0)), NO_INFORMATION);
+  assert_eq!(gridmap.get_val_xy((200, 192)), FREE_SPACE);
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use ruth_planner::maps::builder;
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::hpa_star::HPAStarPlanner;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::get_l2_cost;
+use ruth_planner::Gridmap;
+
+fn get_path_cost(path: &[(u32, u32)]) -> u32 {
+    path.windows(2).map(|pair| get_l2_cost(pair[0], pair[1])).sum()
+}
+
+#[test]
+fn test_hpa_star_plan0() {
+    let arr_map: Vec<Vec<u8>> = common::create_snake_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 4), (6, 4));
+
+    let mut planner = HPAStarPlanner::new(&gridmap);
+    planner.update_cluster_size(3);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    assert_eq!(motion_plan.path.first(), Some(&goal_cell));
+    assert_eq!(motion_plan.path.last(), Some(&start_cell));
+}
+
+#[test]
+fn test_hpa_star_plan1() {
+    common::setup();
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = HPAStarPlanner::new(&gridmap);
+    planner.update_cluster_size(4);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    builder::plot_gridmap(
+        &gridmap,
+        &start_cell,
+        &goal_cell,
+        &motion_plan,
+        "test_maps/planners/hpa_star/test_plan1.png",
+        &30,
+    );
+
+    assert_ne!(motion_plan.path.len(), 0);
+}
+
+#[test]
+fn test_hpa_star_plan2() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_1_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = HPAStarPlanner::new(&gridmap);
+    planner.update_cluster_size(4);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    assert_eq!(motion_plan.path.len(), 0);
+}
+
+#[test]
+fn test_hpa_star_same_cluster() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_2_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (5, 5));
+
+    let mut planner = HPAStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    assert_eq!(motion_plan.path.first(), Some(&goal_cell));
+    assert_eq!(motion_plan.path.last(), Some(&start_cell));
+}
+
+#[test]
+fn test_hpa_star_incremental_update() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = HPAStarPlanner::new(&gridmap);
+    planner.update_cluster_size(4);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    assert_ne!(planner.generate_plan().path.len(), 0);
+
+    // Closing the only gap in the right wall makes the goal unreachable
+    assert!(planner.update_cells(&[((9, 3), 255)]));
+    assert_eq!(planner.generate_plan().path.len(), 0);
+
+    // Incremental rebuild matches a planner built from scratch
+    let mut changed_gridmap = gridmap.clone();
+    changed_gridmap.set_val_xy(255, (9, 3));
+    changed_gridmap.set_val_xy(255, (5, 4));
+    let mut fresh_planner = HPAStarPlanner::new(&changed_gridmap);
+    fresh_planner.update_cluster_size(4);
+
+    assert!(planner.update_gridmap(&changed_gridmap));
+    assert_eq!(planner.get_abstract_node_count(), fresh_planner.get_abstract_node_count());
+
+    // Reopening the gap restores the path
+    assert!(planner.update_cells(&[((9, 3), 0)]));
+    assert_ne!(planner.generate_plan().path.len(), 0);
+
+    assert!(!planner.update_cells(&[((10, 0), 0)]));
+
+    // Same cells at a coarser resolution are not dropped
+    let mut coarse_gridmap = Gridmap::new(gridmap.get_width(), gridmap.get_height(), 0.1);
+    for idx in 0..gridmap.get_cells().len() {
+        coarse_gridmap.set_val_xy(gridmap.get_val_idx(idx), gridmap.idx_to_xy(idx as u32));
+    }
+    assert!(planner.update_gridmap(&coarse_gridmap));
+    assert_eq!(planner.get_gridmap().get_resolution(), 0.1);
+    let motion_plan = planner.generate_plan();
+    assert_eq!(motion_plan.stats.path_length_m, motion_plan.stats.path_cost as f32 / 100.0 * 0.1);
+}
+
+#[test]
+fn test_hpa_star_plan_ros1() {
+    common::setup();
+    let gridmap =
+        builder::build_gridmap_from_pgm("test_maps/nav2_maps/turtlebot3_world.pgm").unwrap();
+
+    let (start_cell, goal_cell) = ((165, 220), (230, 155));
+
+    let mut planner = HPAStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    builder::plot_gridmap(
+        &gridmap,
+        &start_cell,
+        &goal_cell,
+        &motion_plan,
+        "test_maps/planners/hpa_star/test_plan_ros1.png",
+        &2,
+    );
+
+    assert_ne!(motion_plan.path.len(), 0);
+}
+
+#[test]
+fn test_hpa_star_plan_ros2() {
+    common::setup();
+    let gridmap =
+        builder::build_gridmap_from_pgm("test_maps/nav2_maps/ostc_map.pgm").unwrap();
+
+    let (start_cell, goal_cell) = ((5, 5), (60, 35));
+
+    let mut planner = HPAStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    builder::plot_gridmap(
+        &gridmap,
+        &start_cell,
+        &goal_cell,
+        &motion_plan,
+        "test_maps/planners/hpa_star/test_plan_ros2.png",
+        &5,
+    );
+
+    // Near optimal compared to A* on the full gridmap
+    let mut a_star_planner = AStarPlanner::new(&gridmap);
+    a_star_planner.update_start_and_goal(start_cell, goal_cell);
+    let optimal_cost = get_path_cost(&a_star_planner.generate_plan().path);
+
+    let cost = get_path_cost(&motion_plan.path);
+    assert!(cost >= optimal_cost);
+    assert!((cost as f32) < optimal_cost as f32 * 1.2);
+
+    for pair in motion_plan.path.windows(2) {
+        assert!(pair[0].0.abs_diff(pair[1].0) <= 1 && pair[0].1.abs_diff(pair[1].1) <= 1);
+        assert!(gridmap.xy_is_traversable(pair[1]));
+    }
+}
@@ -0,0 +1,86 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::metrics::*;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::Gridmap;
+
+fn assert_close(value: f32, expected: f32) {
+    assert!((value - expected).abs() < 1e-4, "{} is not {}", value, expected);
+}
+
+#[test]
+fn test_metrics_distance_field() {
+    let mut gridmap = Gridmap::new(7, 5, 0.05);
+    assert!(get_obstacle_distance_field(&gridmap).iter().all(|distance| distance.is_infinite()));
+
+    gridmap.set_val_xy(254, (1, 1));
+    gridmap.set_val_xy(255, (6, 4));
+    let distance_field = get_obstacle_distance_field(&gridmap);
+
+    // Compare with the distance to the nearest obstacle found by brute force
+    for y in 0..5 {
+        for x in 0..7 {
+            let expected = [(1.0, 1.0), (6.0, 4.0)]
+                .iter()
+                .map(|(obstacle_x, obstacle_y)| (x as f32 - obstacle_x).hypot(y as f32 - obstacle_y))
+                .fold(f32::INFINITY, f32::min);
+            assert_close(distance_field[gridmap.xy_to_idx((x, y))], expected);
+        }
+    }
+}
+
+#[test]
+fn test_metrics_straight_and_turning() {
+    let mut gridmap = Gridmap::new(20, 20, 0.1);
+    gridmap.set_val_xy(254, (5, 0));
+    for x in 0..20 {
+        gridmap.set_val_xy(100, (x, 10));
+    }
+
+    // Straight along the costly row, ten cells from the obstacle at its closest
+    let straight: Vec<(f32, f32)> = (0..=10).map(|x| (x as f32, 10.0)).collect();
+    let metrics = get_path_metrics(&gridmap, &straight);
+    assert_close(metrics.length_m, 1.0);
+    assert_close(metrics.min_clearance_m, 1.0);
+    assert!(metrics.mean_clearance_m > metrics.min_clearance_m);
+    assert_close(metrics.turning_angle, 0.0);
+    assert_close(metrics.max_curvature, 0.0);
+    assert_close(metrics.smoothness, 0.0);
+    assert_close(metrics.integrated_cost, 100.0);
+
+    // A right angle turn, then a half turn back the other way
+    let turns = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (1.0, 1.0), (2.0, 2.0)];
+    assert_close(get_turning_angle(&turns), FRAC_PI_2 + FRAC_PI_4);
+    let curvatures = get_curvatures(&turns, 0.1);
+    assert_eq!(curvatures.len(), 2);
+    // Circle through the corners of a unit square has a radius of √2 / 2 cells
+    assert_close(curvatures[0], 2.0_f32.sqrt() / 0.1);
+
+    // Reversing has no circle through its points
+    assert!(get_curvatures(&[(0.0, 0.0), (1.0, 0.0), (0.0, 0.0)], 1.0).is_empty());
+    assert_close(get_smoothness(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)], 0.5), 2.0_f32.sqrt() * 0.5);
+}
+
+#[test]
+fn test_metrics_planned_path() {
+    let mut gridmap = Gridmap::new(30, 30, 0.05);
+    for y in 5..30 {
+        gridmap.set_val_xy(254, (15, y));
+    }
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal((5, 25), (25, 25));
+    let motion_plan = planner.generate_plan();
+    let metrics = get_path_metrics(&gridmap, &get_points(&motion_plan.path));
+
+    // Search statistics truncate diagonal moves to 1.41 cells
+    let stats_length_m = motion_plan.stats.path_length_m;
+    assert!(metrics.length_m >= stats_length_m && metrics.length_m < stats_length_m * 1.01);
+    assert!(metrics.turning_angle >= std::f32::consts::PI);
+    assert!(metrics.min_clearance_m >= 0.05 && metrics.min_clearance_m < metrics.mean_clearance_m);
+    assert!(metrics.max_curvature >= metrics.mean_curvature && metrics.mean_curvature > 0.0);
+    assert_close(metrics.integrated_cost, 0.0);
+
+    assert_eq!(get_path_metrics(&gridmap, &[]).length_m, 0.0);
+}
@@ -0,0 +1,80 @@
+use ruth_planner::benchmark;
+use ruth_planner::maps::gridmap::*;
+use ruth_planner::maps::movingai::*;
+use ruth_planner::planners::planner_common::PlanningBudget;
+
+const MAP_PATH: &str = "test_maps/movingai/arena_test.map";
+const SCENARIO_PATH: &str = "test_maps/movingai/arena_test.map.scen";
+
+#[test]
+fn test_movingai_map() {
+    let gridmap = build_gridmap_from_map(MAP_PATH).unwrap();
+
+    assert_eq!((gridmap.get_width(), gridmap.get_height()), (24, 16));
+    assert_eq!(gridmap.get_resolution(), 1.0);
+
+    // Out of bounds, trees, water, swamp and ground
+    assert_eq!(gridmap.get_val_xy((0, 0)), NO_INFORMATION);
+    assert_eq!(gridmap.get_val_xy((8, 1)), LETHAL_OBSTACLE);
+    assert_eq!(gridmap.get_val_xy((16, 2)), LETHAL_OBSTACLE);
+    assert_eq!(gridmap.get_val_xy((14, 5)), FREE_SPACE);
+    assert_eq!(gridmap.get_val_xy((9, 11)), FREE_SPACE);
+
+    // A scenario file has no map header
+    assert!(build_gridmap_from_map(SCENARIO_PATH).is_none());
+}
+
+#[test]
+fn test_movingai_scenarios() {
+    let scenarios = read_scenarios(SCENARIO_PATH).unwrap();
+
+    assert_eq!(scenarios.len(), 10);
+    assert_eq!(
+        scenarios[0],
+        Scenario {
+            bucket: 0,
+            map: "maps/test/arena_test.map".to_string(),
+            map_width: 24,
+            map_height: 16,
+            start: (15, 4),
+            goal: (12, 5),
+            optimal_length: 3.41421356,
+        }
+    );
+
+    assert!(read_scenarios(MAP_PATH).is_none());
+    assert!((get_octile_length(&[(0, 0), (1, 1), (1, 2), (1, 2)]) - (2f64.sqrt() + 1.0)).abs() < 1e-12);
+}
+
+#[test]
+fn test_movingai_optimal_lengths() {
+    let gridmap = build_gridmap_from_map(MAP_PATH).unwrap();
+    let scenarios = read_scenarios(SCENARIO_PATH).unwrap();
+
+    let mut planners = benchmark::get_scenario_planners(&gridmap, &PlanningBudget::default());
+    let names: Vec<&str> = planners.iter().map(|planner| planner.name.as_str()).collect();
+    assert_eq!(names, ["Dijkstra", "A*", "FMM", "Space-Time A*"]);
+    let results = benchmark::run_scenarios(&mut planners, &scenarios);
+
+    // Without corner cutting, Dijkstra and A* find paths of exactly the optimal length
+    for query in results.chunks(4) {
+        for result in &query[..2] {
+            assert!((result.get_suboptimality().unwrap() - 1.0).abs() < 1e-6);
+        }
+        // Moving only 4 ways can not beat the optimal length
+        assert!(query[3].get_suboptimality().unwrap() > 1.0 - 1e-6);
+    }
+
+    let table = benchmark::get_scenario_table(&results);
+    assert!(table.lines().nth(2).unwrap().starts_with("Dijkstra       10/10"));
+    assert_eq!(benchmark::get_scenario_csv(&results).lines().count(), 1 + 4 * 10);
+
+    // With the start at the goal, only a path that stays put is optimal
+    let mut result = results[0].clone();
+    result.optimal_length = 0.0;
+    result.path_length = Some(0.0);
+    assert_eq!(result.get_suboptimality(), Some(1.0));
+    result.path_length = Some(1.0);
+    assert_eq!(result.get_suboptimality(), None);
+    assert!(benchmark::get_scenario_table(&[result]).lines().nth(2).unwrap().starts_with("Dijkstra  1/1"));
+}
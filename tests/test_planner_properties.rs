@@ -0,0 +1,179 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::bfs::BFSPlanner;
+use ruth_planner::dfs::DFSPlanner;
+use ruth_planner::dijkstra::DijkstraPlanner;
+use ruth_planner::fmm::FMMPlanner;
+use ruth_planner::hpa_star::HPAStarPlanner;
+use ruth_planner::maps::generators::*;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::*;
+use ruth_planner::space_time_a_star::SpaceTimeAStarPlanner;
+use ruth_planner::validation::validate_path;
+use ruth_planner::Gridmap;
+
+const NUM_MAPS: u64 = 30;
+const QUERIES_PER_MAP: usize = 4;
+
+/// Seeded random maps of every kind, small enough for the uninformed planners
+fn generate_maps() -> Vec<Gridmap> {
+    (0..NUM_MAPS)
+        .map(|seed| match seed % 4 {
+            0 => generate_forest(30, 20, ObstacleShape::Rectangle, 0.25, 2, seed),
+            1 => generate_forest(30, 20, ObstacleShape::Circle, 0.3, 2, seed),
+            2 => generate_cave(30, 20, 0.4, 3, seed),
+            _ => generate_maze(31, 21, MazeAlgorithm::Kruskal, seed),
+        })
+        .collect()
+}
+
+/// Draw start and goal pairs among the traversable cells of a map
+fn get_random_queries(gridmap: &Gridmap, rng: &mut StdRng) -> Vec<((u32, u32), (u32, u32))> {
+    let cells: Vec<(u32, u32)> = (0..gridmap.get_cells().len())
+        .filter(|idx| gridmap.idx_is_traversable(*idx))
+        .map(|idx| gridmap.idx_to_xy(idx as u32))
+        .collect();
+
+    if cells.is_empty() {
+        return Vec::new();
+    }
+
+    (0..QUERIES_PER_MAP)
+        .map(|_| (cells[rng.gen_range(0..cells.len())], cells[rng.gen_range(0..cells.len())]))
+        .collect()
+}
+
+fn plan<P: Planner>(gridmap: &Gridmap, start: (u32, u32), goal: (u32, u32)) -> Vec<(u32, u32)> {
+    let mut planner = P::new(gridmap);
+    planner.update_start_and_goal(start, goal);
+    planner.generate_plan().path
+}
+
+/// Fewest 8 way moves from the start to every cell, as every move costs the same
+fn get_step_field(gridmap: &Gridmap, start: (u32, u32)) -> Vec<u32> {
+    let mut steps: Vec<u32> = vec![u32::MAX; gridmap.get_cells().len()];
+    let mut frontier = vec![start];
+    steps[gridmap.xy_to_idx(start)] = 0;
+
+    while !frontier.is_empty() {
+        let mut next_frontier = Vec::new();
+        for cell in frontier {
+            for nb in get_neighbors_8_con(cell, gridmap) {
+                if steps[gridmap.xy_to_idx(nb)] == u32::MAX {
+                    steps[gridmap.xy_to_idx(nb)] = steps[gridmap.xy_to_idx(cell)] + 1;
+                    next_frontier.push(nb);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+
+    steps
+}
+
+fn get_path_cost(path: &[(u32, u32)]) -> u32 {
+    path.windows(2).map(|pair| get_l2_cost(pair[0], pair[1])).sum()
+}
+
+fn assert_valid_path(name: &str, gridmap: &Gridmap, path: &[(u32, u32)], start: (u32, u32), goal: (u32, u32)) {
+    let validation = validate_path(gridmap, path, &Neighborhood::EightWay, 0.0);
+    assert!(validation.is_valid(), "{} path from {:?} to {:?}: {}", name, start, goal, validation);
+    assert!(validation.endpoints_match(start, goal), "{} path from {:?} to {:?}", name, start, goal);
+
+    // Validation accepts repeated cells as waits, which single agent paths never need
+    for pair in path.windows(2) {
+        assert_ne!(pair[0], pair[1], "{} path from {:?} to {:?} waits at {:?}", name, start, goal, pair[0]);
+    }
+}
+
+#[test]
+fn test_properties_optimal_costs_agree() {
+    let mut rng = StdRng::seed_from_u64(46);
+
+    for gridmap in generate_maps() {
+        for (start, goal) in get_random_queries(&gridmap, &mut rng) {
+            let optimal_cost = get_cost_field(&gridmap, &[start])[gridmap.xy_to_idx(goal)];
+            if optimal_cost == u32::MAX {
+                continue;
+            }
+
+            let dijkstra_path = plan::<DijkstraPlanner>(&gridmap, start, goal);
+            let a_star_path = plan::<AStarPlanner>(&gridmap, start, goal);
+            assert_eq!(get_path_cost(&dijkstra_path), optimal_cost, "Dijkstra from {:?} to {:?}", start, goal);
+            assert_eq!(get_path_cost(&a_star_path), optimal_cost, "A* from {:?} to {:?}", start, goal);
+
+            // With every move costing the same, BFS finds the fewest moves
+            let optimal_steps = get_step_field(&gridmap, start)[gridmap.xy_to_idx(goal)];
+            let bfs_path = plan::<BFSPlanner>(&gridmap, start, goal);
+            assert_eq!(bfs_path.len() as u32 - 1, optimal_steps, "BFS from {:?} to {:?}", start, goal);
+            assert!(dijkstra_path.len() >= bfs_path.len());
+        }
+    }
+}
+
+#[test]
+fn test_properties_paths_valid() {
+    let mut rng = StdRng::seed_from_u64(47);
+
+    for gridmap in generate_maps() {
+        for (start, goal) in get_random_queries(&gridmap, &mut rng) {
+            if get_cost_field(&gridmap, &[start])[gridmap.xy_to_idx(goal)] == u32::MAX {
+                continue;
+            }
+
+            let mut paths = vec![
+                ("Dijkstra", plan::<DijkstraPlanner>(&gridmap, start, goal)),
+                ("A*", plan::<AStarPlanner>(&gridmap, start, goal)),
+                ("BFS", plan::<BFSPlanner>(&gridmap, start, goal)),
+                ("DFS", plan::<DFSPlanner>(&gridmap, start, goal)),
+                ("HPA*", plan::<HPAStarPlanner>(&gridmap, start, goal)),
+            ];
+
+            // FMM and Space-Time A* only move between 4 way neighbors, so they cannot
+            // squeeze diagonally between two obstacles
+            if get_distance_field(&gridmap, &[start])[gridmap.xy_to_idx(goal)] != u32::MAX {
+                paths.push(("FMM", plan::<FMMPlanner>(&gridmap, start, goal)));
+                paths.push(("Space-Time A*", plan::<SpaceTimeAStarPlanner>(&gridmap, start, goal)));
+            }
+
+            for (name, path) in paths {
+                assert_valid_path(name, &gridmap, &path, start, goal);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_properties_unreachable_goals_fail() {
+    let mut rng = StdRng::seed_from_u64(48);
+    let mut num_unreachable = 0;
+
+    for mut gridmap in generate_maps() {
+        // A wall down the middle cuts the map in two
+        for y in 0..gridmap.get_height() {
+            gridmap.set_val_xy(254, (15, y));
+        }
+
+        for (start, goal) in get_random_queries(&gridmap, &mut rng) {
+            if get_cost_field(&gridmap, &[start])[gridmap.xy_to_idx(goal)] != u32::MAX {
+                continue;
+            }
+            num_unreachable += 1;
+
+            assert!(plan::<DijkstraPlanner>(&gridmap, start, goal).is_empty());
+            assert!(plan::<AStarPlanner>(&gridmap, start, goal).is_empty());
+            assert!(plan::<BFSPlanner>(&gridmap, start, goal).is_empty());
+            assert!(plan::<DFSPlanner>(&gridmap, start, goal).is_empty());
+            assert!(plan::<FMMPlanner>(&gridmap, start, goal).is_empty());
+            assert!(plan::<HPAStarPlanner>(&gridmap, start, goal).is_empty());
+            assert!(plan::<SpaceTimeAStarPlanner>(&gridmap, start, goal).is_empty());
+
+            let mut planner = DijkstraPlanner::new(&gridmap);
+            planner.update_start_and_goal(start, goal);
+            assert!(planner.try_generate_plan().is_ok_and(|motion_plan| motion_plan.path.is_empty()));
+        }
+    }
+
+    assert!(num_unreachable > 10);
+}
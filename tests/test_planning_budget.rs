@@ -0,0 +1,251 @@
+mod common;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ruth_planner::maps::builder;
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::bfs::BFSPlanner;
+use ruth_planner::cbs::CBSPlanner;
+use ruth_planner::cooperative_a_star::CooperativeAStarPlanner;
+use ruth_planner::coverage::CoveragePlanner;
+use ruth_planner::dfs::DFSPlanner;
+use ruth_planner::dijkstra::DijkstraPlanner;
+use ruth_planner::fmm::FMMPlanner;
+use ruth_planner::hpa_star::HPAStarPlanner;
+use ruth_planner::space_time_a_star::SpaceTimeAStarPlanner;
+use ruth_planner::tour::TourPlanner;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::*;
+use ruth_planner::Gridmap;
+
+fn assert_expansion_limit<P: Planner>(gridmap: &Gridmap) {
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = P::new(gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    planner.update_budget(&PlanningBudget {
+        max_expansions: Some(5),
+        ..Default::default()
+    });
+
+    let error = planner.try_generate_plan().unwrap_err();
+
+    assert_eq!(error.interruption, PlanningInterruption::ExpansionLimitReached);
+    assert!(error.partial_plan.closed_list.len() <= 6);
+
+    // The plan without a budget is unaffected
+    planner.update_budget(&PlanningBudget::default());
+    assert_ne!(planner.try_generate_plan().unwrap().path.len(), 0);
+}
+
+#[test]
+fn test_budget_expansion_limit() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    assert_expansion_limit::<BFSPlanner>(&gridmap);
+    assert_expansion_limit::<DFSPlanner>(&gridmap);
+    assert_expansion_limit::<DijkstraPlanner>(&gridmap);
+    assert_expansion_limit::<AStarPlanner>(&gridmap);
+    assert_expansion_limit::<FMMPlanner>(&gridmap);
+    assert_expansion_limit::<SpaceTimeAStarPlanner>(&gridmap);
+
+    let mut planner = HPAStarPlanner::new(&gridmap);
+    planner.update_cluster_size(4);
+    planner.update_start_and_goal((0, 9), (9, 0));
+    planner.update_budget(&PlanningBudget {
+        max_expansions: Some(2),
+        ..Default::default()
+    });
+    assert_eq!(
+        planner.try_generate_plan().unwrap_err().interruption,
+        PlanningInterruption::ExpansionLimitReached
+    );
+}
+
+#[test]
+fn test_budget_partial_plan() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    planner.update_budget(&PlanningBudget {
+        max_expansions: Some(20),
+        ..Default::default()
+    });
+
+    let error = planner.try_generate_plan().unwrap_err();
+    let partial_path = error.partial_plan.path;
+
+    // The partial path leads from the start towards the goal
+    assert!(error.partial_plan.closed_list.len() <= 20);
+    assert_eq!(partial_path.last(), Some(&start_cell));
+    assert!(get_l2_cost(partial_path[0], goal_cell) < get_l2_cost(start_cell, goal_cell));
+
+    // generate_plan only reports complete plans
+    assert_eq!(planner.generate_plan().path.len(), 0);
+}
+
+#[test]
+fn test_budget_cancellation() {
+    // A large map with an unreachable goal, which takes a long time to search
+    let mut gridmap = Gridmap::new(1000, 1000, 0.05);
+    for (x, y) in [(998, 999), (998, 998), (999, 998)] {
+        gridmap.set_val_xy(254, (x, y));
+    }
+
+    let token = CancellationToken::new();
+
+    let mut planner = DijkstraPlanner::new(&gridmap);
+    planner.update_start_and_goal((0, 0), (999, 999));
+    planner.update_budget(&PlanningBudget {
+        cancellation_token: Some(token.clone()),
+        ..Default::default()
+    });
+
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        token.cancel();
+    });
+
+    let start_time = Instant::now();
+    let error = planner.try_generate_plan().unwrap_err();
+    canceller.join().unwrap();
+
+    assert_eq!(error.interruption, PlanningInterruption::Cancelled);
+    assert!(start_time.elapsed() < Duration::from_secs(5));
+    assert_ne!(error.partial_plan.path.len(), 0);
+}
+
+#[test]
+fn test_budget_time_limit() {
+    let mut gridmap = Gridmap::new(1000, 1000, 0.05);
+    for (x, y) in [(998, 999), (998, 998), (999, 998)] {
+        gridmap.set_val_xy(254, (x, y));
+    }
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal((0, 0), (999, 999));
+    planner.update_budget(&PlanningBudget {
+        time_limit: Some(Duration::from_millis(20)),
+        ..Default::default()
+    });
+
+    let start_time = Instant::now();
+    let error = planner.try_generate_plan().unwrap_err();
+
+    assert_eq!(error.interruption, PlanningInterruption::TimedOut);
+    assert!(start_time.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_budget_multi_agent() {
+    // Agents swapping ends of a corridor with no room to pass, which CBS
+    // keeps branching on until it runs out of constraint tree nodes
+    let gridmap = Gridmap::new(20, 1, 0.05);
+    let (starts, goals) = ([(0, 0), (19, 0)], [(19, 0), (0, 0)]);
+
+    let mut planner = CBSPlanner::new(&gridmap);
+    planner.update_agents(&starts, &goals);
+    planner.update_max_high_level_nodes(usize::MAX);
+    planner.update_budget(&PlanningBudget {
+        time_limit: Some(Duration::from_millis(50)),
+        ..Default::default()
+    });
+
+    let start_time = Instant::now();
+    let error = planner.try_generate_plan().unwrap_err();
+    assert!(start_time.elapsed() < Duration::from_secs(5));
+    assert_eq!(error.interruption, PlanningInterruption::TimedOut);
+    // The cheapest constraint tree node still has a path for both agents
+    assert_eq!(error.partial_plan.paths.len(), 2);
+    assert_eq!(planner.generate_plan().paths.len(), 0);
+
+    // Expansions of every agent count towards the limit
+    let arr_map: Vec<Vec<u8>> = common::create_maze_2_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (starts, goals) = ([(0, 0), (9, 0)], [(9, 9), (0, 9)]);
+    let budget = PlanningBudget {
+        max_expansions: Some(30),
+        ..Default::default()
+    };
+
+    // Only the first agent is planned within the budget
+    let mut planner = CBSPlanner::new(&gridmap);
+    planner.update_agents(&starts, &goals);
+    planner.update_budget(&budget);
+    let error = planner.try_generate_plan().unwrap_err();
+    assert_eq!(error.interruption, PlanningInterruption::ExpansionLimitReached);
+    assert_eq!(error.partial_plan.paths.len(), 1);
+    assert_eq!(error.partial_plan.paths[0][0].0, goals[0]);
+    planner.update_budget(&PlanningBudget::default());
+    assert_eq!(planner.try_generate_plan().unwrap().paths.len(), 2);
+
+    let mut planner = CooperativeAStarPlanner::new(&gridmap);
+    planner.update_agents(&starts, &goals);
+    planner.update_budget(&budget);
+    let error = planner.try_generate_plan().unwrap_err();
+    assert_eq!(error.interruption, PlanningInterruption::ExpansionLimitReached);
+    assert_eq!(error.partial_plan.paths.len(), 1);
+    assert_eq!(error.partial_plan.paths[0][0].0, goals[0]);
+
+    // Windowed planning keeps the moves made before the budget ran out
+    planner.update_window(4);
+    let error = planner.try_generate_plan().unwrap_err();
+    assert_eq!(error.interruption, PlanningInterruption::ExpansionLimitReached);
+    for (path, start) in error.partial_plan.paths.iter().zip(starts) {
+        assert!(path.len() > 1);
+        assert_eq!(path[path.len() - 1], (start, 0));
+    }
+    planner.update_budget(&PlanningBudget::default());
+    assert_eq!(planner.try_generate_plan().unwrap().paths.len(), 2);
+}
+
+#[test]
+fn test_budget_tour_and_coverage() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let cancelled_budget = PlanningBudget {
+        cancellation_token: Some(token),
+        ..Default::default()
+    };
+    let limited_budget = PlanningBudget {
+        max_expansions: Some(10),
+        ..Default::default()
+    };
+
+    let mut planner: TourPlanner<AStarPlanner> = TourPlanner::new(&gridmap);
+    planner.update_waypoints(&[(0, 9), (9, 0), (9, 9)]);
+    planner.update_budget(&cancelled_budget);
+    assert_eq!(planner.try_generate_plan().unwrap_err().interruption, PlanningInterruption::Cancelled);
+    // The waypoints are ordered before the budget runs out planning the legs
+    planner.update_budget(&limited_budget);
+    let error = planner.try_generate_plan().unwrap_err();
+    assert_eq!(error.interruption, PlanningInterruption::ExpansionLimitReached);
+    assert_eq!(error.partial_plan.order.len(), 3);
+    assert_eq!(error.partial_plan.order[0], 0);
+    assert_eq!(planner.generate_plan().order.len(), 0);
+    planner.update_budget(&PlanningBudget::default());
+    assert_eq!(planner.try_generate_plan().unwrap().order.len(), 3);
+
+    let mut planner = CoveragePlanner::new(&gridmap);
+    planner.update_start((0, 9));
+    planner.update_budget(&cancelled_budget);
+    assert_eq!(planner.try_generate_plan().unwrap_err().interruption, PlanningInterruption::Cancelled);
+    // The waypoints linked before the budget runs out are kept
+    planner.update_budget(&limited_budget);
+    let error = planner.try_generate_plan().unwrap_err();
+    assert_eq!(error.interruption, PlanningInterruption::ExpansionLimitReached);
+    assert!(error.partial_plan.motion_plan.path.len() > 1);
+    assert_eq!(error.partial_plan.motion_plan.path.last(), Some(&(0, 9)));
+    assert!(error.partial_plan.coverage > 0.0 && error.partial_plan.coverage < 100.0);
+    assert_eq!(planner.generate_plan().motion_plan.path.len(), 0);
+    planner.update_budget(&PlanningBudget::default());
+    assert_eq!(planner.try_generate_plan().unwrap().coverage, 100.0);
+}
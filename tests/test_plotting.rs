@@ -0,0 +1,179 @@
+mod common;
+
+use std::fs;
+
+use image::GenericImageView;
+use plotters::prelude::*;
+use ruth_planner::maps::builder;
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::dijkstra::DijkstraPlanner;
+use ruth_planner::fmm::FMMPlanner;
+use ruth_planner::hpa_star::HPAStarPlanner;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::{get_distance_field, MotionPlan, TraceLevel};
+use ruth_planner::Gridmap;
+
+fn generate_plan<P: Planner>(gridmap: &Gridmap, start_cell: (u32, u32), goal_cell: (u32, u32)) -> MotionPlan {
+    let mut planner = P::new(gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    planner.generate_plan()
+}
+
+#[test]
+fn test_plotting_svg() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+    let file_path = "test_maps/planners/a_star/test_plan1.svg";
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    builder::plot_gridmap(&gridmap, &start_cell, &goal_cell, &motion_plan, file_path, &10);
+
+    let svg = fs::read_to_string(file_path).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("#0000FF"));
+}
+
+#[test]
+fn test_plotting_style() {
+    let gridmap = builder::build_gridmap_from_pgm("test_maps/nav2_maps/ostc_map.pgm").unwrap();
+    let (start_cell, goal_cell) = ((5, 5), (60, 35));
+    let file_path = "test_maps/planners/a_star/test_plan_ros2_styled.png";
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    let style = builder::PlotStyle {
+        img_width: 640,
+        start_color: MAGENTA.to_rgba(),
+        path_color: RED.to_rgba(),
+        show_legend: true,
+        ..Default::default()
+    };
+    builder::plot_gridmap_with_style(&gridmap, &start_cell, &goal_cell, &motion_plan, file_path, &3, &style);
+
+    assert_eq!(image::open(file_path).unwrap().dimensions().0, 640);
+}
+
+#[test]
+fn test_plotting_custom_backend() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+
+    // Draw into memory rather than a file
+    let mut buffer = vec![0u8; 200 * 200 * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (200, 200)).into_drawing_area();
+        let style = builder::PlotStyle::default();
+        builder::draw_gridmap(&root, &gridmap, &start_cell, &goal_cell, &motion_plan, 2, &style);
+        root.present().unwrap();
+    }
+
+    // The background is white, and the map and plan are drawn over it
+    assert!(buffer.iter().any(|val| *val != 255));
+}
+
+#[test]
+fn test_plotting_comparison() {
+    common::setup();
+    let gridmap = builder::build_gridmap_from_pgm("test_maps/nav2_maps/ostc_map.pgm").unwrap();
+    let (start_cell, goal_cell) = ((5, 5), (60, 35));
+
+    let dijkstra_plan = generate_plan::<DijkstraPlanner>(&gridmap, start_cell, goal_cell);
+    let a_star_plan = generate_plan::<AStarPlanner>(&gridmap, start_cell, goal_cell);
+    let fmm_plan = generate_plan::<FMMPlanner>(&gridmap, start_cell, goal_cell);
+    let hpa_star_plan = generate_plan::<HPAStarPlanner>(&gridmap, start_cell, goal_cell);
+    let plans = [
+        ("Dijkstra", &dijkstra_plan),
+        ("A*", &a_star_plan),
+        ("FMM", &fmm_plan),
+        ("HPA*", &hpa_star_plan),
+    ];
+
+    let style = builder::PlotStyle::default();
+    let overlay_path = "test_maps/planners/comparison/test_plan_ros2_overlay.png";
+    let grid_path = "test_maps/planners/comparison/test_plan_ros2_grid.png";
+
+    builder::plot_comparison_overlay(&gridmap, &start_cell, &goal_cell, &plans, overlay_path, &3, &style);
+    builder::plot_comparison_grid(&gridmap, &start_cell, &goal_cell, &plans, grid_path, &2, &style);
+
+    // Four plans fit in two rows of two maps
+    let (overlay_width, overlay_height) = image::open(overlay_path).unwrap().dimensions();
+    let (grid_width, grid_height) = image::open(grid_path).unwrap().dimensions();
+    assert_eq!(grid_width, overlay_width);
+    assert_eq!(grid_height, 2 * (overlay_height / 2 + 20));
+}
+
+#[test]
+fn test_plotting_comparison_svg() {
+    common::setup();
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+    let file_path = "test_maps/planners/comparison/test_plan1_grid.svg";
+
+    let dijkstra_plan = generate_plan::<DijkstraPlanner>(&gridmap, start_cell, goal_cell);
+    let a_star_plan = generate_plan::<AStarPlanner>(&gridmap, start_cell, goal_cell);
+    let fmm_plan = generate_plan::<FMMPlanner>(&gridmap, start_cell, goal_cell);
+    let plans = [("Dijkstra", &dijkstra_plan), ("A*", &a_star_plan), ("FMM", &fmm_plan)];
+
+    builder::plot_comparison_grid(&gridmap, &start_cell, &goal_cell, &plans, file_path, &10, &builder::PlotStyle::default());
+
+    let svg = fs::read_to_string(file_path).unwrap();
+    assert!(plans.iter().all(|(name, _)| svg.contains(name)));
+}
+
+#[test]
+fn test_plotting_heatmaps() {
+    common::setup();
+    let gridmap = builder::build_gridmap_from_pgm("test_maps/nav2_maps/ostc_map.pgm").unwrap();
+    let (start_cell, goal_cell) = ((5, 5), (60, 35));
+    let style = builder::PlotStyle::default();
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    planner.update_trace_level(TraceLevel::Expansions);
+
+    let trace = planner.generate_plan().trace.unwrap();
+    let g_cost_field = trace.get_g_cost_field(&gridmap);
+    let h_cost_field = trace.get_h_cost_field(&gridmap);
+
+    // Only expanded cells have a cost, the start costing nothing to reach
+    assert_eq!(g_cost_field[gridmap.xy_to_idx(start_cell)], 0.0);
+    assert_eq!(
+        g_cost_field.iter().filter(|cost| cost.is_finite()).count(),
+        h_cost_field.iter().filter(|cost| cost.is_finite()).count()
+    );
+    for record in &trace.expansions {
+        assert_eq!(g_cost_field[gridmap.xy_to_idx(record.cell)], record.g_cost as f32);
+    }
+
+    builder::plot_heatmap(&gridmap, &g_cost_field, "test_maps/planners/heatmaps/test_plan_ros2_g_cost.png", &style);
+    builder::plot_heatmap(&gridmap, &h_cost_field, "test_maps/planners/heatmaps/test_plan_ros2_h_cost.png", &style);
+
+    // Number of moves from the goal, and the inflated costs of the map itself
+    let distance_field: Vec<f32> = get_distance_field(&gridmap, &[goal_cell])
+        .iter()
+        .map(|distance| if *distance == u32::MAX { f32::INFINITY } else { *distance as f32 })
+        .collect();
+    let cost_field: Vec<f32> = gridmap.get_cells().iter().map(|cost| *cost as f32).collect();
+
+    builder::plot_heatmap(&gridmap, &distance_field, "test_maps/planners/heatmaps/test_ros2_distance.png", &style);
+    builder::plot_heatmap(&gridmap, &cost_field, "test_maps/planners/heatmaps/test_ros2_costs.png", &style);
+
+    // The colour bar is added beside the map
+    let (width, _) = image::open("test_maps/planners/heatmaps/test_ros2_costs.png").unwrap().dimensions();
+    assert!(width > style.img_width);
+}
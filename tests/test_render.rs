@@ -0,0 +1,57 @@
+mod common;
+
+use ruth_planner::maps::builder;
+use ruth_planner::maps::render::*;
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::Gridmap;
+
+#[test]
+fn test_render_gridmap() {
+    let mut gridmap = Gridmap::new(4, 2, 0.05);
+    gridmap.set_val_xy(254, (1, 0));
+    gridmap.set_val_xy(255, (2, 0));
+    gridmap.set_val_xy(100, (3, 1));
+
+    // The top row is rendered first
+    assert_eq!(render_gridmap(&gridmap, &RenderOptions::default()), ".#?.\n...:\n");
+
+    let options = RenderOptions {
+        glyphs: RenderGlyphs::unicode(),
+        color: ColorMode::Never,
+    };
+    assert_eq!(render_gridmap(&gridmap, &options), "·█▒·\n···░\n");
+}
+
+#[test]
+fn test_render_plan1() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let motion_plan = planner.generate_plan();
+    let options = RenderOptions {
+        color: ColorMode::Never,
+        ..Default::default()
+    };
+    let text = render_plan(&gridmap, &start_cell, &goal_cell, &motion_plan, &options);
+    let rows: Vec<&str> = text.lines().collect();
+
+    assert_eq!(rows.len(), 10);
+    assert!(rows.iter().all(|row| row.chars().count() == 10));
+    assert!(rows[9].starts_with('S'));
+    assert!(rows[0].ends_with('G'));
+    assert_eq!(text.matches('*').count(), motion_plan.path.len() - 2, "{}", text);
+
+    // Colours wrap each glyph other than free space, and are reset after it
+    let options = RenderOptions {
+        color: ColorMode::Always,
+        ..Default::default()
+    };
+    let text = render_plan(&gridmap, &start_cell, &goal_cell, &motion_plan, &options);
+    assert!(text.contains("\x1b[1;36mS\x1b[0m"));
+    assert_eq!(text.matches("\x1b[0m").count(), text.matches("\x1b[").count() / 2);
+}
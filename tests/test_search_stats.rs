@@ -0,0 +1,123 @@
+mod common;
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use ruth_planner::maps::builder;
+use ruth_planner::maps::generators::*;
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::bfs::BFSPlanner;
+use ruth_planner::dfs::DFSPlanner;
+use ruth_planner::dijkstra::DijkstraPlanner;
+use ruth_planner::space_time_a_star::SpaceTimeAStarPlanner;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::*;
+use ruth_planner::Gridmap;
+
+fn assert_consistent_stats<P: Planner>(gridmap: &Gridmap) -> SearchStats {
+    let mut planner = P::new(gridmap);
+    planner.update_start_and_goal((0, 9), (9, 0));
+
+    let motion_plan = planner.generate_plan();
+    let stats = motion_plan.stats;
+    let path_cost: u32 = motion_plan.path.windows(2).map(|pair| get_l2_cost(pair[0], pair[1])).sum();
+
+    assert!(stats.nodes_expanded > 0);
+    assert!(stats.nodes_generated >= stats.nodes_expanded);
+    assert!(stats.max_open_list_size <= stats.nodes_generated);
+    assert_eq!(stats.path_cost, path_cost);
+    assert!((stats.path_length_m - path_cost as f32 / 100.0 * gridmap.get_resolution()).abs() < 1e-6);
+    assert!(stats.elapsed_time > Duration::ZERO);
+
+    stats
+}
+
+#[test]
+fn test_search_stats_planners() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    assert_consistent_stats::<BFSPlanner>(&gridmap);
+    assert_consistent_stats::<DFSPlanner>(&gridmap);
+    assert_consistent_stats::<SpaceTimeAStarPlanner>(&gridmap);
+    assert_consistent_stats::<DijkstraPlanner>(&gridmap);
+    assert_consistent_stats::<AStarPlanner>(&gridmap);
+}
+
+#[test]
+fn test_search_stats_heuristic() {
+    let gridmap = Gridmap::new(10, 10, 0.05);
+
+    let dijkstra_stats = assert_consistent_stats::<DijkstraPlanner>(&gridmap);
+    let a_star_stats = assert_consistent_stats::<AStarPlanner>(&gridmap);
+
+    // Both are optimal, but in open space the heuristic saves A* most expansions
+    assert_eq!(a_star_stats.path_cost, dijkstra_stats.path_cost);
+    assert!(a_star_stats.nodes_expanded < dijkstra_stats.nodes_expanded);
+}
+
+/// Each cell is expanded and traced once, however often it was put on the open list
+fn assert_single_expansions<P: Planner>(gridmap: &Gridmap) {
+    let mut planner = P::new(gridmap);
+    planner.update_start_and_goal((0, 11), (11, 0));
+    planner.update_trace_level(TraceLevel::Expansions);
+
+    let motion_plan = planner.generate_plan();
+    let trace = motion_plan.trace.unwrap();
+    let traced_cells: HashSet<(u32, u32)> = trace.expansions.iter().map(|record| record.cell).collect();
+
+    assert!(motion_plan.stats.nodes_generated > motion_plan.closed_list.len());
+    assert_eq!(motion_plan.stats.nodes_expanded, motion_plan.closed_list.len());
+    assert_eq!(motion_plan.stats.reopenings, 0);
+    assert_eq!(trace.expansions.len(), motion_plan.closed_list.len());
+    assert_eq!(traced_cells.len(), motion_plan.closed_list.len());
+
+    // The expansions needed leave the expansion limit unused
+    planner.update_budget(&PlanningBudget {
+        max_expansions: Some(motion_plan.stats.nodes_expanded),
+        ..Default::default()
+    });
+    assert_eq!(planner.try_generate_plan().unwrap().path, motion_plan.path);
+}
+
+#[test]
+fn test_search_stats_stale_entries() {
+    // Obstacles make A* and DFS put many cells on the open list again before they are expanded
+    let gridmap = generate_forest(12, 12, ObstacleShape::Rectangle, 0.2, 2, 4);
+
+    assert_single_expansions::<DFSPlanner>(&gridmap);
+    assert_single_expansions::<DijkstraPlanner>(&gridmap);
+    assert_single_expansions::<AStarPlanner>(&gridmap);
+}
+
+#[test]
+fn test_search_stats_route() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    let direct_stats = planner.generate_plan().stats;
+
+    // A route there and back costs twice the direct plan, and takes the effort of both legs
+    let route_stats = planner.generate_route_plan(&[start_cell, goal_cell, start_cell]).stats;
+    assert_eq!(route_stats.path_cost, 2 * direct_stats.path_cost);
+    assert!(route_stats.nodes_expanded > direct_stats.nodes_expanded);
+}
+
+#[test]
+fn test_search_stats_partial_plan() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    let mut planner = DijkstraPlanner::new(&gridmap);
+    planner.update_start_and_goal((0, 9), (9, 0));
+    planner.update_budget(&PlanningBudget {
+        max_expansions: Some(10),
+        ..Default::default()
+    });
+
+    let error = planner.try_generate_plan().unwrap_err();
+    assert_eq!(error.partial_plan.stats.nodes_expanded, 10);
+}
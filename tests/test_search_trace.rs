@@ -0,0 +1,109 @@
+mod common;
+
+use std::collections::HashSet;
+
+use ruth_planner::maps::builder;
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::bfs::BFSPlanner;
+use ruth_planner::dfs::DFSPlanner;
+use ruth_planner::dijkstra::DijkstraPlanner;
+use ruth_planner::fmm::FMMPlanner;
+use ruth_planner::hpa_star::HPAStarPlanner;
+use ruth_planner::space_time_a_star::SpaceTimeAStarPlanner;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::*;
+use ruth_planner::Gridmap;
+
+fn assert_consistent_trace<P: Planner>(gridmap: &Gridmap) -> SearchTrace {
+    let mut planner = P::new(gridmap);
+    planner.update_start_and_goal((0, 9), (9, 0));
+
+    // Nothing is recorded by default
+    assert!(planner.generate_plan().trace.is_none());
+
+    planner.update_trace_level(TraceLevel::Expansions);
+    let motion_plan = planner.generate_plan();
+    let trace = motion_plan.trace.unwrap();
+
+    assert_eq!(trace.expansions.len(), motion_plan.stats.nodes_expanded);
+    assert!(trace.open_list_snapshots.is_empty());
+
+    let mut expanded: HashSet<(u32, u32)> = HashSet::new();
+    for (iteration, record) in trace.expansions.iter().enumerate() {
+        assert_eq!(record.iteration, iteration);
+        assert_eq!(record.f_cost, record.g_cost + record.h_cost);
+
+        // Every cell is reached from one expanded before it
+        assert!(record.parent == record.cell || expanded.contains(&record.parent));
+        expanded.insert(record.cell);
+    }
+
+    planner.update_trace_level(TraceLevel::OpenLists);
+    let trace = planner.generate_plan().trace.unwrap();
+    assert_eq!(trace.open_list_snapshots.len(), trace.expansions.len());
+
+    trace
+}
+
+#[test]
+fn test_search_trace_planners() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    assert_consistent_trace::<BFSPlanner>(&gridmap);
+    assert_consistent_trace::<FMMPlanner>(&gridmap);
+    assert_consistent_trace::<HPAStarPlanner>(&gridmap);
+    assert_consistent_trace::<SpaceTimeAStarPlanner>(&gridmap);
+
+    // The start is expanded first, and costs never decrease along the trace
+    let trace = assert_consistent_trace::<DijkstraPlanner>(&gridmap);
+    assert_eq!((trace.expansions[0].cell, trace.expansions[0].g_cost), ((0, 9), 0));
+    assert!(trace.expansions.windows(2).all(|pair| pair[0].g_cost <= pair[1].g_cost));
+
+    // A* records its heuristic
+    let trace = assert_consistent_trace::<AStarPlanner>(&gridmap);
+    assert_eq!(trace.expansions[0].parent, (0, 9));
+    assert_eq!(trace.expansions[0].h_cost, get_l2_cost((0, 9), (9, 0)));
+
+    // The first cell on the stack not yet expanded is the one expanded next
+    let trace = assert_consistent_trace::<DFSPlanner>(&gridmap);
+    let mut expanded: HashSet<(u32, u32)> = HashSet::new();
+    for (record, next_record) in trace.expansions.iter().zip(trace.expansions.iter().skip(1)) {
+        expanded.insert(record.cell);
+        let snapshot = &trace.open_list_snapshots[record.iteration];
+        assert_eq!(snapshot.iter().find(|cell| !expanded.contains(cell)), Some(&next_record.cell));
+    }
+}
+
+#[test]
+fn test_search_trace_route() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_trace_level(TraceLevel::Expansions);
+
+    let route_plan = planner.generate_route_plan(&[(0, 9), (9, 0), (0, 9)]);
+    let trace = route_plan.trace.unwrap();
+
+    // The legs continue numbering where the previous one stopped
+    assert_eq!(trace.expansions.len(), route_plan.stats.nodes_expanded);
+    assert!(trace.expansions.iter().enumerate().all(|(iteration, record)| record.iteration == iteration));
+}
+
+#[test]
+fn test_search_trace_partial_plan() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    let mut planner = BFSPlanner::new(&gridmap);
+    planner.update_start_and_goal((0, 9), (9, 0));
+    planner.update_trace_level(TraceLevel::Expansions);
+    planner.update_budget(&PlanningBudget {
+        max_expansions: Some(10),
+        ..Default::default()
+    });
+
+    let error = planner.try_generate_plan().unwrap_err();
+    assert_eq!(error.partial_plan.trace.unwrap().expansions.len(), 10);
+}
@@ -0,0 +1,93 @@
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::maps::generators::*;
+use ruth_planner::metrics;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::Neighborhood;
+use ruth_planner::post_processing::shortcut::*;
+use ruth_planner::validation::validate_path;
+use ruth_planner::Gridmap;
+
+fn plan(gridmap: &Gridmap, start: (u32, u32), goal: (u32, u32)) -> Vec<(u32, u32)> {
+    let mut planner = AStarPlanner::new(gridmap);
+    planner.update_start_and_goal(start, goal);
+    planner.generate_plan().path
+}
+
+/// Waypoints keep the ends of the path, and the cells between them are collision free
+fn assert_shortcut(gridmap: &Gridmap, path: &[(u32, u32)], waypoints: &[(u32, u32)]) {
+    assert_eq!((waypoints.first(), waypoints.last()), (path.first(), path.last()));
+    assert!(waypoints.len() <= path.len());
+
+    let dense_path = get_dense_path(waypoints);
+    let validation = validate_path(gridmap, &dense_path, &Neighborhood::EightWay, 0.0);
+    assert!(validation.is_valid(), "{}", validation);
+
+    let length = |path: &[(u32, u32)]| metrics::get_length_m(&metrics::get_points(path), 1.0);
+    assert!(length(waypoints) <= length(path) + 1e-4);
+}
+
+#[test]
+fn test_shortcut_lines() {
+    assert_eq!(get_line_cells((0, 0), (4, 2), LineOfSight::Bresenham), [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]);
+    assert_eq!(get_line_cells((4, 2), (0, 0), LineOfSight::Bresenham).len(), 5);
+    assert_eq!(get_line_cells((3, 3), (3, 3), LineOfSight::Supercover), [(3, 3)]);
+
+    // The segment passes exactly through the corner between (1, 0) and (0, 1)
+    assert_eq!(
+        get_line_cells((0, 0), (2, 2), LineOfSight::Supercover),
+        [(0, 0), (1, 0), (0, 1), (1, 1), (2, 1), (1, 2), (2, 2)]
+    );
+    assert_eq!(get_line_cells((0, 2), (3, 0), LineOfSight::Supercover), [(0, 2), (1, 2), (1, 1), (2, 1), (2, 0), (3, 0)]);
+
+    // Bresenham squeezes diagonally past the corner of an obstacle, supercover does not
+    let mut gridmap = Gridmap::new(5, 5, 0.05);
+    gridmap.set_val_xy(254, (1, 0));
+    assert!(has_line_of_sight(&gridmap, (0, 0), (2, 2), LineOfSight::Bresenham));
+    assert!(!has_line_of_sight(&gridmap, (0, 0), (2, 2), LineOfSight::Supercover));
+    assert!(has_line_of_sight(&gridmap, (0, 1), (4, 1), LineOfSight::Supercover));
+}
+
+#[test]
+fn test_shortcut_greedy() {
+    // Around the end of a wall, the path needs a waypoint at the corner
+    let mut gridmap = Gridmap::new(30, 20, 0.05);
+    for y in 4..20 {
+        gridmap.set_val_xy(254, (15, y));
+    }
+    let path = plan(&gridmap, (5, 15), (25, 15));
+
+    let waypoints = shortcut_greedy(&gridmap, &path, LineOfSight::Bresenham);
+    assert_eq!(waypoints, [(25, 15), (15, 3), (5, 15)]);
+    assert_shortcut(&gridmap, &path, &waypoints);
+
+    // A* cuts diagonally past the end of the wall, which supercover lines cannot shorten
+    let waypoints = shortcut_greedy(&gridmap, &path, LineOfSight::Supercover);
+    assert_eq!(waypoints, [(25, 15), (16, 4), (15, 3), (14, 4), (5, 15)]);
+    assert_shortcut(&gridmap, &path, &waypoints);
+
+    // In open space only the ends are left
+    let open_gridmap = Gridmap::new(30, 20, 0.05);
+    let path = plan(&open_gridmap, (2, 3), (27, 18));
+    assert_eq!(shortcut_greedy(&open_gridmap, &path, LineOfSight::Supercover), [path[0], *path.last().unwrap()]);
+    assert!(shortcut_greedy(&open_gridmap, &[], LineOfSight::Supercover).is_empty());
+}
+
+#[test]
+fn test_shortcut_random() {
+    for seed in 0..10 {
+        let gridmap = generate_forest(40, 30, ObstacleShape::Circle, 0.15, 2, seed);
+        let (start, goal) = ((0, 0), (39, 29));
+        let path = plan(&gridmap, start, goal);
+        if path.is_empty() {
+            continue;
+        }
+
+        let waypoints = shortcut_random(&gridmap, &path, LineOfSight::Supercover, 200, seed);
+        assert_shortcut(&gridmap, &path, &waypoints);
+        assert!(waypoints.len() < path.len() / 2);
+        assert_eq!(shortcut_random(&gridmap, &path, LineOfSight::Supercover, 200, seed), waypoints);
+
+        let greedy_waypoints = shortcut_greedy(&gridmap, &path, LineOfSight::Supercover);
+        assert_shortcut(&gridmap, &path, &greedy_waypoints);
+    }
+}
@@ -0,0 +1,90 @@
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::metrics;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::post_processing::smoother::*;
+use ruth_planner::Gridmap;
+
+/// Open map with a wall whose end the paths turn around
+fn create_wall_map() -> Gridmap {
+    let mut gridmap = Gridmap::new(40, 30, 0.05);
+    for y in 8..30 {
+        gridmap.set_val_xy(254, (20, y));
+    }
+    gridmap
+}
+
+fn plan(gridmap: &Gridmap, start: (u32, u32), goal: (u32, u32)) -> Vec<(f32, f32)> {
+    let mut planner = AStarPlanner::new(gridmap);
+    planner.update_start_and_goal(start, goal);
+    metrics::get_points(&planner.generate_plan().path)
+}
+
+#[test]
+fn test_smoother_resample() {
+    let path = vec![(0.0, 0.0), (3.0, 0.0), (3.0, 4.5)];
+    let points = resample_path(&path, 1.0);
+
+    // 7.5 cells long, so 8 segments of 0.9375 cells
+    assert_eq!(points.len(), 9);
+    assert_eq!((points[0], points[8]), (path[0], path[2]));
+    for pair in points.windows(2) {
+        let distance = (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1);
+        assert!(distance <= 0.9375 + 1e-4);
+    }
+    assert!((points[3].0 - 2.8125).abs() < 1e-4 && points[3].1 == 0.0);
+
+    assert_eq!(resample_path(&[(1.0, 1.0)], 1.0), [(1.0, 1.0)]);
+    assert_eq!(resample_path(&path, 0.0), path);
+}
+
+#[test]
+fn test_smoother_around_wall() {
+    let gridmap = create_wall_map();
+    let path = plan(&gridmap, (10, 25), (30, 25));
+    let smoothed = smooth_path(&gridmap, &path, &SmootherConfig::default());
+
+    assert_eq!((smoothed.first(), smoothed.last()), (path.first(), path.last()));
+    assert!(smoothed
+        .iter()
+        .all(|point| gridmap.xy_is_traversable((point.0.round() as u32, point.1.round() as u32))));
+
+    // Smoother, with fewer turns, and pushed away from the end of the wall
+    let (before, after) = (metrics::get_path_metrics(&gridmap, &path), metrics::get_path_metrics(&gridmap, &smoothed));
+    assert!(after.smoothness < before.smoothness);
+    assert!(after.turning_angle < before.turning_angle);
+    assert!(after.max_curvature < before.max_curvature);
+    assert!(after.min_clearance_m > before.min_clearance_m);
+    assert!(after.length_m < before.length_m * 1.1);
+
+    // Evenly spaced, a cell apart at most
+    for pair in smoothed.windows(2) {
+        assert!((pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1) < 1.5);
+    }
+}
+
+#[test]
+fn test_smoother_config() {
+    let gridmap = create_wall_map();
+
+    // Shortcut waypoints are resampled and smoothed like a grid path
+    let cells: Vec<(u32, u32)> = vec![(30, 25), (20, 7), (10, 25)];
+    let smoothed = smooth_path(&gridmap, &metrics::get_points(&cells), &SmootherConfig::default());
+    assert!(smoothed.len() > 30);
+    assert_eq!(smoothed[0], (30.0, 25.0));
+
+    // Without iterations the path is only resampled
+    let path = plan(&gridmap, (10, 25), (30, 25));
+    let config = SmootherConfig {
+        max_iterations: 0,
+        ..Default::default()
+    };
+    assert_eq!(smooth_path(&gridmap, &path, &config), resample_path(&path, 1.0));
+
+    // A straight path in open space is left in place
+    let straight = vec![(2.0, 2.0), (3.0, 2.0), (4.0, 2.0), (5.0, 2.0)];
+    let config = SmootherConfig {
+        spacing: None,
+        ..Default::default()
+    };
+    assert_eq!(smooth_path(&Gridmap::new(8, 8, 0.05), &straight, &config), straight);
+}
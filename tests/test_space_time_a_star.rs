@@ -0,0 +1,98 @@
+mod common;
+
+use ruth_planner::maps::builder;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::space_time_a_star::*;
+use ruth_planner::Gridmap;
+
+fn assert_valid_timed_path(path: &[((u32, u32), u32)], reservation_table: &ReservationTable) {
+    for pair in path.windows(2) {
+        let ((pos, time), (prev_pos, prev_time)) = (pair[0], pair[1]);
+        assert_eq!(time, prev_time + 1);
+        assert!(pos.0.abs_diff(prev_pos.0) + pos.1.abs_diff(prev_pos.1) <= 1);
+        assert!(!reservation_table.is_move_reserved(prev_pos, pos, time));
+    }
+}
+
+#[test]
+fn test_space_time_a_star_no_reservations() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = SpaceTimeAStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+
+    let timed_plan = planner.generate_timed_plan();
+
+    assert_eq!(timed_plan.path.first().map(|(pos, _)| *pos), Some(goal_cell));
+    assert_eq!(timed_plan.path.last(), Some(&(start_cell, 0)));
+    assert_valid_timed_path(&timed_plan.path, planner.get_reservation_table());
+
+    // Without anyone else around there is no reason to wait
+    let motion_plan = planner.generate_plan();
+    assert!(motion_plan.path.windows(2).all(|pair| pair[0] != pair[1]));
+    assert_eq!(motion_plan.path.len(), timed_plan.path.len());
+}
+
+#[test]
+fn test_space_time_a_star_moving_obstacle() {
+    let gridmap = Gridmap::new(5, 3, 0.05);
+    let (start_cell, goal_cell) = ((0, 1), (4, 1));
+
+    // Another robot crosses the corridor just as the planned one would pass
+    let mut reservation_table = ReservationTable::new();
+    reservation_table.reserve_path(&[((2, 2), 3), ((2, 1), 2), ((2, 0), 1)]);
+
+    let mut planner = SpaceTimeAStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    planner.update_reservation_table(&reservation_table);
+
+    let timed_plan = planner.generate_timed_plan();
+
+    assert_eq!(timed_plan.path.first(), Some(&(goal_cell, 5)));
+    assert_valid_timed_path(&timed_plan.path, &reservation_table);
+}
+
+#[test]
+fn test_space_time_a_star_no_swapping() {
+    let gridmap = Gridmap::new(2, 1, 0.05);
+
+    // The other robot moves into the start cell while the planned one would move
+    // into its cell, which would have them swap places
+    let mut reservation_table = ReservationTable::new();
+    reservation_table.reserve_path(&[((0, 0), 1), ((1, 0), 0)]);
+
+    let mut planner = SpaceTimeAStarPlanner::new(&gridmap);
+    planner.update_start_and_goal((0, 0), (1, 0));
+    planner.update_reservation_table(&reservation_table);
+
+    let timed_plan = planner.generate_timed_plan();
+
+    assert_eq!(timed_plan.path.len(), 0);
+}
+
+#[test]
+fn test_space_time_a_star_goal_reservations() {
+    let gridmap = Gridmap::new(5, 3, 0.05);
+    let (start_cell, goal_cell) = ((0, 1), (4, 1));
+
+    // The goal is passed through later on, so the robot may not settle there before
+    let mut reservation_table = ReservationTable::new();
+    reservation_table.reserve_cell(goal_cell, 10);
+
+    let mut planner = SpaceTimeAStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    planner.update_reservation_table(&reservation_table);
+
+    let timed_plan = planner.generate_timed_plan();
+
+    assert_eq!(timed_plan.path.first(), Some(&(goal_cell, 11)));
+    assert_valid_timed_path(&timed_plan.path, &reservation_table);
+
+    // A goal held for good by another robot can never be reached
+    reservation_table.reserve_cell_from(goal_cell, 20);
+    planner.update_reservation_table(&reservation_table);
+
+    assert_eq!(planner.generate_timed_plan().path.len(), 0);
+}
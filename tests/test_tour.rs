@@ -0,0 +1,113 @@
+mod common;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use ruth_planner::maps::builder;
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::tour::*;
+use ruth_planner::Gridmap;
+
+#[test]
+fn test_tour_plan_open() {
+    let gridmap = Gridmap::new(10, 10, 0.05);
+    let waypoints = [(0, 0), (9, 9), (1, 1), (5, 5), (2, 2)];
+
+    let mut planner: TourPlanner<AStarPlanner> = TourPlanner::new(&gridmap);
+    assert!(planner.update_waypoints(&waypoints));
+
+    let tour_plan = planner.generate_plan();
+
+    // Waypoints all lie on the diagonal, so they are visited in order along it
+    assert_eq!(tour_plan.order, vec![0, 2, 4, 3, 1]);
+    assert_eq!(tour_plan.cost, 9 * 141);
+    assert_eq!(tour_plan.motion_plan.path.first(), Some(&(9, 9)));
+    assert_eq!(tour_plan.motion_plan.path.last(), Some(&(0, 0)));
+    assert_eq!(tour_plan.motion_plan.path.len(), 10);
+}
+
+#[test]
+fn test_tour_plan_return_to_start() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let waypoints = [(0, 9), (9, 0), (0, 0), (9, 9)];
+
+    let mut planner: TourPlanner<AStarPlanner> = TourPlanner::new(&gridmap);
+    planner.update_waypoints(&waypoints);
+    planner.update_return_to_start(true);
+
+    let tour_plan = planner.generate_plan();
+
+    assert_eq!(tour_plan.order.len(), 4);
+    assert_eq!(tour_plan.motion_plan.path.first(), Some(&(0, 9)));
+    assert_eq!(tour_plan.motion_plan.path.last(), Some(&(0, 9)));
+    for waypoint in waypoints {
+        assert!(tour_plan.motion_plan.path.contains(&waypoint));
+    }
+}
+
+#[test]
+fn test_tour_plan_unreachable() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_1_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+
+    let mut planner: TourPlanner<AStarPlanner> = TourPlanner::new(&gridmap);
+    planner.update_waypoints(&[(0, 9), (9, 0), (0, 0)]);
+
+    let tour_plan = planner.generate_plan();
+
+    assert_eq!(tour_plan.order.len(), 0);
+    assert_eq!(tour_plan.motion_plan.path.len(), 0);
+
+    assert!(!planner.update_waypoints(&[(10, 0)]));
+    assert!(!planner.update_max_exact_waypoints(30));
+}
+
+#[test]
+fn test_tour_heuristic_matches_exact() {
+    let mut rng = StdRng::seed_from_u64(7);
+
+    for _ in 0..10 {
+        let points: Vec<(f32, f32)> = (0..9)
+            .map(|_| (rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0)))
+            .collect();
+        let cost_matrix: Vec<Vec<u32>> = points
+            .iter()
+            .map(|a| points.iter().map(|b| (a.0 - b.0).hypot(a.1 - b.1) as u32).collect())
+            .collect();
+
+        for return_to_start in [false, true] {
+            let exact = solve_tour_exact(&cost_matrix, return_to_start).unwrap();
+            let heuristic = solve_tour_heuristic(&cost_matrix, return_to_start).unwrap();
+
+            let exact_cost = get_tour_cost(&cost_matrix, &exact, return_to_start).unwrap();
+            let heuristic_cost = get_tour_cost(&cost_matrix, &heuristic, return_to_start).unwrap();
+
+            assert_eq!(exact[0], 0);
+            assert_eq!(heuristic[0], 0);
+            assert!(heuristic_cost >= exact_cost);
+            assert!((heuristic_cost as f32) < exact_cost as f32 * 1.1);
+        }
+    }
+}
+
+#[test]
+fn test_tour_plan_heuristic() {
+    let gridmap =
+        builder::build_gridmap_from_pgm("test_maps/nav2_maps/ostc_map.pgm").unwrap();
+    let waypoints = [(5, 5), (60, 35), (20, 50), (70, 10), (40, 40)];
+
+    let mut planner: TourPlanner<AStarPlanner> = TourPlanner::new(&gridmap);
+    planner.update_waypoints(&waypoints);
+
+    let exact_plan = planner.generate_plan();
+
+    planner.update_max_exact_waypoints(0);
+    let heuristic_plan = planner.generate_plan();
+
+    assert_eq!(exact_plan.motion_plan.path.last(), Some(&(5, 5)));
+    assert!(heuristic_plan.cost >= exact_plan.cost);
+    for waypoint in waypoints {
+        assert!(heuristic_plan.motion_plan.path.contains(&waypoint));
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use ruth_planner::a_star::AStarPlanner;
+use ruth_planner::maps::builder;
+use ruth_planner::planners::planner_base::Planner;
+use ruth_planner::planners::planner_common::Neighborhood;
+use ruth_planner::validation::*;
+use ruth_planner::Gridmap;
+
+#[test]
+fn test_validation_planned_path() {
+    let arr_map: Vec<Vec<u8>> = common::create_maze_0_arr_map();
+    let gridmap = builder::build_gridmap_from_2d_arr(&arr_map).unwrap();
+    let (start_cell, goal_cell) = ((0, 9), (9, 0));
+
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_start_and_goal(start_cell, goal_cell);
+    let path = planner.generate_plan().path;
+
+    let validation = validate_path(&gridmap, &path, &Neighborhood::EightWay, 0.0);
+    assert!(validation.is_valid(), "{}", validation);
+    assert!(validation.endpoints_match(start_cell, goal_cell));
+    assert!(!validation.endpoints_match(goal_cell, start_cell));
+    assert_eq!(validation.to_string(), "path is valid");
+
+    // A* moves diagonally, which four way moves cannot follow
+    let validation = validate_path(&gridmap, &path, &Neighborhood::FourWay, 0.0);
+    assert!(validation.first_invalid_step.is_some());
+    assert!(!validation.is_valid());
+}
+
+#[test]
+fn test_validation_corner_cutting() {
+    let mut gridmap = Gridmap::new(3, 3, 0.05);
+    gridmap.set_val_xy(254, (1, 0));
+
+    // The diagonal from (0, 0) to (1, 1) passes the corner of the obstacle
+    let path = vec![(0, 0), (1, 1), (2, 2)];
+    assert!(validate_path(&gridmap, &path, &Neighborhood::EightWay, 0.0).is_valid());
+    let validation = validate_path(&gridmap, &path, &Neighborhood::EightWayNoCornerCutting, 0.0);
+    assert_eq!(validation.first_invalid_step, Some(1));
+
+    // Without corner cutting, A* steps around the corner
+    let mut planner = AStarPlanner::new(&gridmap);
+    planner.update_neighborhood(Neighborhood::EightWayNoCornerCutting);
+    planner.update_start_and_goal((0, 0), (2, 2));
+    let path = planner.generate_plan().path;
+
+    assert_eq!(path.len(), 4);
+    assert!(validate_path(&gridmap, &path, &Neighborhood::EightWayNoCornerCutting, 0.0).is_valid());
+}
+
+#[test]
+fn test_validation_problems() {
+    let mut gridmap = Gridmap::new(10, 10, 0.05);
+    gridmap.set_val_xy(254, (5, 2));
+
+    // Jumps from (3, 3) to (5, 3), then passes through the obstacle and off the map
+    let path = vec![(1, 1), (2, 2), (2, 2), (3, 3), (5, 3), (5, 2), (5, 1), (5, 0), (5, u32::MAX)];
+    let validation = validate_path(&gridmap, &path, &Neighborhood::EightWay, 0.0);
+
+    assert_eq!(validation.first_invalid_step, Some(4));
+    assert_eq!(validation.first_collision, Some(5));
+    assert_eq!(validation.out_of_bounds, vec![8]);
+    assert_eq!(validation.endpoints, Some(((1, 1), (5, u32::MAX))));
+    assert!(!validation.is_valid());
+    assert_eq!(
+        validation.to_string(),
+        "point 4 is not a single move from the one before it, point 5 collides with an obstacle, \
+         1 points lie outside the map, the first being point 8"
+    );
+
+    let validation = validate_path(&gridmap, &[], &Neighborhood::EightWay, 0.0);
+    assert!(!validation.is_valid() && !validation.endpoints_match((0, 0), (0, 0)));
+    assert_eq!(validation.to_string(), "path is empty");
+}
+
+#[test]
+fn test_validation_footprint() {
+    let mut gridmap = Gridmap::new(10, 10, 0.05);
+    gridmap.set_val_xy(254, (5, 5));
+    let path: Vec<(u32, u32)> = (0..10).map(|x| (x, 7)).collect();
+
+    // The path passes two cells below the obstacle
+    assert!(validate_path(&gridmap, &path, &Neighborhood::FourWay, 0.0).is_valid());
+    assert!(validate_path(&gridmap, &path, &Neighborhood::FourWay, 1.9).is_valid());
+    assert_eq!(validate_path(&gridmap, &path, &Neighborhood::FourWay, 2.0).first_collision, Some(5));
+    assert_eq!(validate_path(&gridmap, &path, &Neighborhood::FourWay, 2.3).first_collision, Some(4));
+
+    // Footprints reaching beyond the map are not collisions
+    assert!(validate_path(&Gridmap::new(3, 3, 0.05), &[(0, 0), (1, 1)], &Neighborhood::EightWay, 5.0).is_valid());
+}